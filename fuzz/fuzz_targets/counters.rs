@@ -0,0 +1,22 @@
+//! Feeds arbitrary bytes through every counter. Counting must never panic, whatever the input,
+//! and the counts must stay consistent with each other.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|content: &[u8]| {
+    let lines = wcx_core::count_lines(content);
+    let newlines = wcx_core::count_newlines(content);
+    assert!(lines == newlines || lines == newlines + 1);
+    assert!(lines <= content.len());
+
+    // Characters and words are only counted for valid UTF-8, as wcx reports an error otherwise.
+    if let Ok(text) = std::str::from_utf8(content) {
+        let chars = wcx_core::count_chars(text);
+        let words = wcx_core::count_words(text);
+        let strict_words = wcx_core::count_strict_words(text);
+        assert!(chars <= content.len());
+        assert!(words <= chars);
+        assert!(strict_words <= words);
+    }
+});
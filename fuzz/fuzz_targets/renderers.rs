@@ -0,0 +1,24 @@
+//! Counts arbitrary files and renders them in every output format. Rendering must never panic,
+//! whatever the file names and contents, and the JSON output must stay valid JSON.
+//!
+//! The first byte picks the layout (pivoted, row numbers, no file names); the rest is split
+//! into files at NUL bytes, each a name line followed by its content. Odd files are counted as
+//! archive entries, so the subtotals are rendered too.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&layout, data)) = data.split_first() else {
+        return;
+    };
+    let files: Vec<(&[u8], &[u8])> = data
+        .split(|&byte| byte == 0)
+        .map(|file| match file.iter().position(|&byte| byte == b'\n') {
+            Some(end) => (&file[..end], &file[end + 1..]),
+            None => (file, &[][..]),
+        })
+        .collect();
+
+    wcx::fuzzing::render_every_format(layout, &files);
+});
@@ -0,0 +1,75 @@
+/// Returns what `name` stands for among `candidates` (pairs of a name and its value): the
+/// candidate it names exactly, or else every candidate it is a prefix of. Names are compared
+/// case-insensitively, with `-` and `_` treated alike. Candidates sharing a value are listed once.
+pub fn matches<'a, T: Copy + PartialEq>(
+    name: &str,
+    candidates: &[(&'a str, T)],
+) -> Vec<(&'a str, T)> {
+    let normalize = |name: &str| name.to_lowercase().replace('-', "_");
+    let name = normalize(name);
+
+    if let Some(exact) = candidates
+        .iter()
+        .find(|(candidate, _)| normalize(candidate) == name)
+    {
+        return vec![*exact];
+    }
+
+    let mut found: Vec<(&str, T)> = Vec::new();
+    for (candidate, value) in candidates {
+        if normalize(candidate).starts_with(&name) && !found.iter().any(|(_, v)| v == value) {
+            found.push((candidate, *value));
+        }
+    }
+    found
+}
+
+/// Resolves `name` among `candidates` like [`matches`], failing when it names none of them or
+/// is ambiguous. `what` describes the candidates in errors, e.g. `column`.
+pub fn resolve<T: Copy + PartialEq>(
+    name: &str,
+    candidates: &[(&str, T)],
+    what: &str,
+) -> anyhow::Result<T> {
+    let found = matches(name, candidates);
+
+    match found.as_slice() {
+        [(_, value)] => Ok(*value),
+        [] => anyhow::bail!(
+            "Unknown {} `{}` (expected one of: {})",
+            what,
+            name,
+            names(candidates.iter().copied())
+        ),
+        _ => anyhow::bail!(
+            "Ambiguous {} `{}`: could be {}",
+            what,
+            name,
+            names(found.into_iter())
+        ),
+    }
+}
+
+fn names<'a, T>(candidates: impl Iterator<Item = (&'a str, T)>) -> String {
+    candidates
+        .map(|(name, _)| name)
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+#[test]
+fn test_resolve_exact_alias_and_prefix() {
+    let candidates = [("words", 1), ("word_len", 2), ("lines", 3), ("loc", 3)];
+
+    assert_eq!(resolve("words", &candidates, "column").unwrap(), 1);
+    assert_eq!(resolve("word-len", &candidates, "column").unwrap(), 2);
+    assert_eq!(resolve("LOC", &candidates, "column").unwrap(), 3);
+    assert_eq!(resolve("l", &candidates, "column").unwrap(), 3);
+
+    let error = resolve("wor", &candidates, "column").expect_err("Ambiguous");
+    assert_eq!(
+        error.to_string(),
+        "Ambiguous column `wor`: could be words, word_len"
+    );
+    assert!(resolve("pages", &candidates, "column").is_err());
+}
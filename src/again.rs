@@ -0,0 +1,96 @@
+use crate::cli::Args;
+use crate::state::state_dir;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::ffi::OsString;
+use std::fs::{read_to_string, rename, write};
+use std::path::PathBuf;
+
+const LAST_RUN_FILE: &str = "last_run.json";
+
+/// The arguments of a counting run and the directory it ran in, so `wcx --again` can repeat it
+/// from any shell.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct LastRun {
+    pub dir: PathBuf,
+    pub args: Vec<String>,
+}
+
+impl LastRun {
+    /// The arguments this process was started with. `None` when one of them is not valid
+    /// UTF-8, as such a run cannot be recorded faithfully.
+    pub fn current() -> Option<LastRun> {
+        Some(LastRun {
+            dir: env::current_dir().ok()?,
+            args: env::args_os()
+                .skip(1)
+                .map(|arg| arg.into_string().ok())
+                .collect::<Option<Vec<String>>>()?,
+        })
+    }
+
+    /// Parses the arguments, profile and environment included, in the current directory.
+    fn parse(&self) -> anyhow::Result<Args> {
+        Args::parse_resolved(
+            std::iter::once("wcx")
+                .chain(self.args.iter().map(String::as_str))
+                .map(OsString::from)
+                .collect(),
+        )
+    }
+}
+
+/// Records `run` as the one `--again` repeats.
+pub fn save(run: &LastRun) -> anyhow::Result<()> {
+    let path = state_dir()?.join(LAST_RUN_FILE);
+    let tmp = path.with_extension("json.tmp");
+    write(&tmp, serde_json::to_vec_pretty(run)?)?;
+    rename(&tmp, &path)?;
+
+    Ok(())
+}
+
+/// Loads the previous counting run, moves into the directory it ran in, and returns it along
+/// with its parsed arguments.
+pub fn load() -> anyhow::Result<(LastRun, Args)> {
+    let path = state_dir()?.join(LAST_RUN_FILE);
+    if !path.exists() {
+        anyhow::bail!("No previous run to repeat");
+    }
+
+    let run: LastRun = serde_json::from_str(&read_to_string(&path)?)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    env::set_current_dir(&run.dir)
+        .with_context(|| format!("Failed to enter {}", run.dir.display()))?;
+    eprintln!("wcx {}", run.args.join(" "));
+    let args = run.parse()?;
+
+    Ok((run, args))
+}
+
+#[test]
+fn test_last_run_parses_its_arguments() {
+    let run = LastRun {
+        dir: PathBuf::from("/tmp"),
+        args: ["-w", "--mode", "mail", "inbox.mbox"]
+            .map(String::from)
+            .to_vec(),
+    };
+
+    let args = run.parse().expect("Failed to parse");
+
+    assert!(args.words_enabled);
+    assert_eq!(args.mode, crate::cli::Mode::Mail);
+    assert_eq!(args.files, vec![PathBuf::from("inbox.mbox")]);
+}
+
+#[test]
+fn test_again_ignores_the_environment() {
+    let argv = vec![OsString::from("wcx"), OsString::from("--again")];
+    let args = Args::parse_resolved_from(argv, |name| {
+        (name == "WCX_ASCII").then(|| String::from("1"))
+    });
+
+    assert!(args.expect("Failed to parse").again);
+}
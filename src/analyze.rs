@@ -1,82 +1,1139 @@
-use std::fs::{metadata, read_to_string, File};
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use crate::balance::{self, Balance};
+use crate::banned::BannedWords;
+use crate::bom::{self, Bom};
+use crate::cli::{ByteSize, EolStyle, IncompleteLine, Metric, WordMode};
+use crate::confusables;
+use crate::eol::{count_lines, LineEndings};
+use crate::fields::{count_fields, FieldCounter};
+use crate::generated::{self, Detector, Generated};
+use crate::paths;
+#[cfg(feature = "spell")]
+use crate::spell::Dictionary;
+use crate::stats::{self, Stats};
+use crate::suspicious;
+use crate::whitespace::Whitespace;
+use crate::wrap::{self, DisplayLines, LongestLine, PageSize};
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{File, Metadata};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use wcx_core::WcxError;
 
-#[derive(Default)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FileResult {
     pub lines: usize,
     pub bytes: usize,
     pub words: usize,
     pub chars: usize,
+    /// Lines of text wrapped at the `--wrap-width`; only counted with `--logical-lines`.
+    pub display_lines: usize,
+    /// Display columns of the longest line, like `wc -L`; only measured with `-L`.
+    pub max_line_length: usize,
+    /// Printed pages the text fills; only counted with `--print-pages`.
+    pub pages: usize,
+    /// Records separated by the `--record-delim` byte, or whole records of `--record-size`.
+    pub records: usize,
+    /// Bytes left over after the last whole record of `--record-size`.
+    pub partial_record: usize,
+    /// Non-blank lines split into fields with `--fields-delim`, and their fields.
+    pub field_lines: usize,
+    pub fields: usize,
+    /// The fewest and most fields of those lines; `None` without any.
+    pub min_fields: Option<usize>,
+    pub max_fields: usize,
+    /// Bytes of the file that hold data rather than holes; only measured with `--sparse`.
+    pub data_bytes: usize,
+    /// Characters inside the counted words, excluding the whitespace between them.
+    pub word_chars: usize,
+    /// Characters of the longest counted word; only measured with `--longest-word`.
+    pub longest_word: usize,
+    /// The start of that word, with `--longest-word=text`.
+    pub longest_word_text: Option<String>,
+    /// Whitespace characters by kind; only counted with `--whitespace-stats`.
+    pub whitespace: Whitespace,
+    /// Zero-width and bidirectional control characters; only counted with `--suspicious-chars`.
+    pub suspicious_chars: usize,
+    /// Letters and digits outside ASCII that look like ASCII ones; only counted with
+    /// `--confusables`.
+    pub confusables: usize,
+    /// Prose words missing from the dictionary; only counted with `--spell`.
+    pub misspelled: usize,
+    /// Matches of the terms of `--banned-words-file`.
+    pub banned: usize,
+    /// Quotes and brackets without a partner; only counted with `--balance`.
+    pub unbalanced: usize,
+    /// Whether the file has holes, i.e. less data than its apparent size.
+    pub sparse: bool,
+    /// Why the file looks machine-generated; only checked with `--detect-generated`.
+    pub generated: Option<Generated>,
+    /// The byte order mark the file starts with; only checked with `--detect-bom`.
+    pub bom: Option<Bom>,
+    /// HTTP status of a fetched URL.
+    pub status: Option<u16>,
+    /// Content type a fetched URL was served with.
+    pub content_type: Option<String>,
+    /// Why the source could not be counted; its counts are then all zero.
+    pub error: Option<String>,
+    /// Structural measurements of a format `--mode`, such as the keys of a config file.
+    pub stats: Stats,
 }
 
-pub fn analyze_file(
-    file: &PathBuf,
-    lines_enabled: bool,
-    bytes_enabled: bool,
-    chars_enabled: bool,
-    words_enabled: bool,
-) -> anyhow::Result<FileResult> {
+impl FileResult {
+    /// Adds the counts of `other` to these counts. Markers like `sparse` are not combined.
+    pub fn add(&mut self, other: &FileResult) {
+        self.lines += other.lines;
+        self.bytes += other.bytes;
+        self.chars += other.chars;
+        self.words += other.words;
+        self.display_lines += other.display_lines;
+        self.max_line_length = self.max_line_length.max(other.max_line_length);
+        self.pages += other.pages;
+        self.records += other.records;
+        self.field_lines += other.field_lines;
+        self.fields += other.fields;
+        self.min_fields = match (self.min_fields, other.min_fields) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.max_fields = self.max_fields.max(other.max_fields);
+        self.word_chars += other.word_chars;
+        self.whitespace.add(&other.whitespace);
+        self.suspicious_chars += other.suspicious_chars;
+        self.confusables += other.confusables;
+        self.misspelled += other.misspelled;
+        self.banned += other.banned;
+        self.unbalanced += other.unbalanced;
+        if other.longest_word > self.longest_word {
+            self.longest_word = other.longest_word;
+            self.longest_word_text = other.longest_word_text.clone();
+        }
+        self.data_bytes += other.data_bytes;
+        stats::add(&mut self.stats, &other.stats);
+    }
+
+    /// Returns the count of a single metric.
+    pub fn count(&self, metric: Metric) -> usize {
+        match metric {
+            Metric::Lines => self.lines,
+            Metric::Bytes => self.bytes,
+            Metric::Chars => self.chars,
+            Metric::Words => self.words,
+        }
+    }
+}
+
+/// Reads a whole file.
+pub fn read(file: &Path) -> Result<Vec<u8>, WcxError> {
+    std::fs::read(file).map_err(WcxError::io(file))
+}
+
+/// Reads the metadata of a file, following symlinks.
+pub fn metadata(file: &Path) -> Result<Metadata, WcxError> {
+    std::fs::metadata(file).map_err(WcxError::io(file))
+}
+
+/// Selects which counts [`analyze_file`] computes, and how bytes are measured.
+#[derive(Default, Clone, Debug)]
+pub struct AnalyzeOptions {
+    pub lines: bool,
+    pub bytes: bool,
+    pub chars: bool,
+    pub words: bool,
+    pub byte_size: ByteSize,
+    pub word_mode: WordMode,
+    pub incomplete_line: IncompleteLine,
+    pub eol_style: EolStyle,
+    /// The byte separating records, when records are counted.
+    pub record_delim: Option<u8>,
+    /// The length of fixed-length records, when they are counted instead.
+    pub record_size: Option<usize>,
+    /// The byte separating fields, when fields per line are counted.
+    pub fields_delim: Option<u8>,
+    /// The width lines wrap at, when display lines are counted.
+    pub wrap_width: Option<usize>,
+    /// The printed page, when pages are counted.
+    pub page_size: Option<PageSize>,
+    pub max_line_length: bool,
+    pub word_chars: bool,
+    pub longest_word: bool,
+    /// Keeps the start of the longest word, not only its length.
+    pub longest_word_text: bool,
+    pub whitespace_stats: bool,
+    pub suspicious_chars: bool,
+    pub confusables: bool,
+    /// The dictionary to count misspelled words with. Loaded once for the whole run.
+    #[cfg(feature = "spell")]
+    pub spell: Option<Arc<Dictionary>>,
+    /// The terms to count matches of. Loaded once for the whole run.
+    pub banned_words: Option<Arc<BannedWords>>,
+    pub balance: bool,
+    pub sparse: bool,
+    pub detect_generated: bool,
+    pub detect_bom: bool,
+    /// Counts a UTF-8 byte order mark as a character, instead of leaving it out of the
+    /// characters and words.
+    pub keep_bom: bool,
+    /// Maps every file [`analyze_file`] counts into memory, not only those of
+    /// [`MMAP_THRESHOLD`] bytes or more.
+    #[cfg(feature = "mmap")]
+    pub mmap: bool,
+}
+
+impl AnalyzeOptions {
+    /// Whether any of the counts needs the content decoded as UTF-8.
+    pub fn decodes(&self) -> bool {
+        self.chars
+            || self.words
+            || self.word_chars
+            || self.longest_word
+            || self.whitespace_stats
+            || self.suspicious_chars
+            || self.confusables
+            || self.spells()
+            || self.banned_words.is_some()
+            || self.balance
+            || self.wrap_width.is_some()
+            || self.page_size.is_some()
+    }
+
+    /// Whether misspelled words are counted, with `--spell`.
+    #[cfg(feature = "spell")]
+    pub fn spells(&self) -> bool {
+        self.spell.is_some()
+    }
+
+    #[cfg(not(feature = "spell"))]
+    pub fn spells(&self) -> bool {
+        false
+    }
+}
+
+/// Counts a regular file in a single pass: it is opened once and read in 64 KiB chunks through
+/// a [`Session`], which updates every count asked for as the bytes go by, so even files of
+/// several GB are never held in memory. Files are not read at all when only their size is
+/// asked for: the byte count, fixed-length records and data extent come from the metadata.
+/// Large files, or every file with `mmap`, are mapped into memory and counted as one slice.
+pub fn analyze_file(file: &PathBuf, options: &AnalyzeOptions) -> Result<FileResult, WcxError> {
+    let reads = options.lines
+        || options.record_delim.is_some()
+        || options.fields_delim.is_some()
+        || options.decodes()
+        || options.max_line_length
+        || options.detect_generated
+        || options.detect_bom;
+    let mut file_result = match reads {
+        true => {
+            let reader = File::open(file).map_err(WcxError::io(file))?;
+            let streamed = AnalyzeOptions {
+                bytes: false,
+                record_size: None,
+                sparse: false,
+                ..options.clone()
+            };
+            match map(file, &reader, options)? {
+                Some(mapped) => analyze_content(&paths::display(file), &mapped, &streamed)?,
+                None => analyze_reader(file, reader, &streamed, None)?,
+            }
+        }
+        false => FileResult::default(),
+    };
+
+    if options.bytes {
+        file_result.bytes = count_bytes_in_file(file, options.byte_size)?;
+    }
+
+    if let Some(size) = options.record_size {
+        let len = addressable(file, metadata(file)?.len())?;
+        count_fixed_records(&mut file_result, len, size);
+    }
+
+    if options.sparse {
+        let metadata = metadata(file)?;
+        let data_bytes = data_extent(file, &metadata);
+        file_result.data_bytes = addressable(file, data_bytes)?;
+        file_result.sparse = data_bytes < metadata.len();
+    }
+
+    Ok(file_result)
+}
+
+/// Files of this many bytes or more are mapped into memory by [`analyze_file`], even without
+/// `--mmap`: counting a mapped file saves copying every chunk out of the page cache.
+#[cfg(feature = "mmap")]
+pub const MMAP_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+/// Maps `file`, opened as `reader`, into memory if it should be; see [`MMAP_THRESHOLD`]. Empty
+/// files cannot be mapped, and are read like small ones.
+#[cfg(feature = "mmap")]
+fn map(
+    file: &Path,
+    reader: &File,
+    options: &AnalyzeOptions,
+) -> Result<Option<memmap2::Mmap>, WcxError> {
+    let len = reader.metadata().map_err(WcxError::io(file))?.len();
+    if len == 0 || !(options.mmap || len >= MMAP_THRESHOLD) {
+        return Ok(None);
+    }
+
+    // SAFETY: the map is only read, and dropped before `analyze_file` returns. Like with any
+    // tool mapping files, a file truncated by another process while it is counted makes the
+    // reads past its new end fault.
+    let mapped = unsafe { memmap2::Mmap::map(reader) }.map_err(WcxError::io(file))?;
+    Ok(Some(mapped))
+}
+
+#[cfg(not(feature = "mmap"))]
+fn map(
+    _file: &Path,
+    _reader: &File,
+    _options: &AnalyzeOptions,
+) -> Result<Option<Vec<u8>>, WcxError> {
+    Ok(None)
+}
+
+/// Counts `content` like [`analyze_content`], except that characters and words are only
+/// counted in the `selected` text fragments, such as the cells of some CSV columns.
+pub fn analyze_selected(
+    name: &str,
+    content: &[u8],
+    selected: &[String],
+    options: &AnalyzeOptions,
+) -> Result<FileResult, WcxError> {
+    let whole = AnalyzeOptions {
+        chars: false,
+        words: false,
+        word_chars: false,
+        longest_word: false,
+        whitespace_stats: false,
+        suspicious_chars: false,
+        confusables: false,
+        #[cfg(feature = "spell")]
+        spell: None,
+        banned_words: None,
+        balance: false,
+        wrap_width: None,
+        page_size: None,
+        max_line_length: false,
+        ..options.clone()
+    };
+    let mut file_result = analyze_content(name, content, &whole)?;
+    let mut page_lines = 0;
+
+    for fragment in selected {
+        if options.chars {
+            file_result.chars += wcx_core::count_chars(fragment);
+        }
+
+        if options.words {
+            file_result.words += count_words(fragment, options.word_mode);
+        }
+
+        if options.word_chars {
+            file_result.word_chars += count_word_chars(fragment, options.word_mode);
+        }
+
+        if options.longest_word {
+            note_longest_word(&mut file_result, fragment, options);
+        }
+
+        if options.whitespace_stats {
+            file_result.whitespace.add(&Whitespace::count(fragment));
+        }
+
+        if options.suspicious_chars {
+            file_result.suspicious_chars += suspicious::count(fragment);
+        }
+
+        if options.confusables {
+            file_result.confusables += confusables::count(fragment);
+        }
+
+        note_misspelled(&mut file_result, fragment, options);
+
+        if let Some(banned_words) = &options.banned_words {
+            file_result.banned += banned_words.count(fragment);
+        }
+
+        if options.balance {
+            file_result.unbalanced += balance::count(fragment);
+        }
+
+        if let Some(width) = options.wrap_width {
+            file_result.display_lines += wrap::count(fragment, width);
+        }
+
+        if let Some(page_size) = options.page_size {
+            page_lines += wrap::count(fragment, page_size.width);
+        }
+
+        if options.max_line_length {
+            file_result.max_line_length = file_result
+                .max_line_length
+                .max(wrap::longest_line(fragment));
+        }
+    }
+
+    if let Some(page_size) = options.page_size {
+        file_result.pages = page_size.pages(page_lines);
+    }
+
+    Ok(file_result)
+}
+
+/// How far an [`Analyzer`] got, passed to its progress callback after every file.
+#[derive(Clone, Copy, Debug)]
+pub struct Progress<'a> {
+    /// Files counted so far, the one just counted included.
+    pub done: usize,
+    pub total: usize,
+    pub file: &'a Path,
+}
+
+/// Asks a running [`Analyzer`] to stop. Clones share the same flag, so one can be handed to
+/// another thread (or a signal handler) and cancelled from there.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Counts a list of files, one by one or several at a time, so long scans can report how far
+/// they got and be stopped between two files.
+pub struct Analyzer<'a> {
+    files: &'a [PathBuf],
+    jobs: usize,
+}
+
+impl<'a> Analyzer<'a> {
+    pub fn new(files: &'a [PathBuf]) -> Analyzer<'a> {
+        Analyzer { files, jobs: 1 }
+    }
+
+    /// Counts up to `jobs` files at the same time, on a pool of that many threads. One, the
+    /// default, counts them in order on the calling thread.
+    pub fn jobs(&mut self, jobs: usize) -> &mut Self {
+        self.jobs = jobs.max(1);
+        self
+    }
+
+    /// Calls `count` on every file, typically [`analyze_file`], and `progress` after each, and
+    /// returns the results in the order of the files whatever order they were counted in. Stops
+    /// at the first error `count` returns, and with [`WcxError::Cancelled`] once `cancel` is
+    /// cancelled; files already being counted are finished first.
+    pub fn analyze_with<T: Send, E: From<WcxError> + Send>(
+        &self,
+        count: impl Fn(&'a PathBuf) -> Result<T, E> + Sync,
+        progress: impl Fn(Progress) + Sync,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<T>, E> {
+        let total = self.files.len();
+        let done = AtomicUsize::new(0);
+        let count_one = |file: &'a PathBuf| {
+            if cancel.is_cancelled() {
+                let done = done.load(Ordering::Relaxed);
+                return Err(WcxError::Cancelled { done, total }.into());
+            }
+
+            let result = count(file)?;
+            progress(Progress {
+                done: done.fetch_add(1, Ordering::Relaxed) + 1,
+                total,
+                file,
+            });
+            Ok(result)
+        };
+
+        // Without a pool (which only fails to build when threads cannot be spawned), the files
+        // are counted one by one.
+        let pool = match self.jobs {
+            1 => None,
+            jobs => ThreadPoolBuilder::new().num_threads(jobs).build().ok(),
+        };
+        match pool {
+            Some(pool) => pool.install(|| self.files.par_iter().map(count_one).collect()),
+            None => self.files.iter().map(count_one).collect(),
+        }
+    }
+}
+
+/// Kinds of non-regular files, which are not counted like ordinary files: reading them may block
+/// forever, never end, or not be possible at all.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpecialFile {
+    Fifo,
+    CharDevice,
+    BlockDevice,
+    Socket,
+}
+
+impl SpecialFile {
+    /// Returns the kind of special file `metadata` describes, or `None` for regular files and
+    /// directories.
+    #[cfg(unix)]
+    pub fn detect(metadata: &Metadata) -> Option<SpecialFile> {
+        use std::os::unix::fs::FileTypeExt;
+
+        let file_type = metadata.file_type();
+        if file_type.is_fifo() {
+            Some(SpecialFile::Fifo)
+        } else if file_type.is_char_device() {
+            Some(SpecialFile::CharDevice)
+        } else if file_type.is_block_device() {
+            Some(SpecialFile::BlockDevice)
+        } else if file_type.is_socket() {
+            Some(SpecialFile::Socket)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn detect(_metadata: &Metadata) -> Option<SpecialFile> {
+        None
+    }
+
+    /// FIFOs and character devices can be read as a stream with `--read-special`.
+    pub fn streamable(&self) -> bool {
+        matches!(self, SpecialFile::Fifo | SpecialFile::CharDevice)
+    }
+}
+
+impl fmt::Display for SpecialFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            SpecialFile::Fifo => "FIFO",
+            SpecialFile::CharDevice => "character device",
+            SpecialFile::BlockDevice => "block device",
+            SpecialFile::Socket => "socket",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Counts what `reader` yields, such as stdin or a FIFO, which can only be read once and may
+/// never end. The content is read a single time, stopping after `limit` bytes when a limit is
+/// given. It is counted as it arrives through a [`Session`]. `file` names it in error messages.
+pub fn analyze_reader(
+    file: &Path,
+    reader: impl Read,
+    options: &AnalyzeOptions,
+    limit: Option<usize>,
+) -> Result<FileResult, WcxError> {
+    let mut session = Session::new(&paths::display(file), options);
+    session.push_reader(file, reader, limit)?;
+    session.finish()
+}
+
+/// Counts content pushed piece by piece, such as data arriving over a socket, without keeping
+/// it: UTF-8 sequences and words split between two pushes are counted once, as if the content
+/// had come in one piece. Content that is not valid UTF-8 fails [`Session::finish`] when
+/// characters or words are counted.
+pub struct Session {
+    name: String,
+    options: AnalyzeOptions,
+    file_result: FileResult,
+    line_endings: LineEndings,
+    /// The `--record-delim` bytes pushed so far.
+    delimiters: usize,
+    fields: Option<FieldCounter>,
+    /// Tells machine-generated content apart, with `detect_generated`.
+    generated: Option<Detector>,
+    balance: Option<Balance>,
+    display_lines: Option<DisplayLines>,
+    /// The display lines at the page width, with `page_size`.
+    page_lines: Option<DisplayLines>,
+    longest_line: Option<LongestLine>,
+    last_byte: Option<u8>,
+    /// The start of a UTF-8 sequence the previous push ended in the middle of.
+    partial: Vec<u8>,
+    /// The word the previous push ended in, if it ended in one.
+    word: Option<Word>,
+    /// The first bytes pushed, enough to hold a byte order mark.
+    head: Vec<u8>,
+    /// Whether no text has been counted yet, so a byte order mark may still come.
+    at_start: bool,
+    /// The text after the last line break pushed, held back from spell checking and banned word
+    /// matching until its line is complete so no word is cut in two.
+    line: String,
+    error: Option<WcxError>,
+}
+
+/// A word being read by a [`Session`].
+struct Word {
+    chars: usize,
+    /// Whether it counts in strict mode: it has an alphanumeric character.
+    strict: bool,
+    /// Its start, when the longest word is shown.
+    excerpt: String,
+}
+
+impl Session {
+    /// Starts counting the content called `name` in error messages.
+    pub fn new(name: &str, options: &AnalyzeOptions) -> Session {
+        Session {
+            name: name.to_string(),
+            options: options.clone(),
+            file_result: FileResult::default(),
+            line_endings: LineEndings::new(options.eol_style),
+            delimiters: 0,
+            fields: options.fields_delim.map(FieldCounter::new),
+            generated: options.detect_generated.then(Detector::default),
+            balance: options.balance.then(Balance::default),
+            display_lines: options.wrap_width.map(DisplayLines::new),
+            page_lines: options
+                .page_size
+                .map(|page_size| DisplayLines::new(page_size.width)),
+            longest_line: options.max_line_length.then(LongestLine::default),
+            last_byte: None,
+            partial: Vec::new(),
+            word: None,
+            head: Vec::new(),
+            at_start: true,
+            line: String::new(),
+            error: None,
+        }
+    }
+
+    /// Pushes everything `reader` yields, in 64 KiB chunks, stopping after `limit` bytes like
+    /// [`analyze_reader`]. `file` names the reader in error messages.
+    pub fn push_reader(
+        &mut self,
+        file: &Path,
+        reader: impl Read,
+        limit: Option<usize>,
+    ) -> Result<(), WcxError> {
+        let mut reader = reader.take(limit.map_or(u64::MAX, |limit| limit as u64));
+        let mut buffer = vec![0; 64 * 1024];
+
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(read) => self.push_bytes(&buffer[..read]),
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(WcxError::io(file)(err)),
+            }
+        }
+    }
+
+    pub fn push_bytes(&mut self, mut bytes: &[u8]) {
+        self.file_result.bytes += bytes.len();
+        self.line_endings.push(bytes);
+        if let Some(delimiter) = self.options.record_delim {
+            self.delimiters += wcx_core::count_byte(bytes, delimiter);
+        }
+        if let Some(fields) = &mut self.fields {
+            fields.push(bytes);
+        }
+        if let Some(generated) = &mut self.generated {
+            generated.push(bytes);
+        }
+        if let Some(longest_line) = &mut self.longest_line {
+            longest_line.push_bytes(bytes);
+        }
+        self.last_byte = bytes.last().copied().or(self.last_byte);
+        if self.head.len() < 3 {
+            let missing = (3 - self.head.len()).min(bytes.len());
+            self.head.extend_from_slice(&bytes[..missing]);
+        }
+
+        if !self.options.decodes() || self.error.is_some() {
+            return;
+        }
+
+        if !self.partial.is_empty() {
+            let missing = utf8_width(self.partial[0]) - self.partial.len();
+            let (rest, next) = bytes.split_at(missing.min(bytes.len()));
+            self.partial.extend_from_slice(rest);
+            bytes = next;
+            if self.partial.len() < utf8_width(self.partial[0]) {
+                return;
+            }
+
+            let sequence = std::mem::take(&mut self.partial);
+            match std::str::from_utf8(&sequence) {
+                Ok(text) => self.count_text(text),
+                Err(source) => return self.fail(source),
+            }
+        }
+
+        match std::str::from_utf8(bytes) {
+            Ok(text) => self.count_text(text),
+            Err(source) => {
+                let (valid, rest) = bytes.split_at(source.valid_up_to());
+                // SAFETY: `from_utf8` checked the bytes up to `valid_up_to`.
+                self.count_text(unsafe { std::str::from_utf8_unchecked(valid) });
+                match source.error_len() {
+                    None => self.partial = rest.to_vec(),
+                    Some(_) => self.fail(source),
+                }
+            }
+        }
+    }
+
+    /// Returns the counts of everything pushed.
+    pub fn finish(mut self) -> Result<FileResult, WcxError> {
+        self.end_word();
+        let line = std::mem::take(&mut self.line);
+        self.match_words(&line);
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        // A sequence still missing bytes at the end is as invalid as a stray byte.
+        if let Err(source) = std::str::from_utf8(&self.partial) {
+            return Err(WcxError::Decode {
+                name: self.name,
+                source,
+            });
+        }
+
+        let options = &self.options;
+        let mut file_result = FileResult {
+            chars: self.file_result.chars,
+            words: self.file_result.words,
+            word_chars: self.file_result.word_chars,
+            longest_word: self.file_result.longest_word,
+            longest_word_text: self.file_result.longest_word_text,
+            whitespace: self.file_result.whitespace,
+            suspicious_chars: self.file_result.suspicious_chars,
+            confusables: self.file_result.confusables,
+            misspelled: self.file_result.misspelled,
+            banned: self.file_result.banned,
+            ..FileResult::default()
+        };
+        if options.lines {
+            file_result.lines = self.line_endings.lines(options.incomplete_line);
+        }
+        if options.bytes {
+            file_result.bytes = self.file_result.bytes;
+        }
+        if let Some(delimiter) = options.record_delim {
+            file_result.records =
+                self.delimiters + usize::from(self.last_byte.is_some_and(|byte| byte != delimiter));
+        }
+        if let Some(size) = options.record_size {
+            count_fixed_records(&mut file_result, self.file_result.bytes, size);
+        }
+        if let Some(fields) = self.fields {
+            fields.finish(&mut file_result);
+        }
+        if let Some(generated) = self.generated {
+            file_result.generated = generated.finish();
+        }
+        if let Some(balance) = self.balance {
+            file_result.unbalanced = balance.finish();
+        }
+        if let Some(display_lines) = self.display_lines {
+            file_result.display_lines = display_lines.finish();
+        }
+        if let (Some(page_lines), Some(page_size)) = (self.page_lines, options.page_size) {
+            file_result.pages = page_size.pages(page_lines.finish());
+        }
+        if let Some(longest_line) = self.longest_line {
+            file_result.max_line_length = longest_line.finish();
+        }
+        if options.sparse {
+            file_result.data_bytes = self.file_result.bytes;
+        }
+        if options.detect_bom {
+            file_result.bom = bom::detect(&self.head);
+        }
+
+        Ok(file_result)
+    }
+
+    fn count_text(&mut self, mut text: &str) {
+        if self.at_start && !text.is_empty() {
+            self.at_start = false;
+            if !self.options.keep_bom {
+                text = bom::strip(text);
+            }
+        }
+        if self.options.chars {
+            self.file_result.chars += wcx_core::count_chars(text);
+        }
+        if self.options.whitespace_stats {
+            self.file_result.whitespace.add(&Whitespace::count(text));
+        }
+        if self.options.suspicious_chars {
+            self.file_result.suspicious_chars += suspicious::count(text);
+        }
+        if self.options.confusables {
+            self.file_result.confusables += confusables::count(text);
+        }
+        if let Some(balance) = &mut self.balance {
+            balance.push(text);
+        }
+        if let Some(display_lines) = &mut self.display_lines {
+            display_lines.push(text);
+        }
+        if let Some(page_lines) = &mut self.page_lines {
+            page_lines.push(text);
+        }
+        if self.options.spells() || self.options.banned_words.is_some() {
+            self.line.push_str(text);
+            if let Some(end) = self.line.rfind('\n') {
+                let lines: String = self.line.drain(..=end).collect();
+                self.match_words(&lines);
+            }
+        }
+        if !(self.options.words || self.options.word_chars || self.options.longest_word) {
+            return;
+        }
+
+        for char in text.chars() {
+            if char.is_whitespace() {
+                self.end_word();
+            } else {
+                let word = self.word.get_or_insert(Word {
+                    chars: 0,
+                    strict: false,
+                    excerpt: String::new(),
+                });
+                word.chars += 1;
+                word.strict |= char.is_alphanumeric();
+                if self.options.longest_word_text && word.chars <= EXCERPT_CHARS + 1 {
+                    word.excerpt.push(char);
+                }
+            }
+        }
+    }
+
+    /// Spell checks and matches banned words in `text`, which ends at a line break or the end.
+    fn match_words(&mut self, text: &str) {
+        note_misspelled(&mut self.file_result, text, &self.options);
+        if let Some(banned_words) = &self.options.banned_words {
+            self.file_result.banned += banned_words.count(text);
+        }
+    }
+
+    fn end_word(&mut self) {
+        let Some(word) = self.word.take() else {
+            return;
+        };
+        if self.options.word_mode == WordMode::Whitespace || word.strict {
+            if self.options.words {
+                self.file_result.words += 1;
+            }
+            if self.options.word_chars {
+                self.file_result.word_chars += word.chars;
+            }
+            if self.options.longest_word && word.chars > self.file_result.longest_word {
+                self.file_result.longest_word = word.chars;
+                self.file_result.longest_word_text = self
+                    .options
+                    .longest_word_text
+                    .then(|| excerpt(&word.excerpt));
+            }
+        }
+    }
+
+    fn fail(&mut self, source: std::str::Utf8Error) {
+        self.error = Some(WcxError::Decode {
+            name: self.name.clone(),
+            source,
+        });
+    }
+}
+
+/// The length of the UTF-8 sequence starting with `first`.
+fn utf8_width(first: u8) -> usize {
+    match first {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    }
+}
+
+/// Reads what `reader` yields into memory the way [`analyze_reader`] does.
+pub fn read_reader(
+    file: &Path,
+    reader: impl Read,
+    limit: Option<usize>,
+) -> Result<Vec<u8>, WcxError> {
+    let mut content: Vec<u8> = Vec::new();
+
+    match limit {
+        Some(limit) => reader.take(limit as u64).read_to_end(&mut content),
+        None => BufReader::new(reader).read_to_end(&mut content),
+    }
+    .map_err(WcxError::io(file))?;
+
+    Ok(content)
+}
+
+/// Counts content that is already in memory, such as an archive entry. `name` is only used in
+/// error messages.
+pub fn analyze_content(
+    name: &str,
+    content: &[u8],
+    options: &AnalyzeOptions,
+) -> Result<FileResult, WcxError> {
     let mut file_result: FileResult = Default::default();
 
-    if lines_enabled {
-        let count = count_lines_in_file(file)?;
-        file_result.lines = count;
+    if options.lines {
+        file_result.lines = count_lines(content, options.eol_style, options.incomplete_line);
+    }
+
+    if options.bytes {
+        file_result.bytes = content.len();
     }
 
-    if bytes_enabled {
-        let count = count_bytes_in_file(file)?;
-        file_result.bytes = count;
+    if let Some(delimiter) = options.record_delim {
+        file_result.records = wcx_core::count_records(content, delimiter);
     }
 
-    if chars_enabled {
-        let count = count_chars_in_file(file);
-        file_result.chars = count;
+    if let Some(size) = options.record_size {
+        count_fixed_records(&mut file_result, content.len(), size);
     }
 
-    if words_enabled {
-        let count = count_words_in_file(file);
-        file_result.words = count;
+    if let Some(delimiter) = options.fields_delim {
+        count_fields(content, delimiter, &mut file_result);
+    }
+
+    if options.decodes() {
+        let decoded_string = std::str::from_utf8(content).map_err(|source| WcxError::Decode {
+            name: name.to_string(),
+            source,
+        })?;
+        let decoded_string = match options.keep_bom {
+            true => decoded_string,
+            false => bom::strip(decoded_string),
+        };
+
+        if options.chars {
+            file_result.chars = wcx_core::count_chars(decoded_string);
+        }
+
+        if options.words {
+            file_result.words = count_words(decoded_string, options.word_mode);
+        }
+
+        if options.word_chars {
+            file_result.word_chars = count_word_chars(decoded_string, options.word_mode);
+        }
+
+        if options.longest_word {
+            note_longest_word(&mut file_result, decoded_string, options);
+        }
+
+        if options.whitespace_stats {
+            file_result.whitespace = Whitespace::count(decoded_string);
+        }
+
+        if options.suspicious_chars {
+            file_result.suspicious_chars = suspicious::count(decoded_string);
+        }
+
+        if options.confusables {
+            file_result.confusables = confusables::count(decoded_string);
+        }
+
+        note_misspelled(&mut file_result, decoded_string, options);
+
+        if let Some(banned_words) = &options.banned_words {
+            file_result.banned = banned_words.count(decoded_string);
+        }
+
+        if options.balance {
+            file_result.unbalanced = balance::count(decoded_string);
+        }
+
+        if let Some(width) = options.wrap_width {
+            file_result.display_lines = wrap::count(decoded_string, width);
+        }
+
+        if let Some(page_size) = options.page_size {
+            file_result.pages = page_size.pages(wrap::count(decoded_string, page_size.width));
+        }
+    }
+
+    if options.max_line_length {
+        file_result.max_line_length = wrap::longest_line_bytes(content);
+    }
+
+    if options.detect_generated {
+        file_result.generated = generated::detect(content);
+    }
+
+    if options.detect_bom {
+        file_result.bom = bom::detect(content);
+    }
+
+    if options.sparse {
+        file_result.data_bytes = content.len();
     }
 
     Ok(file_result)
 }
 
-fn count_bytes_in_file(file: &PathBuf) -> anyhow::Result<usize> {
+/// Splits `len` bytes into records of `size` bytes and what is left of a last one cut short.
+fn count_fixed_records(file_result: &mut FileResult, len: usize, size: usize) {
+    file_result.records = len / size;
+    file_result.partial_record = len % size;
+}
+
+fn count_bytes_in_file(file: &Path, byte_size: ByteSize) -> Result<usize, WcxError> {
     let metadata = metadata(file)?;
-    let count = usize::try_from(metadata.len())?;
+    let len = match byte_size {
+        ByteSize::Apparent => metadata.len(),
+        ByteSize::Blocks => allocated_len(&metadata),
+    };
+    addressable(file, len)
+}
 
-    return Ok(count);
+/// Converts the size of `file` to a count, which fails on platforms with a 32-bit `usize`.
+fn addressable(file: &Path, len: u64) -> Result<usize, WcxError> {
+    usize::try_from(len).map_err(|_| WcxError::UnsupportedInput {
+        name: paths::display(file),
+        reason: format!("has {} bytes, more than this platform can count", len),
+    })
 }
 
-fn count_lines_in_file(file: &PathBuf) -> anyhow::Result<usize> {
-    let lines_reader = BufReader::new(File::open(file)?);
-    let count = lines_reader.lines().count();
+/// Returns the bytes actually allocated on disk for a file, like `du` does: whole blocks, and
+/// less than the apparent size for sparse files.
+#[cfg(unix)]
+fn allocated_len(metadata: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
 
-    return Ok(count);
+    // st_blocks is always counted in 512-byte units, whatever the filesystem block size is.
+    metadata.blocks() * 512
 }
 
-fn count_chars_in_file(file: &PathBuf) -> usize {
-    let decoded_string = read_to_string(file).expect(
-        "Failed to read file. Note: character count (`-m`) only works with valid UTF-8 encoded files.",
-    );
-    let count = decoded_string.chars().count();
+/// Platforms without `st_blocks` fall back to the apparent size.
+#[cfg(not(unix))]
+fn allocated_len(metadata: &Metadata) -> u64 {
+    metadata.len()
+}
+
+/// Returns how many bytes of a file hold data, by walking its data segments with
+/// `SEEK_DATA`/`SEEK_HOLE`. Filesystems that cannot report holes treat the whole file as data;
+/// if seeking fails outright, the allocated size is used as an estimate instead.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "macos"
+))]
+fn data_extent(file: &PathBuf, metadata: &Metadata) -> u64 {
+    use std::os::unix::io::AsRawFd;
+
+    let estimate = metadata.len().min(allocated_len(metadata));
+    let Ok(handle) = File::open(file) else {
+        return estimate;
+    };
+    let fd = handle.as_raw_fd();
+
+    let mut extent: u64 = 0;
+    let mut offset: libc::off_t = 0;
+
+    while (offset as u64) < metadata.len() {
+        // SAFETY: `fd` stays open for as long as `handle` lives, and lseek only moves its offset.
+        let data = unsafe { libc::lseek(fd, offset, libc::SEEK_DATA) };
+        if data < 0 {
+            let error = std::io::Error::last_os_error();
+            if error.raw_os_error() == Some(libc::ENXIO) {
+                // No data past `offset`: the rest of the file is one hole.
+                break;
+            }
+            return estimate;
+        }
+
+        // SAFETY: as above.
+        let hole = unsafe { libc::lseek(fd, data, libc::SEEK_HOLE) };
+        if hole < 0 {
+            return estimate;
+        }
+
+        extent += (hole - data) as u64;
+        offset = hole;
+    }
+
+    extent
+}
+
+/// Without `SEEK_DATA`, the allocated size is the best estimate of the data extent.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "macos"
+)))]
+fn data_extent(_file: &PathBuf, metadata: &Metadata) -> u64 {
+    metadata.len().min(allocated_len(metadata))
+}
 
-    return count;
+fn count_words(text: &str, word_mode: WordMode) -> usize {
+    match word_mode {
+        WordMode::Whitespace => wcx_core::count_words(text),
+        WordMode::Strict => wcx_core::count_strict_words(text),
+    }
+}
+
+/// Adds the misspelled words of `text` to `file_result`, with `--spell`.
+#[cfg(feature = "spell")]
+fn note_misspelled(file_result: &mut FileResult, text: &str, options: &AnalyzeOptions) {
+    if let Some(dictionary) = &options.spell {
+        file_result.misspelled += dictionary.misspelled(text);
+    }
 }
 
-fn count_words_in_file(file: &PathBuf) -> usize {
-    let decoded_string = read_to_string(file)
-        .expect("Failed to read file. Note: word count (`-w`) only works with valid UTF-8 files.");
-    let count = decoded_string.split_whitespace().count();
+#[cfg(not(feature = "spell"))]
+fn note_misspelled(_file_result: &mut FileResult, _text: &str, _options: &AnalyzeOptions) {}
 
-    return count;
+/// How many characters of the longest word `--longest-word=text` shows.
+const EXCERPT_CHARS: usize = 40;
+
+/// Records the longest word of `text` that `count_words` would count in `file_result`, unless
+/// it already holds a longer one. The first of equally long words is kept.
+fn note_longest_word(file_result: &mut FileResult, text: &str, options: &AnalyzeOptions) {
+    let words = text.split_whitespace().filter(|token| {
+        options.word_mode == WordMode::Whitespace || wcx_core::is_strict_word(token)
+    });
+
+    for word in words {
+        let chars = wcx_core::count_chars(word);
+        if chars > file_result.longest_word {
+            file_result.longest_word = chars;
+            file_result.longest_word_text = options.longest_word_text.then(|| excerpt(word));
+        }
+    }
+}
+
+/// Returns the first characters of `word`, followed by `…` when it is cut.
+fn excerpt(word: &str) -> String {
+    match word.char_indices().nth(EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", &word[..end]),
+        None => word.to_string(),
+    }
+}
+
+/// Counts the characters of the words `count_words` would count.
+fn count_word_chars(text: &str, word_mode: WordMode) -> usize {
+    text.split_whitespace()
+        .filter(|token| word_mode == WordMode::Whitespace || wcx_core::is_strict_word(token))
+        .map(wcx_core::count_chars)
+        .sum()
 }
 
 #[test]
 fn test_count_bytes_in_test_1() {
     let test_file_path = PathBuf::from("assets/test_1.txt");
-    let byte_count = count_bytes_in_file(&test_file_path).expect("Failed to count bytes in file");
+    let byte_count = count_bytes_in_file(&test_file_path, ByteSize::Apparent)
+        .expect("Failed to count bytes in file");
 
     assert_eq!(byte_count, 449);
 }
@@ -84,7 +1141,15 @@ fn test_count_bytes_in_test_1() {
 #[test]
 fn test_count_lines_in_test_1() {
     let test_file_path = PathBuf::from("assets/test_1.txt");
-    let line_count = count_lines_in_file(&test_file_path).expect("Failed to count lines in file");
+    let line_count = analyze_file(
+        &test_file_path,
+        &AnalyzeOptions {
+            lines: true,
+            ..Default::default()
+        },
+    )
+    .expect("Failed to count lines in file")
+    .lines;
 
     assert_eq!(line_count, 1);
 }
@@ -92,7 +1157,15 @@ fn test_count_lines_in_test_1() {
 #[test]
 fn test_count_words_in_test_1() {
     let test_file_path = PathBuf::from("assets/test_1.txt");
-    let word_count = count_words_in_file(&test_file_path);
+    let word_count = analyze_file(
+        &test_file_path,
+        &AnalyzeOptions {
+            words: true,
+            ..Default::default()
+        },
+    )
+    .expect("Failed to count words in file")
+    .words;
 
     assert_eq!(word_count, 70);
 }
@@ -100,7 +1173,15 @@ fn test_count_words_in_test_1() {
 #[test]
 fn test_count_chars_in_test_1() {
     let test_file_path = PathBuf::from("assets/test_1.txt");
-    let char_count = count_chars_in_file(&test_file_path);
+    let char_count = analyze_file(
+        &test_file_path,
+        &AnalyzeOptions {
+            chars: true,
+            ..Default::default()
+        },
+    )
+    .expect("Failed to count chars in file")
+    .chars;
 
     assert_eq!(char_count, 449);
 }
@@ -108,7 +1189,8 @@ fn test_count_chars_in_test_1() {
 #[test]
 fn test_count_bytes_in_test_2() {
     let test_file_path = PathBuf::from("assets/test_2.txt");
-    let byte_count = count_bytes_in_file(&test_file_path).expect("Failed to count bytes in file");
+    let byte_count = count_bytes_in_file(&test_file_path, ByteSize::Apparent)
+        .expect("Failed to count bytes in file");
 
     assert_eq!(byte_count, 3);
 }
@@ -116,7 +1198,15 @@ fn test_count_bytes_in_test_2() {
 #[test]
 fn test_count_lines_in_test_2() {
     let test_file_path = PathBuf::from("assets/test_2.txt");
-    let line_count = count_lines_in_file(&test_file_path).expect("Failed to count lines in file");
+    let line_count = analyze_file(
+        &test_file_path,
+        &AnalyzeOptions {
+            lines: true,
+            ..Default::default()
+        },
+    )
+    .expect("Failed to count lines in file")
+    .lines;
 
     assert_eq!(line_count, 1);
 }
@@ -124,7 +1214,15 @@ fn test_count_lines_in_test_2() {
 #[test]
 fn test_count_words_in_test_2() {
     let test_file_path = PathBuf::from("assets/test_2.txt");
-    let word_count = count_words_in_file(&test_file_path);
+    let word_count = analyze_file(
+        &test_file_path,
+        &AnalyzeOptions {
+            words: true,
+            ..Default::default()
+        },
+    )
+    .expect("Failed to count words in file")
+    .words;
 
     assert_eq!(word_count, 1);
 }
@@ -132,7 +1230,15 @@ fn test_count_words_in_test_2() {
 #[test]
 fn test_count_chars_in_test_2() {
     let test_file_path = PathBuf::from("assets/test_2.txt");
-    let char_count = count_chars_in_file(&test_file_path);
+    let char_count = analyze_file(
+        &test_file_path,
+        &AnalyzeOptions {
+            chars: true,
+            ..Default::default()
+        },
+    )
+    .expect("Failed to count chars in file")
+    .chars;
 
     assert_eq!(char_count, 2);
 }
@@ -140,7 +1246,8 @@ fn test_count_chars_in_test_2() {
 #[test]
 fn test_count_bytes_in_test_3() {
     let test_file_path = PathBuf::from("assets/test_3.txt");
-    let byte_count = count_bytes_in_file(&test_file_path).expect("Failed to count bytes in file");
+    let byte_count = count_bytes_in_file(&test_file_path, ByteSize::Apparent)
+        .expect("Failed to count bytes in file");
 
     assert_eq!(byte_count, 0);
 }
@@ -148,7 +1255,15 @@ fn test_count_bytes_in_test_3() {
 #[test]
 fn test_count_lines_in_test_3() {
     let test_file_path = PathBuf::from("assets/test_3.txt");
-    let line_count = count_lines_in_file(&test_file_path).expect("Failed to count lines in file");
+    let line_count = analyze_file(
+        &test_file_path,
+        &AnalyzeOptions {
+            lines: true,
+            ..Default::default()
+        },
+    )
+    .expect("Failed to count lines in file")
+    .lines;
 
     assert_eq!(line_count, 0);
 }
@@ -156,7 +1271,15 @@ fn test_count_lines_in_test_3() {
 #[test]
 fn test_count_words_in_test_3() {
     let test_file_path = PathBuf::from("assets/test_3.txt");
-    let word_count = count_words_in_file(&test_file_path);
+    let word_count = analyze_file(
+        &test_file_path,
+        &AnalyzeOptions {
+            words: true,
+            ..Default::default()
+        },
+    )
+    .expect("Failed to count words in file")
+    .words;
 
     assert_eq!(word_count, 0);
 }
@@ -164,7 +1287,15 @@ fn test_count_words_in_test_3() {
 #[test]
 fn test_count_chars_in_test_3() {
     let test_file_path = PathBuf::from("assets/test_3.txt");
-    let char_count = count_chars_in_file(&test_file_path);
+    let char_count = analyze_file(
+        &test_file_path,
+        &AnalyzeOptions {
+            chars: true,
+            ..Default::default()
+        },
+    )
+    .expect("Failed to count chars in file")
+    .chars;
 
     assert_eq!(char_count, 0);
 }
@@ -172,7 +1303,8 @@ fn test_count_chars_in_test_3() {
 #[test]
 fn test_count_bytes_in_test_4() {
     let test_file_path = PathBuf::from("assets/test_4.txt");
-    let byte_count = count_bytes_in_file(&test_file_path).expect("Failed to count bytes in file");
+    let byte_count = count_bytes_in_file(&test_file_path, ByteSize::Apparent)
+        .expect("Failed to count bytes in file");
 
     assert_eq!(byte_count, 125);
 }
@@ -180,7 +1312,15 @@ fn test_count_bytes_in_test_4() {
 #[test]
 fn test_count_lines_in_test_4() {
     let test_file_path = PathBuf::from("assets/test_4.txt");
-    let line_count = count_lines_in_file(&test_file_path).expect("Failed to count lines in file");
+    let line_count = analyze_file(
+        &test_file_path,
+        &AnalyzeOptions {
+            lines: true,
+            ..Default::default()
+        },
+    )
+    .expect("Failed to count lines in file")
+    .lines;
 
     assert_eq!(line_count, 6);
 }
@@ -188,7 +1328,15 @@ fn test_count_lines_in_test_4() {
 #[test]
 fn test_count_words_in_test_4() {
     let test_file_path = PathBuf::from("assets/test_4.txt");
-    let word_count = count_words_in_file(&test_file_path);
+    let word_count = analyze_file(
+        &test_file_path,
+        &AnalyzeOptions {
+            words: true,
+            ..Default::default()
+        },
+    )
+    .expect("Failed to count words in file")
+    .words;
 
     assert_eq!(word_count, 15);
 }
@@ -196,7 +1344,359 @@ fn test_count_words_in_test_4() {
 #[test]
 fn test_count_chars_in_test_4() {
     let test_file_path = PathBuf::from("assets/test_4.txt");
-    let char_count = count_chars_in_file(&test_file_path);
+    let char_count = analyze_file(
+        &test_file_path,
+        &AnalyzeOptions {
+            chars: true,
+            ..Default::default()
+        },
+    )
+    .expect("Failed to count chars in file")
+    .chars;
 
     assert_eq!(char_count, 83);
 }
+
+#[test]
+fn test_count_blocks_in_test_1() {
+    let test_file_path = PathBuf::from("assets/test_1.txt");
+    let block_bytes = count_bytes_in_file(&test_file_path, ByteSize::Blocks)
+        .expect("Failed to count bytes in file");
+
+    assert_eq!(block_bytes % 512, 0);
+}
+
+#[test]
+fn test_data_extent_of_dense_file() {
+    let test_file_path = PathBuf::from("assets/test_4.txt");
+    let options = AnalyzeOptions {
+        sparse: true,
+        ..Default::default()
+    };
+    let file_result = analyze_file(&test_file_path, &options).expect("Failed to analyze file");
+
+    assert_eq!(file_result.data_bytes, 125);
+    assert!(!file_result.sparse);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_stream_character_device_up_to_limit() {
+    let device = PathBuf::from("/dev/zero");
+    let file_metadata = metadata(&device).expect("Failed to stat /dev/zero");
+    assert_eq!(
+        SpecialFile::detect(&file_metadata),
+        Some(SpecialFile::CharDevice)
+    );
+
+    let options = AnalyzeOptions {
+        bytes: true,
+        ..Default::default()
+    };
+    let reader = File::open(&device).expect("Failed to open /dev/zero");
+    let file_result =
+        analyze_reader(&device, reader, &options, Some(1024)).expect("Failed to read");
+
+    assert_eq!(file_result.bytes, 1024);
+}
+
+#[test]
+fn test_analyze_selected_counts_words_in_fragments_only() {
+    let options = AnalyzeOptions {
+        lines: true,
+        bytes: true,
+        chars: true,
+        words: true,
+        ..Default::default()
+    };
+    let selected = vec![String::from("two words"), String::from("three more words")];
+
+    let file_result =
+        analyze_selected("a.csv", b"h\nx\n", &selected, &options).expect("Failed to analyze");
+
+    assert_eq!(file_result.lines, 2);
+    assert_eq!(file_result.bytes, 4);
+    assert_eq!(file_result.words, 5);
+    assert_eq!(file_result.chars, 25);
+}
+
+#[test]
+fn test_errors_tell_failure_kinds_apart() {
+    let options = AnalyzeOptions {
+        words: true,
+        ..Default::default()
+    };
+
+    let missing = analyze_file(&PathBuf::from("assets/missing.txt"), &options);
+    assert!(matches!(
+        missing,
+        Err(WcxError::Io { ref source, .. }) if source.kind() == std::io::ErrorKind::NotFound
+    ));
+
+    let binary = analyze_content("blob.bin", b"\xff\xfe", &options);
+    assert!(matches!(binary, Err(WcxError::Decode { ref name, .. }) if name == "blob.bin"));
+}
+
+#[test]
+fn test_analyzer_reports_progress_and_stops_when_cancelled() {
+    let files = vec![
+        PathBuf::from("assets/test_1.txt"),
+        PathBuf::from("assets/test_2.txt"),
+    ];
+    let options = AnalyzeOptions {
+        lines: true,
+        ..Default::default()
+    };
+    let cancel = CancellationToken::default();
+    let done = AtomicUsize::new(0);
+
+    let results = Analyzer::new(&files)
+        .analyze_with(
+            |file| analyze_file(file, &options),
+            |progress| done.store(progress.done, Ordering::Relaxed),
+            &cancel,
+        )
+        .expect("Failed to analyze");
+    assert_eq!(results.len(), 2);
+    assert_eq!(done.load(Ordering::Relaxed), 2);
+
+    let cancelled = Analyzer::new(&files).analyze_with(
+        |file| {
+            cancel.cancel();
+            analyze_file(file, &options)
+        },
+        |_| {},
+        &cancel,
+    );
+    assert!(matches!(
+        cancelled,
+        Err(WcxError::Cancelled { done: 1, total: 2 })
+    ));
+}
+
+#[test]
+fn test_parallel_analyzer_keeps_the_order_of_the_files() {
+    let files: Vec<PathBuf> = (1..=5)
+        .map(|index| PathBuf::from(format!("assets/test_{}.txt", index)))
+        .collect();
+    let options = AnalyzeOptions {
+        bytes: true,
+        words: true,
+        ..Default::default()
+    };
+    let cancel = CancellationToken::default();
+    let counts = |jobs| {
+        Analyzer::new(&files)
+            .jobs(jobs)
+            .analyze_with(
+                |file| analyze_file(file, &options).map(|result| (result.bytes, result.words)),
+                |_| {},
+                &cancel,
+            )
+            .expect("Failed to analyze")
+    };
+
+    assert_eq!(counts(4), counts(1));
+}
+
+#[test]
+fn test_session_counts_like_analyze_content_across_pushes() {
+    let content = "héllo wörld\n— ok 漢字 ---\nlast".as_bytes();
+
+    for (word_mode, incomplete_line) in [
+        (WordMode::Whitespace, IncompleteLine::No),
+        (WordMode::Strict, IncompleteLine::Yes),
+    ] {
+        let options = AnalyzeOptions {
+            lines: true,
+            bytes: true,
+            chars: true,
+            words: true,
+            word_chars: true,
+            word_mode,
+            incomplete_line,
+            record_delim: Some(b' '),
+            ..Default::default()
+        };
+        let whole = analyze_content("a.txt", content, &options).expect("Failed to analyze");
+
+        for split in 0..=content.len() {
+            let mut session = Session::new("a.txt", &options);
+            session.push_bytes(&content[..split]);
+            session.push_bytes(&content[split..]);
+            let pushed = session.finish().expect("Failed to finish");
+
+            let counts =
+                |r: &FileResult| (r.lines, r.bytes, r.chars, r.words, r.word_chars, r.records);
+            assert_eq!(counts(&pushed), counts(&whole), "split at {}", split);
+        }
+    }
+}
+
+#[test]
+fn test_session_rejects_invalid_and_truncated_utf8() {
+    let options = AnalyzeOptions {
+        chars: true,
+        ..Default::default()
+    };
+
+    let mut session = Session::new("a.bin", &options);
+    session.push_bytes(b"ok \xff");
+    assert!(matches!(session.finish(), Err(WcxError::Decode { .. })));
+
+    let mut session = Session::new("a.txt", &options);
+    session.push_bytes("é".as_bytes().split_at(1).0);
+    assert!(matches!(session.finish(), Err(WcxError::Decode { .. })));
+}
+
+#[test]
+fn test_bom_is_detected_and_left_out_of_the_counts() {
+    let content = "\u{feff}\nhello world\n".as_bytes();
+    let mut options = AnalyzeOptions {
+        bytes: true,
+        chars: true,
+        words: true,
+        detect_bom: true,
+        ..Default::default()
+    };
+
+    let whole = analyze_content("a.txt", content, &options).expect("Failed to analyze");
+    let mut session = Session::new("a.txt", &options);
+    session.push_bytes(&content[..2]);
+    session.push_bytes(&content[2..]);
+    let pushed = session.finish().expect("Failed to finish");
+    for file_result in [&whole, &pushed] {
+        assert_eq!(file_result.bom, Some(Bom::Utf8));
+        assert_eq!(file_result.bytes, 16);
+        assert_eq!((file_result.chars, file_result.words), (13, 2));
+    }
+
+    options.keep_bom = true;
+    let kept = analyze_content("a.txt", content, &options).expect("Failed to analyze");
+    assert_eq!((kept.chars, kept.words), (14, 3));
+}
+
+#[test]
+fn test_incomplete_last_line_counts_only_when_asked() {
+    let lines =
+        |content: &[u8], incomplete_line| count_lines(content, EolStyle::Lf, incomplete_line);
+
+    assert_eq!(lines(b"one\ntwo", IncompleteLine::No), 1);
+    assert_eq!(lines(b"one\ntwo", IncompleteLine::Yes), 2);
+    assert_eq!(lines(b"one\ntwo\n", IncompleteLine::Yes), 2);
+}
+
+#[test]
+fn test_fixed_records_leave_a_partial_record() {
+    let options = AnalyzeOptions {
+        record_size: Some(4),
+        ..Default::default()
+    };
+
+    let whole = analyze_content("a.dat", b"AAAABBBBCC", &options).expect("Failed to analyze");
+    let mut session = Session::new("a.dat", &options);
+    session.push_bytes(b"AAAABBBBCC");
+    let pushed = session.finish().expect("Failed to finish");
+    for file_result in [&whole, &pushed] {
+        assert_eq!((file_result.records, file_result.partial_record), (2, 2));
+    }
+}
+
+#[test]
+fn test_analyze_reader_counts_up_to_the_limit() {
+    let options = AnalyzeOptions {
+        lines: true,
+        bytes: true,
+        words: true,
+        ..Default::default()
+    };
+    let content = b"one two\nthree\n";
+
+    let file_result =
+        analyze_reader(Path::new("-"), &content[..], &options, None).expect("Failed to analyze");
+    assert_eq!(
+        (file_result.lines, file_result.bytes, file_result.words),
+        (2, 14, 3)
+    );
+
+    let limited =
+        analyze_reader(Path::new("-"), &content[..], &options, Some(4)).expect("Failed to analyze");
+    assert_eq!(limited.bytes, 4);
+}
+
+#[test]
+fn test_longest_word_is_measured_and_cut() {
+    let blob = "QUJD".repeat(15);
+    let content = format!("short prose {} and more\n", blob);
+    let options = AnalyzeOptions {
+        longest_word: true,
+        longest_word_text: true,
+        ..Default::default()
+    };
+
+    let whole = analyze_content("a.md", content.as_bytes(), &options).expect("Failed to analyze");
+    let mut session = Session::new("a.md", &options);
+    for piece in content.as_bytes().chunks(7) {
+        session.push_bytes(piece);
+    }
+    let pushed = session.finish().expect("Failed to finish");
+    for file_result in [&whole, &pushed] {
+        assert_eq!(file_result.longest_word, 60);
+        assert_eq!(
+            file_result.longest_word_text.as_deref(),
+            Some(format!("{}…", &blob[..40]).as_str())
+        );
+    }
+}
+
+#[test]
+fn test_analyze_file_counts_everything_in_one_pass() {
+    let file_result = analyze_file(
+        &PathBuf::from("assets/test_4.txt"),
+        &AnalyzeOptions {
+            lines: true,
+            bytes: true,
+            chars: true,
+            words: true,
+            ..Default::default()
+        },
+    )
+    .expect("Failed to analyze file");
+
+    assert_eq!(
+        (
+            file_result.lines,
+            file_result.words,
+            file_result.chars,
+            file_result.bytes
+        ),
+        (6, 15, 83, 125)
+    );
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_mapped_files_count_like_read_ones() {
+    let file = PathBuf::from("assets/test_4.txt");
+    let options = AnalyzeOptions {
+        lines: true,
+        chars: true,
+        words: true,
+        detect_bom: true,
+        ..Default::default()
+    };
+    let read = analyze_file(&file, &options).expect("Failed to read");
+    let mapped = analyze_file(
+        &file,
+        &AnalyzeOptions {
+            mmap: true,
+            ..options
+        },
+    )
+    .expect("Failed to map");
+
+    assert_eq!(
+        (mapped.lines, mapped.chars, mapped.words, mapped.bom),
+        (read.lines, read.chars, read.words, read.bom)
+    );
+}
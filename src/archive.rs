@@ -0,0 +1,389 @@
+use anyhow::Context;
+use flate2::read::GzDecoder;
+use std::fs::read;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+/// Separates an archive from the path of an entry inside it in row labels, e.g.
+/// `bundle.zip!docs/intro.md`, or `outer.tar.gz!vendor/inner.zip!README` for nested archives.
+pub const SEPARATOR: char = '!';
+
+/// Limits that keep untrusted archives from exhausting memory.
+#[derive(Clone, Copy, Debug)]
+pub struct ArchiveLimits {
+    /// How many levels of archives are opened: 1 opens the archives given, 2 also the archives
+    /// inside them, and so on. Archives below the limit are counted as plain files.
+    pub depth: usize,
+    /// The most bytes decompressed out of one archive given on the command line, nested
+    /// archives included.
+    pub max_expanded_size: u64,
+}
+
+/// A file inside an archive.
+pub struct Entry {
+    /// Archive path and entry path joined with [`SEPARATOR`].
+    pub label: String,
+    /// Path of the entry inside its innermost archive.
+    pub name: String,
+    /// The decompressed content, or why this one entry could not be read (such as an encrypted
+    /// entry without the right password).
+    pub content: anyhow::Result<Vec<u8>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+fn kind(name: &str) -> Option<Kind> {
+    let name = name.to_lowercase();
+
+    // Java archives and Python wheels are zips, and crates.io packages are gzipped tarballs.
+    if [".zip", ".jar", ".whl"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+    {
+        Some(Kind::Zip)
+    } else if name.ends_with(".tar") {
+        Some(Kind::Tar)
+    } else if [".tar.gz", ".tgz", ".crate"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+    {
+        Some(Kind::TarGz)
+    } else {
+        None
+    }
+}
+
+/// Whether `file` is an archive wcx can open: .zip, .tar, .tar.gz or .tgz, or a package in one of
+/// those formats (.jar, .whl, .crate).
+pub fn is_archive(file: &Path) -> bool {
+    kind(&file.to_string_lossy()).is_some()
+}
+
+/// Reads every file inside the archive `file`, opening nested archives up to `limits.depth`.
+/// Fails as soon as more than `limits.max_expanded_size` bytes have been decompressed, however
+/// small the archive claims its entries are, so decompression bombs are stopped early.
+///
+/// Each entry is handed to `visit` as soon as it is read, so only one is held in memory at a
+/// time. Encrypted zip entries are decrypted with `password`. Entries that cannot be read are
+/// visited with their error instead of failing the whole archive.
+pub fn expand(
+    file: &Path,
+    label: &str,
+    limits: &ArchiveLimits,
+    password: Option<&str>,
+    visit: impl FnMut(Entry),
+) -> anyhow::Result<()> {
+    let content = read(file).with_context(|| format!("Failed to read {}", label))?;
+    expand_bytes(
+        &file.to_string_lossy(),
+        label,
+        content,
+        limits,
+        password,
+        visit,
+    )
+}
+
+/// Like [`expand`], for an archive that is already in memory, such as a download. `name` is the
+/// archive's file name, which tells its format.
+pub fn expand_bytes(
+    name: &str,
+    label: &str,
+    content: Vec<u8>,
+    limits: &ArchiveLimits,
+    password: Option<&str>,
+    mut visit: impl FnMut(Entry),
+) -> anyhow::Result<()> {
+    let kind = kind(name).context("Not an archive")?;
+
+    let mut expander = Expander {
+        limits: *limits,
+        password: password.map(str::to_string),
+        remaining: limits.max_expanded_size,
+        visit: &mut visit,
+    };
+    expander.expand(label, kind, content, limits.depth)
+}
+
+/// Asks for the archive password on the terminal without echoing it.
+#[cfg(any(unix, windows))]
+pub fn prompt_password() -> anyhow::Result<String> {
+    rpassword::prompt_password("Archive password: ").context("Failed to read the archive password")
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn prompt_password() -> anyhow::Result<String> {
+    anyhow::bail!("Cannot prompt for a password here; pass --archive-password PASSWORD")
+}
+
+struct Expander<'a> {
+    limits: ArchiveLimits,
+    password: Option<String>,
+    remaining: u64,
+    visit: &'a mut dyn FnMut(Entry),
+}
+
+impl Expander<'_> {
+    fn expand(
+        &mut self,
+        label: &str,
+        kind: Kind,
+        content: Vec<u8>,
+        depth: usize,
+    ) -> anyhow::Result<()> {
+        match kind {
+            Kind::Zip => self.expand_zip(label, content, depth),
+            Kind::Tar => self.expand_tar(label, tar::Archive::new(Cursor::new(content)), depth),
+            Kind::TarGz => self.expand_tar(
+                label,
+                tar::Archive::new(GzDecoder::new(Cursor::new(content))),
+                depth,
+            ),
+        }
+        .with_context(|| format!("Failed to read archive {}", label))
+    }
+
+    fn expand_zip(&mut self, label: &str, content: Vec<u8>, depth: usize) -> anyhow::Result<()> {
+        let mut archive = ZipArchive::new(Cursor::new(content))?;
+
+        for index in 0..archive.len() {
+            let (name, is_file, encrypted) = {
+                let entry = archive.by_index_raw(index)?;
+                (entry.name().to_string(), entry.is_file(), entry.encrypted())
+            };
+            if !is_file {
+                continue;
+            }
+
+            let entry = match (encrypted, &self.password) {
+                (false, _) => archive.by_index(index),
+                (true, Some(password)) => archive.by_index_decrypt(index, password.as_bytes()),
+                (true, None) => {
+                    self.add_error(
+                        label,
+                        name,
+                        anyhow::anyhow!("encrypted; pass --archive-password"),
+                    );
+                    continue;
+                }
+            };
+
+            match entry {
+                Ok(mut entry) => match self.read_limited(&mut entry, label)? {
+                    Ok(content) => self.add(label, name, content, depth)?,
+                    Err(err) => self.add_error(label, name, err.into()),
+                },
+                Err(ZipError::InvalidPassword) => {
+                    self.add_error(label, name, anyhow::anyhow!("wrong --archive-password"))
+                }
+                Err(err) => self.add_error(label, name, err.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn expand_tar<R: Read>(
+        &mut self,
+        label: &str,
+        mut archive: tar::Archive<R>,
+        depth: usize,
+    ) -> anyhow::Result<()> {
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let name = entry.path()?.to_string_lossy().into_owned();
+            match self.read_limited(&mut entry, label)? {
+                Ok(content) => self.add(label, name, content, depth)?,
+                Err(err) => self.add_error(label, name, err.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records an entry, or opens it when it is an archive itself and the depth allows.
+    fn add(
+        &mut self,
+        parent: &str,
+        name: String,
+        content: Vec<u8>,
+        depth: usize,
+    ) -> anyhow::Result<()> {
+        let label = format!("{}{}{}", parent, SEPARATOR, name);
+
+        match kind(&name) {
+            Some(kind) if depth > 1 => self.expand(&label, kind, content, depth - 1),
+            _ => {
+                (self.visit)(Entry {
+                    label,
+                    name,
+                    content: Ok(content),
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Records an entry that could not be read.
+    fn add_error(&mut self, parent: &str, name: String, error: anyhow::Error) {
+        (self.visit)(Entry {
+            label: format!("{}{}{}", parent, SEPARATOR, name),
+            name,
+            content: Err(error),
+        });
+    }
+
+    /// Reads an entry, reading at most one byte past the remaining expansion budget. Going over
+    /// the budget fails the whole archive; other read errors (like a failed decryption) only
+    /// fail this entry and are returned inside.
+    fn read_limited(
+        &mut self,
+        reader: &mut impl Read,
+        label: &str,
+    ) -> anyhow::Result<std::io::Result<Vec<u8>>> {
+        let mut content: Vec<u8> = Vec::new();
+        let result = reader.take(self.remaining + 1).read_to_end(&mut content);
+
+        let read = content.len() as u64;
+        if read > self.remaining {
+            anyhow::bail!(
+                "{} expands to more than --max-expanded-size {} bytes",
+                label,
+                self.limits.max_expanded_size
+            );
+        }
+        self.remaining -= read;
+
+        Ok(result.map(|_| content))
+    }
+}
+
+#[cfg(test)]
+pub fn zip_of(files: &[(&str, &[u8])]) -> Vec<u8> {
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in files {
+        writer
+            .start_file(*name, SimpleFileOptions::default())
+            .expect("Failed to start zip entry");
+        writer
+            .write_all(content)
+            .expect("Failed to write zip entry");
+    }
+    writer.finish().expect("Failed to finish zip").into_inner()
+}
+
+#[cfg(test)]
+fn entries_of(
+    name: &str,
+    content: Vec<u8>,
+    limits: ArchiveLimits,
+    password: Option<&str>,
+) -> anyhow::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    expand_bytes(name, name, content, &limits, password, |entry| {
+        entries.push(entry)
+    })?;
+    Ok(entries)
+}
+
+#[test]
+fn test_expand_nested_archives_up_to_depth() {
+    let inner = zip_of(&[("notes.md", b"inner words\n")]);
+    let outer = zip_of(&[("a.txt", b"one two\n"), ("vendor/inner.zip", &inner)]);
+    let limits = |depth| ArchiveLimits {
+        depth,
+        max_expanded_size: 1024,
+    };
+
+    let entries =
+        entries_of("outer.zip", outer.clone(), limits(2), None).expect("Failed to expand");
+    let labels: Vec<&str> = entries.iter().map(|entry| entry.label.as_str()).collect();
+    assert_eq!(
+        labels,
+        vec!["outer.zip!a.txt", "outer.zip!vendor/inner.zip!notes.md"]
+    );
+
+    let entries = entries_of("outer.zip", outer, limits(1), None).expect("Failed to expand");
+    assert_eq!(entries[1].label, "outer.zip!vendor/inner.zip");
+}
+
+#[test]
+fn test_expand_stops_at_max_expanded_size() {
+    let bomb = zip_of(&[("zeros.txt", &[b'0'; 4096])]);
+
+    let limits = ArchiveLimits {
+        depth: 1,
+        max_expanded_size: 1000,
+    };
+    let error = entries_of("bomb.zip", bomb, limits, None)
+        .err()
+        .expect("Expansion should be refused");
+
+    assert!(format!("{:#}", error).contains("--max-expanded-size 1000"));
+}
+
+#[test]
+fn test_encrypted_entries_fail_on_their_own() {
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+    use zip::AesMode;
+
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    writer
+        .start_file("plain.txt", SimpleFileOptions::default())
+        .expect("Failed to start zip entry");
+    writer
+        .write_all(b"open\n")
+        .expect("Failed to write zip entry");
+    writer
+        .start_file(
+            "secret.txt",
+            SimpleFileOptions::default().with_aes_encryption(AesMode::Aes256, "hunter2"),
+        )
+        .expect("Failed to start zip entry");
+    writer
+        .write_all(b"hidden words\n")
+        .expect("Failed to write zip entry");
+    let archive = writer.finish().expect("Failed to finish zip").into_inner();
+
+    let expand_with = |password: Option<&str>| {
+        let limits = ArchiveLimits {
+            depth: 1,
+            max_expanded_size: 1024,
+        };
+        entries_of("secret.zip", archive.clone(), limits, password).expect("Failed to expand")
+    };
+
+    let entries = expand_with(Some("hunter2"));
+    assert_eq!(entries[1].content.as_ref().unwrap(), b"hidden words\n");
+
+    let entries = expand_with(Some("wrong"));
+    assert_eq!(entries[0].content.as_ref().unwrap(), b"open\n");
+    assert!(entries[1].content.is_err());
+
+    let entries = expand_with(None);
+    assert!(entries[1].content.is_err());
+}
+
+#[test]
+fn test_packages_are_archives() {
+    assert!(is_archive(Path::new("serde-1.0.200.crate")));
+    assert!(is_archive(Path::new("guava-33.0.jar")));
+    assert!(is_archive(Path::new("requests-2.32.0-py3-none-any.whl")));
+    assert!(is_archive(Path::new("requests-2.32.0.tar.gz")));
+    assert!(!is_archive(Path::new("notes.md")));
+}
@@ -0,0 +1,163 @@
+use anyhow::Context;
+use prettytable::{format, Cell, Row, Table};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Lines and words attributed to one author by `git blame`, and the number of files they
+/// appear in.
+#[derive(Default, Debug, PartialEq)]
+struct AuthorTotals {
+    lines: usize,
+    words: usize,
+    files: usize,
+}
+
+/// Attributes the lines and words of every git-tracked file below `paths` to the author who last
+/// changed them, and prints one row per author, most lines first.
+pub fn invoke(paths: &[PathBuf]) -> anyhow::Result<()> {
+    let mut authors: HashMap<String, AuthorTotals> = HashMap::new();
+
+    for file in tracked_files(paths)? {
+        let blame = git(&["blame", "--line-porcelain", "--"], &file)?;
+
+        for (author, totals) in attribute_lines(&blame) {
+            let author_totals = authors.entry(author).or_default();
+            author_totals.lines += totals.lines;
+            author_totals.words += totals.words;
+            author_totals.files += 1;
+        }
+    }
+
+    let mut authors: Vec<(String, AuthorTotals)> = authors.into_iter().collect();
+    authors.sort_by(|(a_name, a), (b_name, b)| b.lines.cmp(&a.lines).then(a_name.cmp(b_name)));
+
+    print_table(&authors);
+    Ok(())
+}
+
+/// Sums lines and words per author from `git blame --line-porcelain` output, where every content
+/// line (prefixed with a tab) is preceded by its commit's `author` header.
+fn attribute_lines(blame: &str) -> HashMap<String, AuthorTotals> {
+    let mut authors: HashMap<String, AuthorTotals> = HashMap::new();
+    let mut author = "";
+
+    for line in blame.lines() {
+        if let Some(name) = line.strip_prefix("author ") {
+            author = name;
+        } else if let Some(content) = line.strip_prefix('\t') {
+            let totals = authors.entry(author.to_string()).or_default();
+            totals.lines += 1;
+            totals.words += wcx_core::count_words(content);
+        }
+    }
+
+    authors
+}
+
+fn print_table(authors: &[(String, AuthorTotals)]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+    table.set_titles(Row::new(
+        ["Author", "Lines", "Words", "Files", "Share"]
+            .iter()
+            .map(|title| Cell::new(title).style_spec("b"))
+            .collect(),
+    ));
+
+    let total_lines: usize = authors.iter().map(|(_, totals)| totals.lines).sum();
+    let total_words: usize = authors.iter().map(|(_, totals)| totals.words).sum();
+
+    for (author, totals) in authors {
+        let share = totals.lines as f64 * 100.0 / total_lines.max(1) as f64;
+        table.add_row(Row::new(vec![
+            Cell::new(author),
+            Cell::new(&totals.lines.to_string()),
+            Cell::new(&totals.words.to_string()),
+            Cell::new(&totals.files.to_string()),
+            Cell::new(&format!("{:.1}%", share)),
+        ]));
+    }
+
+    if authors.len() > 1 {
+        table.add_row(Row::new(vec![
+            Cell::new("total").style_spec("bFg"),
+            Cell::new(&total_lines.to_string()).style_spec("bFg"),
+            Cell::new(&total_words.to_string()).style_spec("bFg"),
+            Cell::new(""),
+            Cell::new(""),
+        ]));
+    }
+
+    table.printstd();
+}
+
+/// Lists the git-tracked files below `paths`, relative to the current directory.
+fn tracked_files(paths: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut command = Command::new("git");
+    command.args(["ls-files", "-z", "--"]).args(paths);
+
+    let listing = run(&mut command)?;
+    let files = listing
+        .split('\0')
+        .filter(|file| !file.is_empty())
+        .map(PathBuf::from)
+        .collect();
+
+    Ok(files)
+}
+
+fn git(args: &[&str], file: &Path) -> anyhow::Result<String> {
+    run(Command::new("git").args(args).arg(file))
+        .with_context(|| format!("Failed to blame {}", file.display()))
+}
+
+fn run(command: &mut Command) -> anyhow::Result<String> {
+    let output = command
+        .output()
+        .context("Failed to run git. Note: `wcx authors` needs git on the PATH.")?;
+
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[test]
+fn test_attribute_lines_from_porcelain_blame() {
+    let blame = "\
+1111111111111111111111111111111111111111 1 1 2
+author Ada
+author-mail <ada@example.com>
+summary first
+filename notes.md
+\tHello brave new world
+1111111111111111111111111111111111111111 2 2
+author Ada
+author-mail <ada@example.com>
+summary first
+filename notes.md
+\t
+2222222222222222222222222222222222222222 3 3 1
+author Grace Hopper
+author-mail <grace@example.com>
+summary second
+filename notes.md
+\tauthor of this line
+";
+
+    let authors = attribute_lines(blame);
+
+    assert_eq!(authors.len(), 2);
+    assert_eq!(
+        authors["Ada"],
+        AuthorTotals {
+            lines: 2,
+            words: 4,
+            files: 0
+        }
+    );
+    assert_eq!(authors["Grace Hopper"].lines, 1);
+    assert_eq!(authors["Grace Hopper"].words, 4);
+}
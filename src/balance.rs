@@ -0,0 +1,85 @@
+/// Counts the unbalanced double quotes, parentheses, brackets and braces of text pushed piece by
+/// piece, for `--balance`. A string in double quotes must end on the line it starts on, and the
+/// brackets inside it are not counted; single quotes are left out, as apostrophes in prose would
+/// make most of them unbalanced.
+#[derive(Clone, Debug, Default)]
+pub struct Balance {
+    /// The brackets opened and not closed yet, innermost last.
+    open: Vec<char>,
+    in_quote: bool,
+    /// Whether the previous character was a backslash inside a quoted string.
+    escaped: bool,
+    unbalanced: usize,
+}
+
+impl Balance {
+    pub fn push(&mut self, text: &str) {
+        for char in text.chars() {
+            if self.in_quote {
+                match char {
+                    _ if std::mem::take(&mut self.escaped) => {}
+                    '\\' => self.escaped = true,
+                    '"' => self.in_quote = false,
+                    '\n' => {
+                        self.unbalanced += 1;
+                        self.in_quote = false;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            match char {
+                '"' => self.in_quote = true,
+                '(' | '[' | '{' => self.open.push(char),
+                ')' | ']' | '}' => {
+                    // A closing bracket also closes the brackets opened inside its own and left
+                    // open, counting them; one with no opening bracket at all is counted itself.
+                    match self.open.iter().rposition(|&open| open == opening(char)) {
+                        Some(index) => {
+                            self.unbalanced += self.open.len() - index - 1;
+                            self.open.truncate(index);
+                        }
+                        None => self.unbalanced += 1,
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the number of quotes and brackets without a partner in everything pushed.
+    pub fn finish(self) -> usize {
+        self.unbalanced + self.open.len() + usize::from(self.in_quote)
+    }
+}
+
+fn opening(closing: char) -> char {
+    match closing {
+        ')' => '(',
+        ']' => '[',
+        _ => '{',
+    }
+}
+
+/// Counts the unbalanced quotes and brackets of `text`; see [`Balance`].
+pub fn count(text: &str) -> usize {
+    let mut balance = Balance::default();
+    balance.push(text);
+    balance.finish()
+}
+
+#[test]
+fn test_count_finds_unmatched_quotes_and_brackets() {
+    assert_eq!(count(r#"{"a": [1, (2)], "b": "x)\"y"}"#), 0);
+    assert_eq!(count("f(a[1)]"), 2);
+    assert_eq!(count("\"open\nclose\"\n"), 2);
+    assert_eq!(count("{{ ]"), 3);
+    assert_eq!(count(r#"{"a": [1, 2}"#), 1);
+
+    // A string split between two pushes is one string.
+    let mut balance = Balance::default();
+    balance.push("say(\"hi (");
+    balance.push("there\")");
+    assert_eq!(balance.finish(), 0);
+}
@@ -0,0 +1,77 @@
+use crate::paths;
+use anyhow::Context;
+use regex::{Regex, RegexBuilder};
+use std::cmp::Reverse;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// The terms screened for with `--banned-words-file`, matched case-insensitively and only as
+/// whole words, so `ass` does not match `class`.
+pub struct BannedWords {
+    /// The file the terms were read from.
+    file: String,
+    regex: Regex,
+}
+
+impl fmt::Debug for BannedWords {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BannedWords({})", self.file)
+    }
+}
+
+impl BannedWords {
+    /// Reads the terms of `file`, one word or phrase per line. Blank lines and lines starting
+    /// with `#` are skipped.
+    pub fn load(file: &Path) -> anyhow::Result<BannedWords> {
+        let contents = fs::read_to_string(file)
+            .with_context(|| format!("Failed to read banned words {}", file.display()))?;
+        BannedWords::parse(&paths::display(file), &contents)
+    }
+
+    fn parse(file: &str, contents: &str) -> anyhow::Result<BannedWords> {
+        // The words of a phrase may be apart by any spaces or tabs, but not by a line break.
+        let mut terms: Vec<String> = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|term| {
+                let words: Vec<String> = term.split_whitespace().map(regex::escape).collect();
+                words.join(r"[ \t]+")
+            })
+            .collect();
+        if terms.is_empty() {
+            anyhow::bail!("No banned words in {}", file);
+        }
+        // Longer terms first, so a phrase is matched whole rather than the word it starts with.
+        terms.sort_by_key(|term| Reverse(term.len()));
+
+        // Half boundaries only require no word character outside the term, so terms starting
+        // or ending with punctuation (`f**k`) still match.
+        let pattern = format!(r"\b{{start-half}}(?:{})\b{{end-half}}", terms.join("|"));
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(true)
+            .build()
+            .with_context(|| format!("Too many banned words in {}", file))?;
+
+        Ok(BannedWords {
+            file: file.to_string(),
+            regex,
+        })
+    }
+
+    /// Counts the banned terms in `text`.
+    pub fn count(&self, text: &str) -> usize {
+        self.regex.find_iter(text).count()
+    }
+}
+
+#[test]
+fn test_count_matches_whole_words_in_any_case() {
+    let banned = BannedWords::parse("banned.txt", "# legal\ndamn\nf**k\nclick  here\n")
+        .expect("Failed to parse");
+
+    assert_eq!(banned.count("Damn. Click here, damnit, F**K!"), 3);
+    assert_eq!(banned.count("Click\nhere to damnify the fork"), 0);
+    assert!(BannedWords::parse("empty.txt", "# none yet\n\n").is_err());
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A byte order mark at the start of a file, naming the encoding it was saved in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Bom {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Bom {
+    const ALL: [(&'static [u8], Bom); 3] = [
+        (b"\xef\xbb\xbf", Bom::Utf8),
+        (b"\xff\xfe", Bom::Utf16Le),
+        (b"\xfe\xff", Bom::Utf16Be),
+    ];
+}
+
+impl fmt::Display for Bom {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Bom::Utf8 => "UTF-8",
+            Bom::Utf16Le => "UTF-16LE",
+            Bom::Utf16Be => "UTF-16BE",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Returns the byte order mark `content` starts with, if any.
+pub fn detect(content: &[u8]) -> Option<Bom> {
+    Bom::ALL
+        .iter()
+        .find(|(mark, _)| content.starts_with(mark))
+        .map(|(_, bom)| *bom)
+}
+
+/// Returns `text` without the byte order mark it starts with. Decoded text can only start with
+/// a UTF-8 one: the others are not valid UTF-8.
+pub fn strip(text: &str) -> &str {
+    text.strip_prefix('\u{feff}').unwrap_or(text)
+}
+
+#[test]
+fn test_detect_tells_encodings_apart() {
+    assert_eq!(detect(b"\xef\xbb\xbfhello"), Some(Bom::Utf8));
+    assert_eq!(detect(b"\xff\xfeh\0i\0"), Some(Bom::Utf16Le));
+    assert_eq!(detect(b"\xfe\xff\0h\0i"), Some(Bom::Utf16Be));
+    assert_eq!(detect(b"\xef\xbb"), None);
+    assert_eq!(strip("\u{feff}hello"), "hello");
+}
@@ -0,0 +1,464 @@
+use crate::cache::Cache;
+use crate::cli::Metric;
+use crate::paths;
+use crate::walk::collect_files;
+use anyhow::Context;
+use globset::{GlobBuilder, GlobMatcher};
+use prettytable::{format, Cell, Row, Table};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{read_to_string, write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::str::FromStr;
+
+/// How a metric is compared against the limit value of a budget.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+}
+
+impl Comparison {
+    /// Operators in matching order, two-character operators first.
+    const OPERATORS: [(&'static str, Comparison); 5] = [
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("==", Comparison::Equal),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ];
+
+    fn holds(&self, count: usize, value: usize) -> bool {
+        match self {
+            Comparison::Less => count < value,
+            Comparison::LessOrEqual => count <= value,
+            Comparison::Greater => count > value,
+            Comparison::GreaterOrEqual => count >= value,
+            Comparison::Equal => count == value,
+        }
+    }
+
+    fn operator(&self) -> &'static str {
+        Self::OPERATORS
+            .iter()
+            .find(|(_, comparison)| comparison == self)
+            .map_or("", |(operator, _)| operator)
+    }
+}
+
+/// A condition a file's metric must satisfy, parsed from rules like `lines<=800`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Limit {
+    metric: Metric,
+    comparison: Comparison,
+    value: usize,
+}
+
+impl Limit {
+    pub fn holds(&self, count: usize) -> bool {
+        self.comparison.holds(count, self.value)
+    }
+
+    /// Returns how far `count` is from satisfying the limit, 0 when it holds. Used by the ratchet
+    /// to tell whether a violation got worse, whichever direction the limit points in.
+    fn excess(&self, count: usize) -> usize {
+        match self.comparison {
+            Comparison::Less => (count + 1).saturating_sub(self.value),
+            Comparison::LessOrEqual => count.saturating_sub(self.value),
+            Comparison::Greater => (self.value + 1).saturating_sub(count),
+            Comparison::GreaterOrEqual => self.value.saturating_sub(count),
+            Comparison::Equal => count.abs_diff(self.value),
+        }
+    }
+}
+
+impl FromStr for Limit {
+    type Err = anyhow::Error;
+
+    fn from_str(rule: &str) -> anyhow::Result<Self> {
+        let (metric, comparison, value) = Comparison::OPERATORS
+            .iter()
+            .find_map(|(operator, comparison)| {
+                rule.split_once(operator)
+                    .map(|(metric, value)| (metric, *comparison, value))
+            })
+            .with_context(|| {
+                format!(
+                    "Invalid limit `{}`: expected METRIC OP N, e.g. `lines<=800`",
+                    rule
+                )
+            })?;
+
+        let metric = <Metric as clap::ValueEnum>::from_str(metric.trim(), true).map_err(|_| {
+            anyhow::anyhow!("Invalid limit `{}`: unknown metric `{}`", rule, metric)
+        })?;
+        let value = value
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid limit `{}`: `{}` is not a number", rule, value))?;
+
+        Ok(Limit {
+            metric,
+            comparison,
+            value,
+        })
+    }
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let metric = format!("{:?}", self.metric).to_lowercase();
+        write!(f, "{}{}{}", metric, self.comparison.operator(), self.value)
+    }
+}
+
+/// Whether violating a budget fails the check outright or only counts towards `--max-warnings`.
+#[derive(Deserialize, Default, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum Severity {
+    #[serde(alias = "warning")]
+    Warn,
+    #[default]
+    Error,
+}
+
+/// The `wcx.budgets.toml` file: a list of `[[budget]]` tables, each pairing a glob (relative to
+/// the budgets file) with a limit and an optional severity (`error` by default).
+///
+/// ```toml
+/// [[budget]]
+/// path = "src/**/*.rs"
+/// limit = "lines<=800"
+/// severity = "warn"
+/// ```
+#[derive(Deserialize)]
+struct BudgetsFile {
+    #[serde(default, rename = "budget")]
+    budgets: Vec<BudgetConfig>,
+}
+
+#[derive(Deserialize)]
+struct BudgetConfig {
+    path: String,
+    limit: String,
+    #[serde(default)]
+    severity: Severity,
+}
+
+struct Budget {
+    path: String,
+    matcher: GlobMatcher,
+    limit: Limit,
+    severity: Severity,
+}
+
+impl Budget {
+    fn new(config: &BudgetConfig) -> anyhow::Result<Budget> {
+        let matcher = GlobBuilder::new(&config.path)
+            .literal_separator(true)
+            .build()
+            .with_context(|| format!("Invalid budget path `{}`", config.path))?
+            .compile_matcher();
+
+        Ok(Budget {
+            path: config.path.clone(),
+            matcher,
+            limit: config.limit.parse()?,
+            severity: config.severity,
+        })
+    }
+}
+
+/// A file whose metric does not satisfy the limit of a budget it matches. Violations recorded in
+/// the ratchet baseline that did not get worse are `tolerated` and never fail the check.
+struct Violation {
+    file: String,
+    budget: String,
+    count: usize,
+    severity: Severity,
+    tolerated: bool,
+}
+
+/// Counts of the violations accepted as existing debt, keyed by file and then by budget
+/// (`<path glob> <limit>`). Stored as JSON by `--update-baseline` and read by `--ratchet`.
+type Baseline = BTreeMap<String, BTreeMap<String, usize>>;
+
+fn load_baseline(path: &Path) -> anyhow::Result<Baseline> {
+    if !path.exists() {
+        return Ok(Baseline::new());
+    }
+
+    let contents = read_to_string(path)
+        .with_context(|| format!("Failed to read baseline {}", path.display()))?;
+    let baseline = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse baseline {}", path.display()))?;
+
+    Ok(baseline)
+}
+
+fn save_baseline(path: &Path, violations: &[Violation]) -> anyhow::Result<()> {
+    let mut baseline = Baseline::new();
+    for violation in violations {
+        baseline
+            .entry(violation.file.clone())
+            .or_default()
+            .insert(violation.budget.clone(), violation.count);
+    }
+
+    let mut contents = serde_json::to_string_pretty(&baseline)?;
+    contents.push('\n');
+    write(path, contents)
+        .with_context(|| format!("Failed to write baseline {}", path.display()))?;
+
+    Ok(())
+}
+
+/// Evaluates every budget in `budgets_file` against the files below the directory holding it and
+/// prints the violations. The check fails if any `error` budget is violated, or if `warn`
+/// budgets are violated more than `max_warnings` times.
+///
+/// With a `ratchet` baseline, violations already recorded in it only count when they got worse,
+/// so existing oversized files do not block the check while new debt does. `update_baseline`
+/// instead records the current violations as the new baseline.
+pub fn invoke(
+    budgets_file: &Path,
+    max_warnings: Option<usize>,
+    ratchet: Option<&Path>,
+    update_baseline: bool,
+) -> anyhow::Result<ExitCode> {
+    let contents = read_to_string(budgets_file)
+        .with_context(|| format!("Failed to read budgets file {}", budgets_file.display()))?;
+    let config: BudgetsFile = toml::from_str(&contents)
+        .with_context(|| format!("Failed to parse budgets file {}", budgets_file.display()))?;
+    let budgets = config
+        .budgets
+        .iter()
+        .map(Budget::new)
+        .collect::<anyhow::Result<Vec<Budget>>>()?;
+
+    let root = match budgets_file.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let baseline = match ratchet {
+        Some(path) if !update_baseline => load_baseline(path)?,
+        _ => Baseline::new(),
+    };
+
+    let mut cache = Cache::load();
+    let mut violations: Vec<Violation> = Vec::new();
+    let mut checked = 0;
+
+    for file in collect_files(root)? {
+        let relative = paths::slash(file.strip_prefix(root).unwrap_or(&file));
+        let matching: Vec<&Budget> = budgets
+            .iter()
+            .filter(|budget| budget.matcher.is_match(&relative))
+            .collect();
+
+        if matching.is_empty() {
+            continue;
+        }
+
+        let file_result = cache.count(&file)?;
+        checked += 1;
+
+        for budget in matching {
+            let count = file_result.count(budget.limit.metric);
+            if !budget.limit.holds(count) {
+                let file = relative.clone();
+                let budget_name = format!("{} {}", budget.path, budget.limit);
+                let tolerated = baseline
+                    .get(&file)
+                    .and_then(|budgets| budgets.get(&budget_name))
+                    .is_some_and(|recorded| {
+                        budget.limit.excess(count) <= budget.limit.excess(*recorded)
+                    });
+
+                violations.push(Violation {
+                    file,
+                    budget: budget_name,
+                    count,
+                    severity: budget.severity,
+                    tolerated,
+                });
+            }
+        }
+    }
+
+    let _ = cache.save();
+
+    if let (Some(path), true) = (ratchet, update_baseline) {
+        save_baseline(path, &violations)?;
+        println!(
+            "Recorded {} violations in baseline {}.",
+            violations.len(),
+            path.display()
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if violations.is_empty() {
+        println!("All budgets met ({} files checked).", checked);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let counted = |severity: Severity| {
+        violations
+            .iter()
+            .filter(|violation| !violation.tolerated && violation.severity == severity)
+            .count()
+    };
+    let errors = counted(Severity::Error);
+    let warnings = counted(Severity::Warn);
+    let tolerated = violations.len() - errors - warnings;
+
+    print_violations(&violations);
+    if ratchet.is_some() {
+        println!(
+            "{} errors, {} warnings, {} within baseline ({} files checked).",
+            errors, warnings, tolerated, checked
+        );
+    } else {
+        println!(
+            "{} errors, {} warnings ({} files checked).",
+            errors, warnings, checked
+        );
+    }
+
+    if let Some(max_warnings) = max_warnings.filter(|max| warnings > *max) {
+        println!(
+            "Too many warnings: {} (--max-warnings {}).",
+            warnings, max_warnings
+        );
+    }
+
+    if fails(errors, warnings, max_warnings) {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+fn fails(errors: usize, warnings: usize, max_warnings: Option<usize>) -> bool {
+    errors > 0 || max_warnings.is_some_and(|max| warnings > max)
+}
+
+fn print_violations(violations: &[Violation]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+    table.set_titles(Row::new(
+        ["File", "Budget", "Actual", "Severity"]
+            .iter()
+            .map(|title| Cell::new(title).style_spec("b"))
+            .collect(),
+    ));
+
+    for violation in violations {
+        table.add_row(Row::new(vec![
+            Cell::new(&violation.file),
+            Cell::new(&violation.budget),
+            Cell::new(&violation.count.to_string()),
+            match (violation.tolerated, violation.severity) {
+                (true, _) => Cell::new("baseline"),
+                (false, Severity::Error) => Cell::new("error").style_spec("Fr"),
+                (false, Severity::Warn) => Cell::new("warn").style_spec("Fy"),
+            },
+        ]));
+    }
+
+    table.printstd();
+}
+
+#[test]
+fn test_limit_parses_and_displays() {
+    let limit: Limit = "lines<=800".parse().expect("Failed to parse limit");
+
+    assert_eq!(limit.metric, Metric::Lines);
+    assert_eq!(limit.comparison, Comparison::LessOrEqual);
+    assert_eq!(limit.value, 800);
+    assert_eq!(limit.to_string(), "lines<=800");
+
+    let limit: Limit = " words >= 100 ".parse().expect("Failed to parse limit");
+    assert_eq!(limit.to_string(), "words>=100");
+}
+
+#[test]
+fn test_limit_holds() {
+    let at_most: Limit = "bytes<=10".parse().unwrap();
+    assert!(at_most.holds(10));
+    assert!(!at_most.holds(11));
+
+    let more_than: Limit = "words>3".parse().unwrap();
+    assert!(!more_than.holds(3));
+    assert!(more_than.holds(4));
+}
+
+#[test]
+fn test_limit_rejects_invalid_rules() {
+    assert!("lines".parse::<Limit>().is_err());
+    assert!("pages<=3".parse::<Limit>().is_err());
+    assert!("lines<=many".parse::<Limit>().is_err());
+}
+
+#[test]
+fn test_budget_paths_match_relative_to_root() {
+    let budget = Budget::new(&BudgetConfig {
+        path: String::from("src/*.rs"),
+        limit: String::from("lines<=800"),
+        severity: Severity::Error,
+    })
+    .expect("Failed to build budget");
+
+    assert!(budget.matcher.is_match("src/main.rs"));
+    assert!(!budget.matcher.is_match("src/nested/main.rs"));
+}
+
+#[test]
+fn test_budgets_file_severity_defaults_to_error() {
+    let config: BudgetsFile = toml::from_str(
+        r#"
+        [[budget]]
+        path = "src/**/*.rs"
+        limit = "lines<=800"
+
+        [[budget]]
+        path = "docs/**/*.md"
+        limit = "words>=100"
+        severity = "warning"
+        "#,
+    )
+    .expect("Failed to parse budgets file");
+
+    assert_eq!(config.budgets[0].severity, Severity::Error);
+    assert_eq!(config.budgets[1].severity, Severity::Warn);
+}
+
+#[test]
+fn test_check_fails_on_errors_or_too_many_warnings() {
+    assert!(fails(1, 0, None));
+    assert!(!fails(0, 12, None));
+    assert!(!fails(0, 3, Some(3)));
+    assert!(fails(0, 4, Some(3)));
+}
+
+#[test]
+fn test_limit_excess_grows_as_the_violation_gets_worse() {
+    let at_most: Limit = "lines<=800".parse().unwrap();
+    assert_eq!(at_most.excess(800), 0);
+    assert_eq!(at_most.excess(950), 150);
+
+    let at_least: Limit = "words>=100".parse().unwrap();
+    assert_eq!(at_least.excess(100), 0);
+    assert_eq!(at_least.excess(40), 60);
+
+    let below: Limit = "bytes<10".parse().unwrap();
+    assert_eq!(below.excess(9), 0);
+    assert_eq!(below.excess(10), 1);
+}
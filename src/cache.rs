@@ -0,0 +1,124 @@
+use crate::analyze::{analyze_file, AnalyzeOptions, FileResult};
+use crate::state::state_dir;
+use crate::walk::{walk_files, WalkOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{metadata, read_to_string, rename, write};
+use std::path::{absolute, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+const CACHE_FILE: &str = "cache.json";
+
+/// Counts for a single file, valid for as long as the file keeps the same length and
+/// modification time.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    len: u64,
+    modified_ns: u64,
+    result: FileResult,
+}
+
+/// Persists per-file counts in the state directory so that repeated runs over mostly unchanged
+/// trees (shell prompts, goal tracking) only re-read the files that changed.
+///
+/// New instances are obtained via [`Cache::load`], which never fails: a missing or unreadable
+/// cache file simply starts an empty cache. Call [`Cache::save`] to write changes back.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Cache {
+    entries: HashMap<PathBuf, CacheEntry>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl Cache {
+    pub fn load() -> Cache {
+        state_dir()
+            .ok()
+            .and_then(|dir| read_to_string(dir.join(CACHE_FILE)).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let path = state_dir()?.join(CACHE_FILE);
+        let tmp = path.with_extension("json.tmp");
+        write(&tmp, serde_json::to_vec(self)?)?;
+        rename(&tmp, &path)?;
+
+        Ok(())
+    }
+
+    /// Returns all four counts for `file`, from the cache when it is still fresh.
+    ///
+    /// Files that are not valid UTF-8 are cached with lines and bytes only, so that binaries in
+    /// a tree contribute nothing to word and character totals instead of failing the run.
+    pub fn count(&mut self, file: &Path) -> anyhow::Result<FileResult> {
+        let key = absolute(file)?;
+        let metadata = metadata(&key)?;
+        let len = metadata.len();
+        let modified_ns = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+
+        if let Some(entry) = self.entries.get(&key) {
+            if entry.len == len && entry.modified_ns == modified_ns {
+                return Ok(entry.result.clone());
+            }
+        }
+
+        let mut options = AnalyzeOptions {
+            lines: true,
+            bytes: true,
+            chars: true,
+            words: true,
+            ..Default::default()
+        };
+        let result = match analyze_file(&key, &options) {
+            Ok(result) => result,
+            Err(_) => {
+                options.chars = false;
+                options.words = false;
+                analyze_file(&key, &options)?
+            }
+        };
+
+        self.entries.insert(
+            key,
+            CacheEntry {
+                len,
+                modified_ns,
+                result: result.clone(),
+            },
+        );
+        self.dirty = true;
+
+        Ok(result)
+    }
+
+    /// Sums the counts of every file below `path` (see [`walk_files`]). Files that disappear
+    /// or cannot be read while walking are left out of the total instead of failing the walk;
+    /// directories that cannot be read are reported and skipped.
+    pub fn count_path(&mut self, path: &Path) -> anyhow::Result<FileResult> {
+        let mut totals: FileResult = Default::default();
+        let options = WalkOptions {
+            skip_unreadable: true,
+            ..Default::default()
+        };
+
+        for file in walk_files(path, options)? {
+            if let Ok(result) = self.count(&file) {
+                totals.lines += result.lines;
+                totals.bytes += result.bytes;
+                totals.chars += result.chars;
+                totals.words += result.words;
+            }
+        }
+
+        Ok(totals)
+    }
+}
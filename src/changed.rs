@@ -0,0 +1,166 @@
+use anyhow::Context;
+use prettytable::{format, Cell, Row, Table};
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+use std::fs::read;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Words added and removed in one file between the base revision and the working tree.
+#[derive(Default, Debug, PartialEq)]
+struct WordChanges {
+    added: usize,
+    removed: usize,
+}
+
+/// Reports, for every file that differs from `base` in the working tree (optionally limited to
+/// `paths`), how many words were added and removed. Words are compared with a word-level diff,
+/// so moving a sentence counts its words as removed and added, while re-wrapping a paragraph
+/// counts nothing. Untracked files that are not ignored count as added in full.
+pub fn invoke(base: &str, paths: &[PathBuf]) -> anyhow::Result<()> {
+    let mut changed_files: Vec<(String, String)> = Vec::new();
+
+    let mut command = Command::new("git");
+    command
+        .args(["diff", "--name-status", "--no-renames", "--relative", "-z"])
+        .arg(base)
+        .arg("--")
+        .args(paths);
+    let listing = run(&mut command)?;
+    let mut fields = listing.split('\0').filter(|field| !field.is_empty());
+    while let (Some(status), Some(file)) = (fields.next(), fields.next()) {
+        changed_files.push((status.to_string(), file.to_string()));
+    }
+
+    let mut command = Command::new("git");
+    command
+        .args(["ls-files", "-z", "--others", "--exclude-standard", "--"])
+        .args(paths);
+    for file in run(&mut command)?
+        .split('\0')
+        .filter(|file| !file.is_empty())
+    {
+        changed_files.push((String::from("A"), file.to_string()));
+    }
+
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+    table.set_titles(Row::new(
+        ["Added", "Removed", "Net", "File"]
+            .iter()
+            .map(|title| Cell::new(title).style_spec("b"))
+            .collect(),
+    ));
+
+    let mut totals: WordChanges = Default::default();
+
+    for (status, file) in &changed_files {
+        let old = if status == "A" {
+            String::new()
+        } else {
+            run(Command::new("git")
+                .arg("show")
+                .arg(format!("{}:./{}", base, file)))?
+        };
+
+        let new = if status == "D" {
+            String::new()
+        } else {
+            let bytes = read(file).with_context(|| format!("Failed to read {}", file))?;
+            String::from_utf8_lossy(&bytes).into_owned()
+        };
+
+        let changes = word_changes(&old, &new);
+        totals.added += changes.added;
+        totals.removed += changes.removed;
+
+        table.add_row(Row::new(vec![
+            Cell::new(&format!("+{}", changes.added)).style_spec("Fg"),
+            Cell::new(&format!("-{}", changes.removed)).style_spec("Fr"),
+            Cell::new(&net(&changes)),
+            Cell::new(file),
+        ]));
+    }
+
+    if changed_files.is_empty() {
+        println!("No changes against {}.", base);
+        return Ok(());
+    }
+
+    if changed_files.len() > 1 {
+        table.add_row(Row::new(vec![
+            Cell::new(&format!("+{}", totals.added)).style_spec("bFg"),
+            Cell::new(&format!("-{}", totals.removed)).style_spec("bFg"),
+            Cell::new(&net(&totals)).style_spec("bFg"),
+            Cell::new("total").style_spec("bFg"),
+        ]));
+    }
+
+    table.printstd();
+    Ok(())
+}
+
+/// Counts the words added and removed by a word-level diff from `old` to `new`.
+fn word_changes(old: &str, new: &str) -> WordChanges {
+    let old_words: Vec<&str> = old.split_whitespace().collect();
+    let new_words: Vec<&str> = new.split_whitespace().collect();
+
+    let mut changes: WordChanges = Default::default();
+
+    for op in capture_diff_slices(Algorithm::Myers, &old_words, &new_words) {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        match tag {
+            DiffTag::Equal => {}
+            DiffTag::Delete => changes.removed += old_range.len(),
+            DiffTag::Insert => changes.added += new_range.len(),
+            DiffTag::Replace => {
+                changes.removed += old_range.len();
+                changes.added += new_range.len();
+            }
+        }
+    }
+
+    changes
+}
+
+fn net(changes: &WordChanges) -> String {
+    format!("{:+}", changes.added as i64 - changes.removed as i64)
+}
+
+fn run(command: &mut Command) -> anyhow::Result<String> {
+    let output = command
+        .output()
+        .context("Failed to run git. Note: `wcx changed` needs git on the PATH.")?;
+
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[test]
+fn test_word_changes_counts_insertions_and_deletions() {
+    let old = "The quick brown fox jumps over the lazy dog.";
+    let new = "The quick red fox leaps over the\nlazy dog. Twice!";
+
+    assert_eq!(
+        word_changes(old, new),
+        WordChanges {
+            added: 3,
+            removed: 2
+        }
+    );
+}
+
+#[test]
+fn test_word_changes_ignores_rewrapping() {
+    let old = "one two three\nfour five";
+    let new = "one two\nthree four\n\nfive";
+
+    assert_eq!(word_changes(old, new), Default::default());
+}
+
+#[test]
+fn test_word_changes_of_new_file() {
+    assert_eq!(word_changes("", "a new file").added, 3);
+}
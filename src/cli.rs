@@ -1,9 +1,23 @@
-use clap::Parser;
+use crate::group::GroupBy;
+use chrono::NaiveDate;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use std::ffi::OsString;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true,
+    args_override_self = true,
+    group(clap::ArgGroup::new("filters").multiple(true))
+)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Count number of lines in the file
     #[arg(short = 'l')]
     pub lines_enabled: bool,
@@ -20,18 +34,913 @@ pub struct Args {
     #[arg(short = 'w')]
     pub words_enabled: bool,
 
+    /// Show the display width of the longest line in the file, like `wc -L`; the total is the
+    /// longest of all files
+    #[arg(short = 'L')]
+    pub max_line_length: bool,
+
+    /// What the byte count (`-c`) measures: the content length, or the space allocated on disk
+    /// in whole blocks like `du`
+    #[arg(long = "bytes", value_enum, value_name = "MODE", default_value_t = ByteSize::Apparent)]
+    pub byte_size: ByteSize,
+
+    /// What counts as a word (`-w`): any whitespace-separated token, or only tokens containing
+    /// a letter or digit
+    #[arg(long = "words", value_enum, value_name = "MODE", default_value_t = WordMode::Whitespace)]
+    pub word_mode: WordMode,
+
+    /// Whether a last line without a trailing newline counts as a line (`-l`). By default only
+    /// newlines are counted, like wc
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = IncompleteLine::No)]
+    pub count_incomplete_line: IncompleteLine,
+
+    /// Which line endings `-l` counts: `lf` (`\n`, which also ends CRLF lines), `crlf`, `cr`
+    /// (lone `\r`, classic Mac), `any` of them, or `auto`, which reads files without any `\n`
+    /// as `cr`
+    #[arg(long, value_enum, value_name = "STYLE", default_value_t = EolStyle::Lf)]
+    pub eol_style: EolStyle,
+
+    /// Add a Display lines column counting the lines a file takes up on screen or paper when
+    /// long lines wrap at --wrap-width columns, with wide characters taking two columns
+    #[arg(long)]
+    pub logical_lines: bool,
+
+    /// The width --logical-lines wraps lines at; implies --logical-lines [default: 80]
+    #[arg(long, value_name = "COLUMNS")]
+    pub wrap_width: Option<std::num::NonZeroUsize>,
+
+    /// Add a Pages column estimating the pages a file fills when printed, wrapping long lines at
+    /// --page-width; every file starts on a new page
+    #[arg(long)]
+    pub print_pages: bool,
+
+    /// The lines on a printed page; implies --print-pages [default: 54]
+    #[arg(long, value_name = "N")]
+    pub page_lines: Option<std::num::NonZeroUsize>,
+
+    /// The columns of a printed page; implies --print-pages [default: 80]
+    #[arg(long, value_name = "COLUMNS")]
+    pub page_width: Option<std::num::NonZeroUsize>,
+
+    /// Add a Records column counting records separated by NUL bytes, like the output of
+    /// `find -print0`; same as `--record-delim '\0'`
+    #[arg(long, value_enum, value_name = "KIND", conflicts_with = "record_delim")]
+    pub records: Option<Records>,
+
+    /// Add a Records column counting records separated by this byte: a single ASCII character,
+    /// or an escape like `\0`, `\t`, `\n`, `\r` or `\x1e`
+    #[arg(long, value_name = "CHAR")]
+    pub record_delim: Option<Delimiter>,
+
+    /// Add a Records column counting fixed-length records of this many bytes, and a Partial
+    /// column with the bytes of a last record cut short
+    #[arg(long, value_name = "BYTES", conflicts_with_all = ["records", "record_delim"])]
+    pub record_size: Option<std::num::NonZeroUsize>,
+
+    /// Add Min fields, Max fields and Avg fields columns with the fields per line, split on this
+    /// byte (`\t` for TSV), so ragged tables stand out
+    #[arg(long, value_name = "CHAR")]
+    pub fields_delim: Option<Delimiter>,
+
+    /// Add a Longest word column with the length in characters of the longest word (`-w`) of
+    /// each file, which catches base64 blobs or minified code in prose; `--longest-word=text`
+    /// also shows the word
+    #[arg(
+        long,
+        value_enum,
+        value_name = "SHOW",
+        num_args = 0..=1,
+        default_missing_value = "length",
+        require_equals = true
+    )]
+    pub longest_word: Option<LongestWord>,
+
+    /// Add Spaces, Tabs, NBSP (no-break spaces) and Other ws columns counting the whitespace of
+    /// each file by kind, to track down invisible characters that break diffs and YAML files
+    #[arg(long)]
+    pub whitespace_stats: bool,
+
+    /// Add a Suspicious column counting zero-width characters and bidirectional controls, which
+    /// can hide or reorder source code ("Trojan Source")
+    #[arg(long)]
+    pub suspicious_chars: bool,
+
+    /// Add a Confusables column counting letters and digits outside ASCII that look like ASCII
+    /// ones, such as a Cyrillic `е` in place of `e`
+    #[arg(long)]
+    pub confusables: bool,
+
+    /// Add a Misspelled column counting the prose words missing from the Hunspell dictionary of
+    /// LANGUAGE, e.g. `en_US`, or from a `.dic` file
+    #[cfg(feature = "spell")]
+    #[arg(long, value_name = "LANGUAGE")]
+    pub spell: Option<String>,
+
+    /// Add a Banned column counting the matches of the words and phrases listed in FILE, one per
+    /// line, in any case and only as whole words. Blank lines and lines starting with `#` are
+    /// skipped
+    #[arg(long, value_name = "FILE")]
+    pub banned_words_file: Option<PathBuf>,
+
+    /// Add an Unbalanced column counting the double quotes, parentheses, brackets and braces
+    /// without a partner, a quick sanity check of config and data files
+    #[arg(long)]
+    pub balance: bool,
+
+    /// Add columns with statistics derived from the counts, e.g.
+    /// `--derived avg-words-per-line,avg-word-len`
+    #[arg(long, value_enum, value_name = "STATS", value_delimiter = ',')]
+    pub derived: Vec<Derived>,
+
+    /// How files are read: as plain `text`; as `csv`, `json` or `html`, counting words and
+    /// characters only in cells, string values or visible text; or as `config`, `subtitle`,
+    /// `idl`, `patch` or `mail`, adding structural counts
+    #[arg(long, value_enum, default_value_t = Mode::Text)]
+    pub mode: Mode,
+
+    /// With --mode csv, only count words and characters in these columns, e.g. `title,body`
+    #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+    pub csv_column: Vec<String>,
+
+    /// With --mode json, only count words and characters in the strings this JSONPath selects,
+    /// e.g. `$.items[*].description`
+    #[arg(long, value_name = "PATH")]
+    pub select: Option<String>,
+
+    /// With --mode html, only count words and characters in the elements this CSS selector
+    /// matches, e.g. `article p`
+    #[arg(long, value_name = "SELECTOR")]
+    pub select_css: Option<String>,
+
+    /// With --mode mail, leave quoted lines of replies (starting with `>`) out of the body words
+    #[arg(long)]
+    pub exclude_quoted: bool,
+
+    /// Show only these columns, in this order: `lines`, `words`, `avg-word-len`, a --mode column
+    /// like `types`, an alias like `loc`, or an unambiguous prefix like `wo`
+    #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    /// Order the rows by a column (named as in --columns), by `file` or by `ext`, smallest first.
+    /// Several keys order rows equal in the first by the next, and `-` orders largest first,
+    /// e.g. `ext,-lines`
+    #[arg(long, value_name = "KEYS", allow_hyphen_values = true)]
+    pub sort: Option<String>,
+
+    /// Only show the rows whose file (or group) name contains TEXT, e.g. `tests/`. The totals
+    /// still count every file
+    #[arg(long, value_name = "TEXT", group = "filters")]
+    pub filter_name: Option<String>,
+
+    /// Only show the rows whose file (or group) name matches the regular expression REGEX, e.g.
+    /// `\.rs$`. The totals still count every file
+    #[arg(long, value_name = "REGEX", group = "filters")]
+    pub filter_name_regex: Option<regex::Regex>,
+
+    /// Only show the rows whose counts satisfy EXPR, e.g. `lines>1000 && words<50`: columns
+    /// (named as in --columns) compared with numbers using <, <=, >, >=, == or !=, combined
+    /// with !, && and || and grouped with parentheses
+    #[arg(long = "where", value_name = "EXPR", group = "filters")]
+    pub where_: Option<crate::predicate::Predicate>,
+
+    /// Make the totals count only the rows left by --where, --filter-name and
+    /// --filter-name-regex, instead of every file
+    #[arg(long, requires = "filters")]
+    pub filtered_totals: bool,
+
+    /// Combine the files into one row per group: `language`, `owner` (the user owning each
+    /// file, Unix only), `mtime:PERIOD` (when each file was last modified, by `day`, `week`,
+    /// `month` or `year`), or `dir:N` (the first N directories of each path)
+    #[arg(long, value_name = "KEY")]
+    pub group_by: Option<GroupBy>,
+
+    /// Count the files inside .zip, .tar, .tar.gz and .tgz archives instead of the archives
+    #[arg(long)]
+    pub archives: bool,
+
+    /// How many levels of archives are opened with --archives; 0 counts them as plain files
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    pub archive_depth: usize,
+
+    /// Stop reading an archive once this many bytes have been decompressed from it
+    #[arg(long, value_name = "BYTES", default_value_t = 1 << 30)]
+    pub max_expanded_size: u64,
+
+    /// Password for encrypted zip entries with --archives, given as `--archive-password=PASSWORD`;
+    /// without a value it is asked for
+    #[arg(
+        long,
+        value_name = "PASSWORD",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "",
+        requires = "archives"
+    )]
+    pub archive_password: Option<String>,
+
+    /// Add a Generated column flagging files that look machine-generated (`@generated`
+    /// markers, sourcemap comments, or one enormous minified line)
+    #[arg(long)]
+    pub detect_generated: bool,
+
+    /// Leave files that look machine-generated out of the totals (implies --detect-generated)
+    #[arg(long)]
+    pub exclude_generated: bool,
+
+    /// Add a BOM column naming the byte order mark files start with: UTF-8, UTF-16LE or
+    /// UTF-16BE
+    #[arg(long)]
+    pub detect_bom: bool,
+
+    /// Count a UTF-8 byte order mark as a character, like any other text. By default it is
+    /// left out of the characters and words, so a BOM does not change the counts
+    #[arg(long)]
+    pub keep_bom: bool,
+
+    /// Add a Data column with the bytes that actually hold data and mark sparse files
+    #[arg(long)]
+    pub sparse: bool,
+
+    /// Count FIFOs and character devices instead of skipping them; --read-limit, or else
+    /// --max-bytes, caps how much is read
+    #[arg(long)]
+    pub read_special: bool,
+
+    /// Read at most BYTES from stdin, FIFOs and character devices, so endless streams like
+    /// /dev/zero stop; a stream cut short is reported
+    #[arg(long, value_name = "BYTES")]
+    pub read_limit: Option<usize>,
+
+    /// Map every file into memory to count it, instead of reading it in chunks. Files of 64 MiB
+    /// or more are mapped either way
+    #[cfg(feature = "mmap")]
+    #[arg(long)]
+    pub mmap: bool,
+
     // get_possible_values = &["no_title", "no_linesep_with_title", "no_linesep", "no_colsep", "clean", "borders_only", "no_border", "no_border_line_separator", "box_chars"],
     // Style of the table
     #[arg(short = 'f',  default_value_t = String::from("no_border_line_separator"))]
     pub format: String,
 
-    /// Count 1 or many files
-    #[arg(required = true, num_args = 1..)]
+    /// Draw table borders with `+`, `-` and `|` only, even with `-f box_chars`, for terminals
+    /// and CI log viewers that mangle box-drawing characters
+    #[arg(long)]
+    pub ascii: bool,
+
+    /// Show the table through $PAGER (`less` by default), even when it fits on the screen. By
+    /// default tables taller than the terminal are paged
+    #[arg(long, overrides_with = "no_pager")]
+    pub pager: bool,
+
+    /// Never show the table through a pager
+    #[arg(long, overrides_with = "pager")]
+    pub no_pager: bool,
+
+    /// Print the results as a table or in a machine-readable format
+    #[arg(short = 'o', long, value_enum, value_name = "FORMAT", default_value_t = Output::Table)]
+    pub output: Output,
+
+    /// Print every file's counts as labeled `metric: value` lines instead of a table, without
+    /// any box drawing, so a screen reader reads them out clearly; same as `-o screen-reader`
+    #[arg(long, conflicts_with_all = ["output", "raw"])]
+    pub screen_reader: bool,
+
+    /// Make the output byte-stable for snapshot tests: files sorted, paths relative to the
+    /// current directory, and no timestamps or timings
+    #[arg(long)]
+    pub deterministic: bool,
+
+    /// Transpose the table, csv and markdown output: one row per metric and one column per file
+    #[arg(long)]
+    pub pivot: bool,
+
+    /// Print only the number of each file, one per line, for scripts: `$(wcx -l --raw FILE)`.
+    /// Needs exactly one column
+    #[arg(short = 'q', long, conflicts_with_all = ["output", "pivot", "hook"])]
+    pub raw: bool,
+
+    /// Leave out the File column, like `grep -h`, printing bare counts
+    #[arg(long, conflicts_with = "pivot")]
+    pub no_filename: bool,
+
+    /// Align table columns: `right` or `left` for every column, or per column like
+    /// `words=right,file=left` (columns named as in --sort)
+    #[arg(
+        long,
+        value_name = "ALIGN",
+        value_delimiter = ',',
+        conflicts_with = "pivot"
+    )]
+    pub align: Vec<String>,
+
+    /// Make table columns at least WIDTH characters wide: `8` for every column, or per column
+    /// like `words=8`, so counts line up from one run to the next
+    #[arg(
+        long,
+        value_name = "WIDTH",
+        value_delimiter = ',',
+        conflicts_with = "pivot"
+    )]
+    pub min_width: Vec<String>,
+
+    /// Number the rows of the table and CSV output in a first column, to point at "row 42"
+    #[arg(long, conflicts_with = "pivot")]
+    pub row_numbers: bool,
+
+    /// Label the totals row LABEL instead of `total`; subtotal rows read `LABEL (local)` and so on
+    #[arg(long, value_name = "LABEL", default_value = "total")]
+    pub total_label: String,
+
+    /// Add rows under the table, e.g. `--footer files,errors,elapsed`
+    #[arg(
+        long,
+        value_enum,
+        value_name = "ROWS",
+        value_delimiter = ',',
+        conflicts_with = "pivot"
+    )]
+    pub footer: Vec<Footer>,
+
+    /// Count all files as one stream in a single row, like `cat FILES | wc`, so words and lines
+    /// running from the end of one file into the next are counted once
+    #[arg(long, conflicts_with_all = ["group_by", "archives", "urls_from"])]
+    pub concat: bool,
+
+    /// Stop at the first file that cannot be counted, instead of counting the rest and listing
+    /// every failure (with its causes) after the results
+    #[arg(long)]
+    pub fail_fast: bool,
+
+    /// Count N files at the same time; defaults to the number of CPUs. Rows keep the order of
+    /// the files given either way
+    #[arg(short = 'j', long, value_name = "N")]
+    pub jobs: Option<std::num::NonZeroUsize>,
+
+    /// Show on stderr how many files have been counted so far, when stderr is a terminal
+    #[arg(long)]
+    pub progress: bool,
+
+    /// Record every counted file in FILE, so a run that is interrupted (Ctrl-C, a crash) can be
+    /// started again with the same FILE and skip the files it already counted. FILE is deleted
+    /// once the run has gone through every file
+    #[arg(long, value_name = "FILE", conflicts_with = "concat")]
+    pub resume: Option<PathBuf>,
+
+    /// Pre-commit hook mode: print only the files that exceed a `--max-*` limit, one per line
+    #[arg(long)]
+    pub hook: bool,
+
+    /// Fail when a file has more than N lines
+    #[arg(long, value_name = "N")]
+    pub max_lines: Option<usize>,
+
+    /// Fail when a file has more than N bytes
+    #[arg(long, value_name = "N")]
+    pub max_bytes: Option<usize>,
+
+    /// Fail when a file has more than N characters
+    #[arg(long, value_name = "N")]
+    pub max_chars: Option<usize>,
+
+    /// Fail when a file has more than N words
+    #[arg(long, value_name = "N")]
+    pub max_words: Option<usize>,
+
+    /// Fail when the total of a metric is under N, e.g. `words=300`, or with --per-file when
+    /// any file's is
+    #[arg(long, value_name = "METRIC=N", value_delimiter = ',')]
+    pub fail_under: Vec<crate::command::MinCount>,
+
+    /// Fail when any file's count in a column is over N, e.g. `banned=0` or `lines=1000`
+    #[arg(long, value_name = "COLUMN=N", value_delimiter = ',')]
+    pub fail_over: Vec<crate::command::MaxCount>,
+
+    /// Check --fail-under against every file instead of the totals, reporting each file under
+    /// a limit
+    #[arg(long, requires = "fail_under")]
+    pub per_file: bool,
+
+    /// Report files whose lines differ by more than N fields (0 for any difference) with
+    /// --fields-delim, and exit with a failure status
+    #[arg(long, value_name = "N", requires = "fields_delim")]
+    pub max_field_spread: Option<usize>,
+
+    /// Fail when a file has more than N suspicious characters (0 for any) with --suspicious-chars
+    #[arg(long, value_name = "N", requires = "suspicious_chars")]
+    pub max_suspicious_chars: Option<usize>,
+
+    /// Start from the flags of `[profile.NAME]` in the nearest wcx.toml; flags given on the
+    /// command line override them
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Repeat the previous counting run with the same arguments, in the directory it ran in
+    #[arg(long, exclusive = true)]
+    pub again: bool,
+
+    /// Append this run's totals to the local history (see `wcx history`)
+    #[arg(long)]
+    pub record: bool,
+
+    /// Count every file below the directories given, in name order. Hidden files and
+    /// directories (names starting with `.`) are skipped, and so are the files ignored by
+    /// `.gitignore` and `.ignore` files
+    #[arg(short = 'r', long)]
+    pub recursive: bool,
+
+    /// With --recursive, also count hidden files and directories
+    #[arg(long, requires = "recursive")]
+    pub hidden: bool,
+
+    /// With --recursive, also count the files ignored by `.gitignore` and `.ignore` files
+    #[arg(long, requires = "recursive", overrides_with = "respect_gitignore")]
+    pub no_ignore: bool,
+
+    /// With --recursive, skip the files ignored by `.gitignore` and `.ignore` files (the default;
+    /// overrides an earlier --no-ignore, e.g. from a profile)
+    #[arg(long, requires = "recursive", overrides_with = "no_ignore")]
+    pub respect_gitignore: bool,
+
+    /// With --recursive, go at most N directories deep: 1 counts only the files directly in the
+    /// directories given
+    #[arg(long, value_name = "N", requires = "recursive")]
+    pub max_depth: Option<usize>,
+
+    /// With --recursive, walk the files and directories symbolic links point to. A link back to
+    /// a directory it is in is reported and skipped
+    #[arg(long, requires = "recursive", overrides_with = "no_follow")]
+    pub follow_symlinks: bool,
+
+    /// With --recursive, skip symbolic links (the default; overrides an earlier
+    /// --follow-symlinks)
+    #[arg(long, requires = "recursive", overrides_with = "follow_symlinks")]
+    pub no_follow: bool,
+
+    /// Also count the files listed in FILE, one path per line (`-` reads the list from stdin).
+    /// Blank lines and lines starting with `#` are skipped
+    #[arg(long, value_name = "FILE")]
+    pub files_from: Option<PathBuf>,
+
+    /// Also count the files listed in FILE, separated by NUL bytes as `find -print0` writes them
+    /// (`-` reads the list from stdin), like GNU wc's option of the same name
+    #[arg(long, value_name = "FILE")]
+    pub files0_from: Option<PathBuf>,
+
+    /// Fetch and count the URLs listed in FILE, one per line (`-` reads the list from stdin).
+    /// Adds Status, Content type and Error columns; failed URLs are reported in their row
+    #[arg(long, value_name = "FILE")]
+    pub urls_from: Option<PathBuf>,
+
+    /// Give up on a request to a URL that has not connected, or has not started to answer, after
+    /// this many seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Give up on a URL whose body has not finished downloading after this many seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = 300)]
+    pub body_timeout: u64,
+
+    /// Retry a URL this many times after a connection error, timeout, 429 or 5xx status, waiting
+    /// twice as long (plus jitter) before each retry
+    #[arg(long, value_name = "N", default_value_t = 2)]
+    pub retries: u32,
+
+    /// Download URLs no faster than RATE bytes per second in total, e.g. `500K` or `1M`
+    #[cfg(feature = "remote")]
+    #[arg(long, value_name = "RATE")]
+    pub limit_rate: Option<crate::throttle::Rate>,
+
+    /// Count 1 or many files. Without any, wcx lets you pick files below the current directory
+    /// when run in a terminal
+    #[arg(num_args = 1..)]
     pub files: Vec<PathBuf>,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Print a single compact count segment (e.g. `12k words`) for shell prompts
+    Prompt {
+        /// File or directory to count
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Metric shown in the segment
+        #[arg(short = 'm', long, value_enum, default_value_t = Metric::Words)]
+        metric: Metric,
+    },
+
+    /// Track daily progress towards a word-count goal (e.g. NaNoWriMo)
+    Goal {
+        #[command(subcommand)]
+        action: GoalAction,
+    },
+
+    /// Show how the totals of runs made with `--record` grew over time
+    History {
+        /// Draw a sparkline of one metric instead of the table
+        #[arg(long)]
+        chart: bool,
+
+        /// Metric drawn by `--chart`
+        #[arg(short = 'm', long, value_enum, default_value_t = Metric::Words)]
+        metric: Metric,
+
+        /// Include runs recorded in every directory, not just the current one
+        #[arg(long)]
+        all: bool,
+
+        /// Only show the most recent N runs
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+    },
+
+    /// Attribute lines and words of git-tracked files to their authors using git blame
+    #[cfg(feature = "git")]
+    Authors {
+        /// Files or directories to attribute
+        #[arg(default_value = ".", num_args = 1..)]
+        paths: Vec<PathBuf>,
+    },
+
+    /// Report words added and removed in each modified file compared to HEAD
+    #[cfg(feature = "git")]
+    Changed {
+        /// Revision to compare the working tree against
+        #[arg(long, default_value = "HEAD")]
+        base: String,
+
+        /// Only report changes below these paths
+        paths: Vec<PathBuf>,
+    },
+
+    /// Check files against the per-path limits of a budgets file and fail on violations
+    Check {
+        /// Budgets file; globs in it are relative to its directory
+        #[arg(long, default_value = "wcx.budgets.toml")]
+        budgets: PathBuf,
+
+        /// Fail when `warn` budgets are violated more than N times
+        #[arg(long, value_name = "N")]
+        max_warnings: Option<usize>,
+
+        /// Only fail on violations that are new or worse than in this baseline file
+        #[arg(long, value_name = "BASELINE")]
+        ratchet: Option<PathBuf>,
+
+        /// Write the current violations to the `--ratchet` baseline instead of checking
+        #[arg(long, requires = "ratchet")]
+        update_baseline: bool,
+    },
+
+    /// Measure the source files in a package (.crate, .jar, .whl, .tar.gz or .zip)
+    Pkg {
+        /// Package file, e.g. serde-1.0.200.crate, or a package to download from crates.io or
+        /// PyPI: crates:serde@1.0.200, pypi:requests (latest version)
+        package: String,
+
+        /// List every source file instead of one row per language
+        #[arg(long)]
+        files: bool,
+
+        /// Whether a last line without a trailing newline counts as a line, as for counting
+        #[arg(long, value_enum, value_name = "WHEN", default_value_t = IncompleteLine::No)]
+        count_incomplete_line: IncompleteLine,
+
+        /// Which line endings are counted, as for counting
+        #[arg(long, value_enum, value_name = "STYLE", default_value_t = EolStyle::Lf)]
+        eol_style: EolStyle,
+    },
+
+    /// Compare files side by side, highlighting the largest and smallest value of each metric
+    Vs {
+        /// Files to compare, e.g. three drafts of the same document
+        #[arg(required = true, num_args = 2..)]
+        files: Vec<PathBuf>,
+    },
+
+    /// Inventory files with their size, SHA-256 hash and counts, and check them against it later
+    Manifest {
+        #[command(subcommand)]
+        action: ManifestAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ManifestAction {
+    /// Print a manifest of every file below the paths, e.g. `wcx manifest create docs > MANIFEST`
+    Create {
+        /// Files or directories to inventory
+        #[arg(default_value = ".", num_args = 1..)]
+        paths: Vec<PathBuf>,
+    },
+
+    /// Re-count the files of a manifest and report the ones that changed, are missing or are new
+    Verify {
+        /// Manifest written by `wcx manifest create`
+        manifest: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum GoalAction {
+    /// Set or replace the word goal of a project
+    Set {
+        /// Number of words to reach
+        target: usize,
+
+        /// File or directory holding the project
+        #[arg(long, default_value = ".")]
+        project: PathBuf,
+
+        /// Date the goal should be reached by (YYYY-MM-DD)
+        #[arg(long)]
+        deadline: Option<NaiveDate>,
+    },
+
+    /// Record today's word count and report progress, daily delta and projected completion
+    Status {
+        /// File or directory holding the project
+        #[arg(long, default_value = ".")]
+        project: PathBuf,
+    },
+}
+
+/// One of the four wcx count modes, for subcommands that report a single metric
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Lines,
+    Bytes,
+    Chars,
+    Words,
+}
+
+/// How the byte count of a file is measured
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ByteSize {
+    /// Length of the file content
+    #[default]
+    Apparent,
+    /// Bytes allocated on disk (512-byte blocks), smaller than the content for sparse files
+    Blocks,
+}
+
+/// Which whitespace-separated tokens count as words
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum WordMode {
+    /// Every token between whitespace, like `wc`
+    #[default]
+    Whitespace,
+    /// Only tokens with at least one alphanumeric character, so `---` or `*` are not words
+    Strict,
+}
+
+/// Whether a last line that does not end in a newline is counted
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum IncompleteLine {
+    /// Count it, so every line with content counts, like editors show them
+    Yes,
+    /// Count only newlines, like `wc`
+    #[default]
+    No,
+}
+
+/// Which bytes end a line
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum EolStyle {
+    /// Use `cr` for files without a single `\n`, `lf` otherwise
+    Auto,
+    /// `\n`, so CRLF lines count too
+    #[default]
+    Lf,
+    /// Only `\r\n`
+    Crlf,
+    /// `\r`, as written by classic Mac OS and some legacy exporters
+    Cr,
+    /// `\n`, `\r\n` and lone `\r`, each counted once
+    Any,
+}
+
+/// Kinds of records `--records` counts
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Records {
+    /// Separated by NUL bytes
+    Nul,
+}
+
+/// A single-byte separator, parsed from `--record-delim` and `--fields-delim`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Delimiter(pub u8);
+
+impl std::str::FromStr for Delimiter {
+    type Err = String;
+
+    fn from_str(delimiter: &str) -> Result<Self, Self::Err> {
+        let byte = match delimiter {
+            "\\0" => Some(b'\0'),
+            "\\t" => Some(b'\t'),
+            "\\n" => Some(b'\n'),
+            "\\r" => Some(b'\r'),
+            _ => match delimiter.strip_prefix("\\x") {
+                Some(hex) if hex.len() == 2 => u8::from_str_radix(hex, 16).ok(),
+                Some(_) => None,
+                None => match delimiter.as_bytes() {
+                    [byte] if byte.is_ascii() => Some(*byte),
+                    _ => None,
+                },
+            },
+        };
+
+        byte.map(Delimiter).ok_or_else(|| {
+            format!(
+                "`{}` is not a single byte (expected an ASCII character or an escape like \\0 \
+                 or \\x1e)",
+                delimiter
+            )
+        })
+    }
+}
+
+/// How the content of files is interpreted
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// Count the whole file as text
+    #[default]
+    Text,
+    /// Count words and characters in the data cells of CSV files (not the header row); lines
+    /// and bytes still cover the whole file
+    Csv,
+    /// Count words and characters in the string values of JSON files (not keys or numbers)
+    Json,
+    /// Count words and characters in the visible text of HTML files (not tags, scripts or
+    /// styles)
+    Html,
+    /// Add the number of keys, the nesting depth and the comment lines of YAML, TOML and INI
+    /// files
+    Config,
+    /// Add the number of cues, the words in them and their total on-screen duration of SRT and
+    /// WebVTT subtitles
+    Subtitle,
+    /// Add the number of types, fields and comment lines of Protocol Buffers, GraphQL and
+    /// Thrift schemas
+    Idl,
+    /// Add the number of files touched, lines added, lines removed and hunks of unified diffs
+    Patch,
+    /// Add the number of messages, header lines and body words of mbox and .eml files
+    Mail,
+}
+
+/// What `--longest-word` shows
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LongestWord {
+    /// The length of the longest word
+    Length,
+    /// Its length, and the word itself (cut after 40 characters)
+    Text,
+}
+
+/// Statistics computed from the base counts
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Derived {
+    /// Words divided by lines; minified files stand out with a huge value
+    AvgWordsPerLine,
+    /// Characters per word, not counting the whitespace between words
+    AvgWordLen,
+}
+
+/// Rows added under the table with `--footer`
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Footer {
+    /// How many files (archive entries and URLs included) were counted
+    Files,
+    /// How many files and URLs could not be counted
+    Errors,
+    /// How long counting took; left out with --deterministic
+    Elapsed,
+}
+
+/// Where the results are printed as
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Output {
+    /// A table styled by `-f`
+    #[default]
+    Table,
+    /// Comma-separated values with a header row
+    Csv,
+    /// A JSON object with one entry per file
+    Json,
+    /// A Markdown (GitHub-flavored) table
+    Markdown,
+    /// Labeled `metric: value` lines, a block per file, for screen readers
+    ScreenReader,
+}
+
 impl Args {
-    pub fn parse_args() -> Self {
-        Self::parse()
+    pub fn parse_args() -> anyhow::Result<Self> {
+        Self::parse_resolved(std::env::args_os().collect())
     }
+
+    /// Parses `argv` (the program name first) on top of the flags of the `--profile` it selects
+    /// (or `WCX_PROFILE`) and the `WCX_*` environment variables: the command line overrides the
+    /// environment, which overrides the profile. Exits on invalid arguments, like
+    /// [`Parser::parse`]. `--again` is parsed alone, as the run it repeats resolves its own.
+    pub fn parse_resolved(argv: Vec<OsString>) -> anyhow::Result<Self> {
+        Self::parse_resolved_from(argv, |name| std::env::var(name).ok())
+    }
+
+    /// Parses `argv` like [`Args::parse_resolved`], reading environment variables with `var`.
+    pub fn parse_resolved_from(
+        mut argv: Vec<OsString>,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> anyhow::Result<Self> {
+        expand_subcommand(&mut argv)?;
+        let args = Self::parse_from(&argv);
+        if args.command.is_some() || args.again {
+            return Ok(args);
+        }
+
+        let from_environment = crate::environment::arguments_from(&Self::command(), &var)?;
+        let profile = args
+            .profile
+            .clone()
+            .or_else(|| var(&format!("{}PROFILE", crate::environment::PREFIX)));
+        let from_profile = match profile {
+            Some(name) => crate::profile::arguments(&name)?,
+            None => Vec::new(),
+        };
+
+        let mut resolved: Vec<OsString> = argv[..1].to_vec();
+        resolved.extend(from_profile.into_iter().map(OsString::from));
+        resolved.extend(from_environment.into_iter().map(OsString::from));
+        resolved.extend(argv[1..].iter().cloned());
+
+        let mut args = Self::parse_from(resolved);
+        args.files = crate::walk::expand_globs(&args.files)?;
+        Ok(args)
+    }
+}
+
+/// Expands an abbreviated subcommand in `argv`, so `wcx hist` runs `wcx history`. An argument
+/// naming an existing file is left alone, as are ones that match no subcommand (they are files
+/// that do not exist yet, and fail as such). Fails when the abbreviation is ambiguous, like
+/// `wcx ch` for `check` and `changed`.
+fn expand_subcommand(argv: &mut [OsString]) -> anyhow::Result<()> {
+    let Some(first) = argv.get(1).and_then(|arg| arg.to_str()) else {
+        return Ok(());
+    };
+    if first.starts_with('-') || std::path::Path::new(first).exists() {
+        return Ok(());
+    }
+
+    let command = Args::command();
+    let subcommands: Vec<(&str, &str)> = command
+        .get_subcommands()
+        .map(|subcommand| (subcommand.get_name(), subcommand.get_name()))
+        .collect();
+
+    match crate::abbrev::matches(first, &subcommands).as_slice() {
+        [(_, name)] => argv[1] = OsString::from(name),
+        [] => {}
+        found => anyhow::bail!(
+            "Ambiguous command `{}`: could be {}",
+            first,
+            found
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<&str>>()
+                .join(", ")
+        ),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_bare_archive_password_leaves_the_files_alone() {
+    let args = Args::try_parse_from(["wcx", "--archives", "--archive-password", "b.zip"])
+        .expect("Failed to parse");
+    assert_eq!(args.archive_password.as_deref(), Some(""));
+    assert_eq!(args.files, vec![PathBuf::from("b.zip")]);
+
+    let args = Args::try_parse_from(["wcx", "--archives", "--archive-password=pw", "b.zip"])
+        .expect("Failed to parse");
+    assert_eq!(args.archive_password.as_deref(), Some("pw"));
+
+    assert!(Args::try_parse_from(["wcx", "--archive-password=pw", "b.zip"]).is_err());
+}
+
+#[test]
+fn test_filtered_totals_needs_a_filter() {
+    assert!(Args::try_parse_from(["wcx", "--filtered-totals", "a.txt"]).is_err());
+
+    let args = Args::try_parse_from([
+        "wcx",
+        "--filtered-totals",
+        "--filter-name",
+        "src/",
+        "--where",
+        "lines>1",
+        "a.txt",
+    ])
+    .expect("Failed to parse");
+    assert!(args.filtered_totals);
 }
@@ -0,0 +1,113 @@
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// How `--sort file:...` compares file names.
+#[derive(Debug)]
+pub enum Collation {
+    /// Byte by byte, the default.
+    Bytes,
+    /// Runs of digits by their value, so `log.2` comes before `log.10`.
+    Natural,
+    /// By the collation rules of the locale in `LC_ALL`, `LC_COLLATE` or `LANG`.
+    #[cfg(feature = "collation")]
+    Locale(Box<icu_collator::Collator>),
+}
+
+impl Collation {
+    /// Parses the part of a sort key after `:`, `natural` or `locale`.
+    pub fn parse(name: &str) -> anyhow::Result<Collation> {
+        match name {
+            "natural" => Ok(Collation::Natural),
+            "locale" => locale(),
+            _ => anyhow::bail!("Unknown ordering `{}` (expected natural or locale)", name),
+        }
+    }
+
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            Collation::Bytes => a.cmp(b),
+            Collation::Natural => natural(a, b).then_with(|| a.cmp(b)),
+            #[cfg(feature = "collation")]
+            Collation::Locale(collator) => collator.compare(a, b).then_with(|| a.cmp(b)),
+        }
+    }
+}
+
+#[cfg(feature = "collation")]
+fn locale() -> anyhow::Result<Collation> {
+    // `de_DE.UTF-8` names the `de-DE` locale; `C` and `POSIX` name none, sorting by the root
+    // collation rules.
+    let name = ["LC_ALL", "LC_COLLATE", "LANG"]
+        .iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .find(|name| !name.is_empty())
+        .unwrap_or_default();
+    let name = name.split(['.', '@']).next().unwrap_or_default();
+    let locale: icu_provider::DataLocale = match name {
+        "" | "C" | "POSIX" => Default::default(),
+        name => name
+            .replace('_', "-")
+            .parse()
+            .map_err(|err| anyhow::anyhow!("Unknown locale `{}`: {}", name, err))?,
+    };
+
+    let collator = icu_collator::Collator::try_new(&locale, Default::default())
+        .map_err(|err| anyhow::anyhow!("No collation rules for the locale `{}`: {}", name, err))?;
+    Ok(Collation::Locale(Box::new(collator)))
+}
+
+#[cfg(not(feature = "collation"))]
+fn locale() -> anyhow::Result<Collation> {
+    anyhow::bail!("Cannot sort by locale: wcx was built without the `collation` feature")
+}
+
+/// Compares `a` and `b` character by character, except for runs of ASCII digits, which are
+/// compared by their value.
+fn natural(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+
+    loop {
+        let ordering = match (a.peek(), b.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (x, y) = (digits(&mut a), digits(&mut b));
+                let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.cmp(y);
+                a.next();
+                b.next();
+                ordering
+            }
+        };
+
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+fn digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    digits
+}
+
+#[test]
+fn test_natural_order_compares_numbers_by_value() {
+    let mut names = vec![
+        "log.10", "log.2", "log.1", "log", "log.02", "a10b2", "a10b10",
+    ];
+    names.sort_by(|a, b| Collation::Natural.compare(a, b));
+
+    assert_eq!(
+        names,
+        vec!["a10b2", "a10b10", "log", "log.1", "log.02", "log.2", "log.10"]
+    );
+}
@@ -1,55 +1,783 @@
-use crate::analyze::{analyze_file, FileResult};
+use crate::abbrev;
+use crate::analyze::{
+    self, analyze_content, analyze_file, analyze_reader, analyze_selected, read_reader,
+    AnalyzeOptions, Analyzer, CancellationToken, FileResult, Session, SpecialFile,
+};
+use crate::archive::{self, ArchiveLimits};
+use crate::banned::BannedWords;
+use crate::cli::{
+    Args, ByteSize, Delimiter, Derived, EolStyle, Footer, IncompleteLine, LongestWord, Metric,
+    Mode, Output, Records, WordMode,
+};
+use crate::collate::Collation;
+use crate::diagnostic;
+use crate::group::GroupBy;
+use crate::history;
+use crate::layout::Layout;
+use crate::output;
+use crate::pager::Paging;
+use crate::paths;
+#[cfg(feature = "remote")]
+use crate::remote;
+use crate::resume::Resume;
+use crate::select::Selector;
+#[cfg(feature = "spell")]
+use crate::spell::Dictionary;
+use crate::stats::{self, MeasureOptions, Stat};
+#[cfg(feature = "remote")]
+use crate::walk::read_url_list;
+use crate::walk::{read_file0_list, read_file_list, walk_files, WalkOptions};
+use crate::wrap::{self, PageSize};
+use anyhow::Context;
+use clap::ValueEnum;
 use prettytable::{
     format::{self, TableFormat},
     Cell, Row, Table,
 };
-use std::path::PathBuf;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{IsTerminal, Read};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use wcx_core::WcxError;
+
+/// The label of the single row `--concat` prints.
+const CONCATENATED: &str = "(concatenated)";
+
+/// Other names `--columns` and `--sort` accept for some columns, next to their keys.
+const ALIASES: [(&str, Column); 5] = [
+    ("loc", Column::Lines),
+    ("size", Column::Bytes),
+    ("disk_bytes", Column::Bytes),
+    ("characters", Column::Chars),
+    ("comments", Column::Stat(Stat::CommentLines)),
+];
+
+/// A count column of the results table. The File column always follows them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Column {
+    Lines,
+    Bytes,
+    Chars,
+    Words,
+    MaxLineLength,
+    DisplayLines,
+    Pages,
+    Records,
+    PartialRecord,
+    MinFields,
+    MaxFields,
+    AvgFields,
+    AvgWordsPerLine,
+    AvgWordLen,
+    LongestWord,
+    LongestWordText,
+    Spaces,
+    Tabs,
+    Nbsp,
+    OtherWhitespace,
+    SuspiciousChars,
+    Confusables,
+    Misspelled,
+    Banned,
+    Unbalanced,
+    DataBytes,
+    Sparse,
+    Generated,
+    Bom,
+    Status,
+    ContentType,
+    Error,
+    Stat(Stat),
+}
+
+impl Column {
+    /// Returns the title written into the header row for this column.
+    pub fn title(&self, byte_size: ByteSize) -> &'static str {
+        match self {
+            Column::Lines => "Lines",
+            Column::Bytes => match byte_size {
+                ByteSize::Apparent => "Bytes",
+                ByteSize::Blocks => "Disk bytes",
+            },
+            Column::Chars => "Chars",
+            Column::Words => "Words",
+            Column::MaxLineLength => "Max line",
+            Column::DisplayLines => "Display lines",
+            Column::Pages => "Pages",
+            Column::Records => "Records",
+            Column::PartialRecord => "Partial",
+            Column::MinFields => "Min fields",
+            Column::MaxFields => "Max fields",
+            Column::AvgFields => "Avg fields",
+            Column::AvgWordsPerLine => "Words/line",
+            Column::AvgWordLen => "Word len",
+            Column::LongestWord => "Longest word",
+            Column::LongestWordText => "Longest word text",
+            Column::Spaces => "Spaces",
+            Column::Tabs => "Tabs",
+            Column::Nbsp => "NBSP",
+            Column::OtherWhitespace => "Other ws",
+            Column::SuspiciousChars => "Suspicious",
+            Column::Confusables => "Confusables",
+            Column::Misspelled => "Misspelled",
+            Column::Banned => "Banned",
+            Column::Unbalanced => "Unbalanced",
+            Column::DataBytes => "Data",
+            Column::Sparse => "Sparse",
+            Column::Generated => "Generated",
+            Column::Bom => "BOM",
+            Column::Status => "Status",
+            Column::ContentType => "Content type",
+            Column::Error => "Error",
+            Column::Stat(stat) => stat.title(),
+        }
+    }
+
+    /// Returns the field name of this column in machine-readable output.
+    pub fn key(&self, byte_size: ByteSize) -> &'static str {
+        match self {
+            Column::Lines => "lines",
+            Column::Bytes => match byte_size {
+                ByteSize::Apparent => "bytes",
+                ByteSize::Blocks => "disk_bytes",
+            },
+            Column::Chars => "chars",
+            Column::Words => "words",
+            Column::MaxLineLength => "max_line_length",
+            Column::DisplayLines => "display_lines",
+            Column::Pages => "pages",
+            Column::Records => "records",
+            Column::PartialRecord => "partial_record",
+            Column::MinFields => "min_fields",
+            Column::MaxFields => "max_fields",
+            Column::AvgFields => "avg_fields",
+            Column::AvgWordsPerLine => "avg_words_per_line",
+            Column::AvgWordLen => "avg_word_len",
+            Column::LongestWord => "longest_word",
+            Column::LongestWordText => "longest_word_text",
+            Column::Spaces => "spaces",
+            Column::Tabs => "tabs",
+            Column::Nbsp => "nbsp",
+            Column::OtherWhitespace => "other_whitespace",
+            Column::SuspiciousChars => "suspicious_chars",
+            Column::Confusables => "confusables",
+            Column::Misspelled => "misspelled",
+            Column::Banned => "banned",
+            Column::Unbalanced => "unbalanced",
+            Column::DataBytes => "data_bytes",
+            Column::Sparse => "sparse",
+            Column::Generated => "generated",
+            Column::Bom => "bom",
+            Column::Status => "status",
+            Column::ContentType => "content_type",
+            Column::Error => "error",
+            Column::Stat(stat) => stat.key(),
+        }
+    }
+
+    /// Returns every column with the names it is known by: its key and any aliases.
+    fn names() -> Vec<(&'static str, Column)> {
+        let mut columns = vec![
+            Column::Lines,
+            Column::Bytes,
+            Column::Chars,
+            Column::Words,
+            Column::MaxLineLength,
+            Column::DisplayLines,
+            Column::Pages,
+            Column::Records,
+            Column::PartialRecord,
+            Column::MinFields,
+            Column::MaxFields,
+            Column::AvgFields,
+            Column::AvgWordsPerLine,
+            Column::AvgWordLen,
+            Column::LongestWord,
+            Column::LongestWordText,
+            Column::Spaces,
+            Column::Tabs,
+            Column::Nbsp,
+            Column::OtherWhitespace,
+            Column::SuspiciousChars,
+            Column::Confusables,
+            Column::Misspelled,
+            Column::Banned,
+            Column::Unbalanced,
+            Column::DataBytes,
+            Column::Sparse,
+            Column::Generated,
+            Column::Bom,
+            Column::Status,
+            Column::ContentType,
+            Column::Error,
+        ];
+        columns.extend(Stat::ALL.map(Column::Stat));
+
+        let mut names: Vec<(&str, Column)> = columns
+            .into_iter()
+            .map(|column| (column.key(ByteSize::Apparent), column))
+            .collect();
+        names.extend(ALIASES);
+        names
+    }
+
+    /// Finds a column by its key (`words`, `avg-word-len`), an alias (`loc`) or an unambiguous
+    /// prefix of either (`wo`).
+    pub fn parse(name: &str) -> anyhow::Result<Column> {
+        abbrev::resolve(name, &Self::names(), "column")
+    }
+
+    /// Returns the cell value of this column for one file. Sources that could not be counted
+    /// have blank counts.
+    pub fn value(&self, file_result: &FileResult) -> String {
+        if file_result.error.is_some() && self.has_total() {
+            return String::new();
+        }
+
+        match self {
+            Column::Lines => file_result.lines.to_string(),
+            Column::Bytes => file_result.bytes.to_string(),
+            Column::Chars => file_result.chars.to_string(),
+            Column::Words => file_result.words.to_string(),
+            Column::MaxLineLength => file_result.max_line_length.to_string(),
+            Column::DisplayLines => file_result.display_lines.to_string(),
+            Column::Pages => file_result.pages.to_string(),
+            Column::Records => file_result.records.to_string(),
+            Column::PartialRecord => match file_result.partial_record {
+                0 => String::new(),
+                partial => partial.to_string(),
+            },
+            Column::MinFields => file_result
+                .min_fields
+                .map_or_else(|| String::from("-"), |min| min.to_string()),
+            Column::MaxFields => file_result.max_fields.to_string(),
+            Column::AvgFields | Column::AvgWordsPerLine | Column::AvgWordLen => {
+                match self.ratio(file_result) {
+                    Some(ratio) => format!("{:.2}", ratio),
+                    None => String::from("-"),
+                }
+            }
+            Column::LongestWord => file_result.longest_word.to_string(),
+            Column::LongestWordText => file_result.longest_word_text.clone().unwrap_or_default(),
+            Column::Spaces => file_result.whitespace.spaces.to_string(),
+            Column::Tabs => file_result.whitespace.tabs.to_string(),
+            Column::Nbsp => file_result.whitespace.nbsp.to_string(),
+            Column::OtherWhitespace => file_result.whitespace.other.to_string(),
+            Column::SuspiciousChars => file_result.suspicious_chars.to_string(),
+            Column::Confusables => file_result.confusables.to_string(),
+            Column::Misspelled => file_result.misspelled.to_string(),
+            Column::Banned => file_result.banned.to_string(),
+            Column::Unbalanced => file_result.unbalanced.to_string(),
+            Column::DataBytes => file_result.data_bytes.to_string(),
+            Column::Sparse => match file_result.sparse {
+                true => String::from("sparse"),
+                false => String::new(),
+            },
+            Column::Generated => file_result
+                .generated
+                .map(|generated| generated.to_string())
+                .unwrap_or_default(),
+            Column::Bom => file_result
+                .bom
+                .map(|bom| bom.to_string())
+                .unwrap_or_default(),
+            Column::Status => file_result
+                .status
+                .map(|status| status.to_string())
+                .unwrap_or_default(),
+            Column::ContentType => file_result.content_type.clone().unwrap_or_default(),
+            Column::Error => file_result.error.clone().unwrap_or_default(),
+            Column::Stat(stat) => file_result
+                .stats
+                .get(stat)
+                .map(|value| stat.format(*value))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the value of this column for one file as JSON. Sources that could not be counted
+    /// have `null` counts.
+    pub fn json(&self, file_result: &FileResult) -> serde_json::Value {
+        if file_result.error.is_some() && self.has_total() {
+            return serde_json::Value::Null;
+        }
+
+        match self {
+            Column::Lines => file_result.lines.into(),
+            Column::Bytes => file_result.bytes.into(),
+            Column::Chars => file_result.chars.into(),
+            Column::Words => file_result.words.into(),
+            Column::MaxLineLength => file_result.max_line_length.into(),
+            Column::DisplayLines => file_result.display_lines.into(),
+            Column::Pages => file_result.pages.into(),
+            Column::Records => file_result.records.into(),
+            Column::PartialRecord => file_result.partial_record.into(),
+            Column::MinFields => file_result.min_fields.into(),
+            Column::MaxFields => file_result.max_fields.into(),
+            Column::AvgFields | Column::AvgWordsPerLine | Column::AvgWordLen => {
+                self.ratio(file_result).into()
+            }
+            Column::LongestWord => file_result.longest_word.into(),
+            Column::LongestWordText => file_result.longest_word_text.clone().into(),
+            Column::Spaces => file_result.whitespace.spaces.into(),
+            Column::Tabs => file_result.whitespace.tabs.into(),
+            Column::Nbsp => file_result.whitespace.nbsp.into(),
+            Column::OtherWhitespace => file_result.whitespace.other.into(),
+            Column::SuspiciousChars => file_result.suspicious_chars.into(),
+            Column::Confusables => file_result.confusables.into(),
+            Column::Misspelled => file_result.misspelled.into(),
+            Column::Banned => file_result.banned.into(),
+            Column::Unbalanced => file_result.unbalanced.into(),
+            Column::DataBytes => file_result.data_bytes.into(),
+            Column::Sparse => file_result.sparse.into(),
+            Column::Generated => file_result
+                .generated
+                .map(|generated| generated.to_string())
+                .into(),
+            Column::Bom => file_result.bom.map(|bom| bom.to_string()).into(),
+            Column::Status => file_result.status.into(),
+            Column::ContentType => file_result.content_type.clone().into(),
+            Column::Error => file_result.error.clone().into(),
+            Column::Stat(stat) => file_result.stats.get(stat).copied().into(),
+        }
+    }
+
+    /// Returns the value of a derived column, or `None` when it is undefined (no lines or no
+    /// words). Totals use the ratio of the summed counts.
+    fn ratio(&self, file_result: &FileResult) -> Option<f64> {
+        let (numerator, denominator) = match self {
+            Column::AvgWordsPerLine => (file_result.words, file_result.lines),
+            Column::AvgWordLen => (file_result.word_chars, file_result.words),
+            Column::AvgFields => (file_result.fields, file_result.field_lines),
+            _ => return None,
+        };
+
+        (denominator > 0).then(|| numerator as f64 / denominator as f64)
+    }
+
+    /// Whether the column has a value in the totals row. Markers and fetch details have no
+    /// total.
+    pub fn has_total(&self) -> bool {
+        !matches!(
+            self,
+            Column::PartialRecord
+                | Column::Sparse
+                | Column::Generated
+                | Column::Bom
+                | Column::Status
+                | Column::ContentType
+                | Column::Error
+        )
+    }
+
+    /// Returns the cell value of this column in the totals row. Markers have no total.
+    pub fn total(&self, totals: &FileResult) -> String {
+        match self.has_total() {
+            true => self.value(totals),
+            false => String::new(),
+        }
+    }
+}
+
+/// What `--sort` orders the rows by, smallest first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortKey {
+    /// The file name, or the group name when grouping.
+    Label,
+    /// The extension of the file name, like `md`; names without one come first.
+    Extension,
+    Column(Column),
+}
+
+impl SortKey {
+    /// Parses a column name like [`Column::parse`], `file` (or the title of the grouping, like
+    /// `language`) for the labels, or `ext` for their extensions.
+    pub fn parse(name: &str, group_by: Option<GroupBy>) -> anyhow::Result<SortKey> {
+        let group = group_by.map(|group_by| group_by.title().to_lowercase());
+
+        let mut candidates: Vec<(&str, SortKey)> = Column::names()
+            .into_iter()
+            .map(|(name, column)| (name, SortKey::Column(column)))
+            .collect();
+        candidates.push(("file", SortKey::Label));
+        candidates.push(("ext", SortKey::Extension));
+        if let Some(group) = &group {
+            candidates.push((group, SortKey::Label));
+        }
+
+        abbrev::resolve(name, &candidates, "sort key")
+    }
+
+    fn compare(&self, a: &(String, FileResult), b: &(String, FileResult)) -> Ordering {
+        match self {
+            SortKey::Label => a.0.cmp(&b.0),
+            SortKey::Extension => {
+                let extension = |label: &str| Path::new(label).extension().map(|e| e.to_owned());
+                extension(&a.0).cmp(&extension(&b.0))
+            }
+            SortKey::Column(column) => compare_values(&column.json(&a.1), &column.json(&b.1)),
+        }
+    }
+}
+
+/// One of the keys of a [`SortOrder`].
+#[derive(Debug)]
+struct SortBy {
+    key: SortKey,
+    descending: bool,
+    /// How labels are compared, for the `file` key.
+    collation: Collation,
+}
+
+/// The keys `--sort` orders the rows by, like `ext,-lines`: rows are ordered by the first key,
+/// rows equal in it by the second, and so on. A key starting with `-` orders largest first, and
+/// `file:natural` or `file:locale` compares file names as [`Collation`] describes.
+#[derive(Debug)]
+pub struct SortOrder {
+    keys: Vec<SortBy>,
+}
+
+impl SortOrder {
+    /// Parses comma-separated keys, each named like [`SortKey::parse`].
+    pub fn parse(keys: &str, group_by: Option<GroupBy>) -> anyhow::Result<SortOrder> {
+        let keys = keys
+            .split(',')
+            .map(|key| {
+                let key = key.trim();
+                let (key, descending) = match key.strip_prefix('-') {
+                    Some(key) => (key, true),
+                    None => (key, false),
+                };
+                let (key, collation) = match key.split_once(':') {
+                    Some((key, collation)) => (key, Collation::parse(collation)?),
+                    None => (key, Collation::Bytes),
+                };
+
+                let key = SortKey::parse(key, group_by)?;
+                if key != SortKey::Label && !matches!(collation, Collation::Bytes) {
+                    anyhow::bail!("Only file names can be sorted `natural` or by `locale`");
+                }
+                Ok(SortBy {
+                    key,
+                    descending,
+                    collation,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(SortOrder { keys })
+    }
+
+    fn compare(&self, a: &(String, FileResult), b: &(String, FileResult)) -> Ordering {
+        self.keys
+            .iter()
+            .map(|sort_by| {
+                let (a, b) = match sort_by.descending {
+                    true => (b, a),
+                    false => (a, b),
+                };
+                match sort_by.key {
+                    SortKey::Label => sort_by.collation.compare(&a.0, &b.0),
+                    key => key.compare(a, b),
+                }
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Orders column values: blanks first, then numbers by value, and other values as text.
+fn compare_values(a: &serde_json::Value, b: &serde_json::Value) -> Ordering {
+    use serde_json::Value;
+
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .unwrap_or_default()
+            .total_cmp(&b.as_f64().unwrap_or_default()),
+        (Value::Null, Value::Null) => Ordering::Equal,
+        (Value::Null, _) => Ordering::Less,
+        (_, Value::Null) => Ordering::Greater,
+        (a, b) => a.to_string().cmp(&b.to_string()),
+    }
+}
+
+/// Where a row's content came from, for the subtotals of runs that mix sources.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Source {
+    /// A regular local file.
+    Local,
+    /// A FIFO or character device read with `--read-special`.
+    Stream,
+    /// An entry of an archive expanded with `--archives`.
+    Archive,
+    /// A URL fetched with `--urls-from`.
+    #[cfg(feature = "remote")]
+    Remote,
+}
+
+impl Source {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Source::Local => "local",
+            Source::Stream => "stream",
+            Source::Archive => "archive",
+            #[cfg(feature = "remote")]
+            Source::Remote => "remote",
+        }
+    }
+}
 
-/// Used to keep track of the String titles that the TableManager will insert into the header row
-/// of the prettyTable::Table
-struct TableHeaders {
-    lines: String,
-    bytes: String,
-    words: String,
-    chars: String,
-    file: String,
+/// The label of the subtotal row of `source`, e.g. `total (remote)` for the `total` label.
+pub fn subtotal_label(total_label: &str, source: Source) -> String {
+    format!("{} ({})", total_label, source.name())
 }
 
 /// Adds up total counts for each wcx flag enabled.
 ///
 ///
 /// New instances of `TotalsCounter` are obtained via [`TotalsCounter::new(files_len)`], where `files_len` is the number of
-/// files being provided to the TableManager. The TotalsCounter will be set to 'enabled' if more than one file is provided.
-
+/// files being provided to the TableManager. The TotalsCounter will be set to 'enabled' if more than one file is provided,
+/// which decides whether the totals row is written; the counts are summed either way.
 ///
 /// See function level documentation for details on the various configuration
 /// settings.
 ///
 /// [`build`]: method@Self::add_to_totals
 pub struct TotalsCounter {
-    enabled: bool,
-    lines_total: usize,
-    bytes_total: usize,
-    chars_total: usize,
-    words_total: usize,
+    pub enabled: bool,
+    totals: FileResult,
+    by_source: BTreeMap<Source, FileResult>,
 }
 
 impl TotalsCounter {
     pub fn new(files_len: usize) -> TotalsCounter {
         TotalsCounter {
             enabled: files_len > 1,
-            lines_total: 0,
-            bytes_total: 0,
-            chars_total: 0,
-            words_total: 0,
+            totals: Default::default(),
+            by_source: BTreeMap::new(),
+        }
+    }
+
+    pub fn add_to_totals(&mut self, file_result: &FileResult, source: Source) {
+        self.totals.add(file_result);
+        self.by_source.entry(source).or_default().add(file_result);
+    }
+
+    /// Returns the summed counts as a `FileResult`.
+    pub fn totals(&self) -> FileResult {
+        self.totals.clone()
+    }
+
+    /// Returns the summed counts of every kind of source, when there is more than one.
+    pub fn subtotals(&self) -> Vec<(Source, FileResult)> {
+        match self.by_source.len() {
+            0 | 1 => Vec::new(),
+            _ => self
+                .by_source
+                .iter()
+                .map(|(source, totals)| (*source, totals.clone()))
+                .collect(),
         }
     }
+}
+
+/// A lower limit set with `--fail-under`, parsed from `METRIC=N` like `words=300`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MinCount {
+    pub metric: Metric,
+    pub min: usize,
+}
+
+impl FromStr for MinCount {
+    type Err = String;
+
+    fn from_str(limit: &str) -> Result<Self, Self::Err> {
+        let (metric, min) = limit.split_once('=').ok_or_else(|| {
+            format!(
+                "invalid limit `{}` (expected METRIC=N, e.g. words=300)",
+                limit
+            )
+        })?;
+        let metric = Metric::from_str(metric.trim(), true).map_err(|_| {
+            format!(
+                "unknown metric `{}` (expected lines, bytes, chars or words)",
+                metric
+            )
+        })?;
+        let min = min
+            .trim()
+            .parse()
+            .map_err(|_| format!("`{}` is not a number", min))?;
+
+        Ok(MinCount { metric, min })
+    }
+}
+
+impl MinCount {
+    /// Returns the message reporting that `label`, counting `file_result`, is under the limit,
+    /// e.g. `intro.md: 120 words is under --fail-under words=300`.
+    fn shortfall(&self, label: &str, file_result: &FileResult) -> Option<String> {
+        let count = file_result.count(self.metric);
+        let metric = self.metric.to_possible_value()?;
+
+        (count < self.min).then(|| {
+            format!(
+                "{}: {} {} is under --fail-under {}={}",
+                label,
+                count,
+                metric.get_name(),
+                metric.get_name(),
+                self.min
+            )
+        })
+    }
+}
+
+/// An upper limit set with `--fail-over`, parsed from `COLUMN=N` like `banned=0`. Any numeric
+/// column can be limited.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MaxCount {
+    pub column: Column,
+    pub max: usize,
+}
+
+impl FromStr for MaxCount {
+    type Err = String;
+
+    fn from_str(limit: &str) -> Result<Self, Self::Err> {
+        let (column, max) = limit.split_once('=').ok_or_else(|| {
+            format!(
+                "invalid limit `{}` (expected COLUMN=N, e.g. banned=0)",
+                limit
+            )
+        })?;
+        let column = Column::parse(column.trim()).map_err(|err| err.to_string())?;
+        let max = max
+            .trim()
+            .parse()
+            .map_err(|_| format!("`{}` is not a number", max))?;
+
+        Ok(MaxCount { column, max })
+    }
+}
+
+impl MaxCount {
+    /// Returns the message reporting that `label`, counting `file_result`, is over the limit,
+    /// e.g. `guide.md: 2 banned exceeds --fail-over banned=0`.
+    fn excess(&self, label: &str, file_result: &FileResult) -> Option<String> {
+        let count = self.column.json(file_result).as_f64()?;
+        let key = self.column.key(ByteSize::Apparent);
+
+        (count > self.max as f64).then(|| {
+            format!(
+                "{}: {} {} exceeds --fail-over {}={}",
+                label, count, key, key, self.max
+            )
+        })
+    }
+}
+
+/// Per-file upper limits set with the `--max-*` flags and `--fail-over`, and lower limits set
+/// with `--fail-under` for every file or for the totals. Every file over (or under) a limit is
+/// reported as a violation, and any violation makes wcx exit with a failure status.
+#[derive(Default, Clone)]
+pub struct Thresholds {
+    pub max_lines: Option<usize>,
+    pub max_bytes: Option<usize>,
+    pub max_chars: Option<usize>,
+    pub max_words: Option<usize>,
+    pub fail_under: Vec<MinCount>,
+    /// Whether `fail_under` applies to every file rather than to the totals.
+    pub per_file: bool,
+    /// How many more fields the longest line of a file may have than the shortest.
+    pub max_field_spread: Option<usize>,
+    pub max_suspicious_chars: Option<usize>,
+    pub fail_over: Vec<MaxCount>,
+}
+
+impl Thresholds {
+    /// Returns one message per limit that `file_result` exceeds, in lines, bytes, chars, words,
+    /// suspicious-chars order, e.g. `src/main.rs: 1200 lines exceeds --max-lines 1000`, then one
+    /// per `--fail-over` limit it exceeds, one per `--fail-under` limit it is under when those
+    /// apply to every file, and last one when its lines differ by more than `--max-field-spread`
+    /// fields.
+    pub fn violations(&self, file: &Path, file_result: &FileResult) -> Vec<String> {
+        let checks = [
+            (self.max_lines, file_result.lines, "lines"),
+            (self.max_bytes, file_result.bytes, "bytes"),
+            (self.max_chars, file_result.chars, "chars"),
+            (self.max_words, file_result.words, "words"),
+            (
+                self.max_suspicious_chars,
+                file_result.suspicious_chars,
+                "suspicious-chars",
+            ),
+        ];
+
+        checks
+            .into_iter()
+            .filter_map(|(max, count, metric)| match max {
+                Some(max) if count > max => Some(format!(
+                    "{}: {} {} exceeds --max-{} {}",
+                    paths::display(file),
+                    count,
+                    metric,
+                    metric,
+                    max
+                )),
+                _ => None,
+            })
+            .chain(
+                self.fail_over
+                    .iter()
+                    .filter_map(|max_count| max_count.excess(&paths::display(file), file_result)),
+            )
+            .chain(
+                self.fail_under
+                    .iter()
+                    .filter(|_| self.per_file)
+                    .filter_map(|min_count| {
+                        min_count.shortfall(&paths::display(file), file_result)
+                    }),
+            )
+            .chain(self.field_spread(file, file_result))
+            .collect()
+    }
+
+    fn field_spread(&self, file: &Path, file_result: &FileResult) -> Option<String> {
+        let (max_spread, min) = (self.max_field_spread?, file_result.min_fields?);
+        let max = file_result.max_fields;
+
+        (max - min > max_spread).then(|| {
+            format!(
+                "{}: lines have {} to {} fields, more apart than --max-field-spread {}",
+                paths::display(file),
+                min,
+                max,
+                max_spread
+            )
+        })
+    }
 
-    pub fn add_to_totals(&mut self, file_result: &FileResult) {
-        self.lines_total += file_result.lines;
-        self.bytes_total += file_result.bytes;
-        self.chars_total += file_result.chars;
-        self.words_total += file_result.words;
+    /// Returns one message per `--fail-under` limit the totals are under, when those apply to
+    /// the totals.
+    pub fn total_violations(&self, total_label: &str, totals: &FileResult) -> Vec<String> {
+        match self.per_file {
+            true => Vec::new(),
+            false => self
+                .fail_under
+                .iter()
+                .filter_map(|min_count| min_count.shortfall(total_label, totals))
+                .collect(),
+        }
     }
 }
 
@@ -64,19 +792,38 @@ impl TotalsCounter {
 ///
 /// # Example
 ///
-/// ```
-///    // build table manager
-///    let table_manager = Builder::new()
-///        .enable_flags(false, true, true, false)
-///        .table_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR)
-///        .build(files_len)
+/// ```ignore
+/// use wcx::command::Builder;
+///
+/// let files_len = 2;
+/// let table_manager = Builder::new()
+///     .enable_flags(false, true, true, false)
+///     .table_format("no_border_line_separator")
+///     .build(files_len);
 /// ```
 pub struct Builder {
-    lines_enabled: bool,
-    bytes_enabled: bool,
-    words_enabled: bool,
-    chars_enabled: bool,
+    options: AnalyzeOptions,
+    read_special: bool,
+    read_limit: Option<usize>,
+    deterministic: bool,
+    pivot: bool,
+    no_filename: bool,
+    total_label: String,
+    layout: Layout,
+    exclude_generated: bool,
+    group_by: Option<GroupBy>,
+    archives: Option<ArchiveLimits>,
+    archive_password: Option<String>,
+    urls: bool,
+    mode: Mode,
+    measure_options: MeasureOptions,
+    selector: Option<Selector>,
+    derived: Vec<Derived>,
+    columns: Vec<Column>,
     table_format: Option<TableFormat>,
+    ascii: bool,
+    row_numbers: bool,
+    thresholds: Thresholds,
 }
 
 impl Builder {
@@ -85,11 +832,28 @@ impl Builder {
     /// Configuration methods can be chained on the return value.
     pub fn new() -> Builder {
         Builder {
-            lines_enabled: false,
-            bytes_enabled: false,
-            words_enabled: false,
-            chars_enabled: false,
+            options: Default::default(),
+            read_special: false,
+            read_limit: None,
+            deterministic: false,
+            pivot: false,
+            no_filename: false,
+            total_label: String::from("total"),
+            layout: Default::default(),
+            exclude_generated: false,
+            group_by: None,
+            archives: None,
+            archive_password: None,
+            urls: false,
+            mode: Mode::Text,
+            measure_options: Default::default(),
+            selector: None,
+            derived: Vec::new(),
+            columns: Vec::new(),
             table_format: None,
+            ascii: false,
+            row_numbers: false,
+            thresholds: Default::default(),
         }
     }
 
@@ -100,7 +864,9 @@ impl Builder {
     /// chars_enabled: The number of characters in each input file is written to the table.
     /// words_enablec: The number of words in each input file is written to the table.
     ///
-    /// If no flags are provided, each of the count modes will be written to the table.
+    /// If no flags are provided, each of the count modes will be written to the table, unless
+    /// [`Builder::max_line_length`] was enabled first: like `wc -L`, it shows only the longest
+    /// line by itself.
     pub fn enable_flags(
         &mut self,
         lines_enabled: bool,
@@ -108,19 +874,286 @@ impl Builder {
         chars_enabled: bool,
         words_enabled: bool,
     ) -> &mut Self {
-        let default: bool = !lines_enabled && !bytes_enabled && !chars_enabled && !words_enabled;
+        let default: bool = !lines_enabled
+            && !bytes_enabled
+            && !chars_enabled
+            && !words_enabled
+            && !self.options.max_line_length;
+
+        self.options.lines = lines_enabled || default;
+        self.options.bytes = bytes_enabled || default;
+        self.options.chars = chars_enabled || default;
+        self.options.words = words_enabled || default;
+        self
+    }
+
+    /// Adds the Max line column, measuring the longest line in display columns. Its total is
+    /// the longest line of all files rather than a sum.
+    pub fn max_line_length(&mut self, max_line_length: bool) -> &mut Self {
+        self.options.max_line_length = max_line_length;
+        self
+    }
+
+    /// Sets how byte counts are measured; see [`ByteSize`].
+    pub fn byte_size(&mut self, byte_size: ByteSize) -> &mut Self {
+        self.options.byte_size = byte_size;
+        self
+    }
+
+    /// Sets which tokens count as words; see [`WordMode`].
+    pub fn word_mode(&mut self, word_mode: WordMode) -> &mut Self {
+        self.options.word_mode = word_mode;
+        self
+    }
+
+    /// Sets whether a last line without a trailing newline is counted; see [`IncompleteLine`].
+    pub fn incomplete_line(&mut self, incomplete_line: IncompleteLine) -> &mut Self {
+        self.options.incomplete_line = incomplete_line;
+        self
+    }
+
+    /// Sets which line endings are counted; see [`EolStyle`].
+    pub fn eol_style(&mut self, eol_style: EolStyle) -> &mut Self {
+        self.options.eol_style = eol_style;
+        self
+    }
+
+    /// Adds the Display lines column, counting the lines of text wrapped at `wrap_width`
+    /// columns.
+    pub fn wrap_width(&mut self, wrap_width: Option<usize>) -> &mut Self {
+        self.options.wrap_width = wrap_width;
+        self
+    }
+
+    /// Adds the Pages column, estimating the pages of `page_size` the text fills when printed.
+    pub fn print_pages(&mut self, page_size: Option<PageSize>) -> &mut Self {
+        self.options.page_size = page_size;
+        self
+    }
+
+    /// Adds the Records column, counting records separated by `record_delim`.
+    pub fn record_delim(&mut self, record_delim: Option<u8>) -> &mut Self {
+        self.options.record_delim = record_delim;
+        self
+    }
+
+    /// Adds the Records column, counting fixed-length records of `record_size` bytes, and the
+    /// Partial column with the bytes of a last record cut short.
+    pub fn record_size(&mut self, record_size: Option<NonZeroUsize>) -> &mut Self {
+        self.options.record_size = record_size.map(NonZeroUsize::get);
+        self
+    }
+
+    /// Adds the Min fields, Max fields and Avg fields columns, splitting every line into fields
+    /// on `fields_delim`.
+    pub fn fields_delim(&mut self, fields_delim: Option<u8>) -> &mut Self {
+        self.options.fields_delim = fields_delim;
+        self
+    }
+
+    /// Adds the Longest word column, and the Longest word text column when `longest_word` asks
+    /// for the text.
+    pub fn longest_word(&mut self, longest_word: Option<LongestWord>) -> &mut Self {
+        self.options.longest_word = longest_word.is_some();
+        self.options.longest_word_text = longest_word == Some(LongestWord::Text);
+        self
+    }
+
+    /// Adds the Spaces, Tabs, NBSP and Other ws columns, counting whitespace by kind.
+    pub fn whitespace_stats(&mut self, whitespace_stats: bool) -> &mut Self {
+        self.options.whitespace_stats = whitespace_stats;
+        self
+    }
+
+    /// Adds the Suspicious column, counting zero-width and bidirectional control characters.
+    pub fn suspicious_chars(&mut self, suspicious_chars: bool) -> &mut Self {
+        self.options.suspicious_chars = suspicious_chars;
+        self
+    }
+
+    /// Adds the Confusables column, counting letters and digits that pass for ASCII ones.
+    pub fn confusables(&mut self, confusables: bool) -> &mut Self {
+        self.options.confusables = confusables;
+        self
+    }
+
+    /// Adds the Misspelled column, counting the prose words missing from `dictionary`.
+    #[cfg(feature = "spell")]
+    pub fn spell(&mut self, dictionary: Option<Arc<Dictionary>>) -> &mut Self {
+        self.options.spell = dictionary;
+        self
+    }
+
+    /// Adds the Banned column, counting the matches of `banned_words`.
+    pub fn banned_words(&mut self, banned_words: Option<Arc<BannedWords>>) -> &mut Self {
+        self.options.banned_words = banned_words;
+        self
+    }
+
+    /// Adds the Unbalanced column, counting quotes and brackets without a partner.
+    pub fn balance(&mut self, balance: bool) -> &mut Self {
+        self.options.balance = balance;
+        self
+    }
+
+    /// Adds columns with statistics derived from the counts. The counts they need are taken
+    /// even when their own columns are not shown.
+    pub fn derived(&mut self, derived: &[Derived]) -> &mut Self {
+        self.derived = derived.to_vec();
+        self
+    }
+
+    /// Shows only `columns`, in this order, counting just what they need (and what the
+    /// thresholds check). Empty keeps the columns the other settings choose.
+    pub fn columns(&mut self, columns: &[Column]) -> &mut Self {
+        self.columns = columns.to_vec();
+        self
+    }
+
+    /// Combines the files into one row per group instead of one row per file.
+    pub fn group_by(&mut self, group_by: Option<GroupBy>) -> &mut Self {
+        self.group_by = group_by;
+        self
+    }
+
+    /// Counts the files inside archives, within `limits`, instead of the archives themselves. A
+    /// depth of 0 counts archives as plain files.
+    pub fn archives(&mut self, limits: Option<ArchiveLimits>) -> &mut Self {
+        self.archives = limits;
+        self
+    }
+
+    /// Sets the password used to decrypt encrypted zip entries.
+    pub fn archive_password(&mut self, password: Option<String>) -> &mut Self {
+        self.archive_password = password;
+        self
+    }
+
+    /// Reads files as `mode`, adding a column for each structural measurement it takes.
+    pub fn mode(&mut self, mode: Mode) -> &mut Self {
+        self.mode = mode;
+        self
+    }
+
+    /// With the mail mode, leaves quoted lines of replies out of the body words.
+    pub fn exclude_quoted(&mut self, exclude_quoted: bool) -> &mut Self {
+        self.measure_options.exclude_quoted = exclude_quoted;
+        self
+    }
+
+    /// Counts words and characters only in the parts of each file `selector` picks.
+    pub fn selector(&mut self, selector: Option<Selector>) -> &mut Self {
+        self.selector = selector;
+        self
+    }
+
+    /// Adds the Status, Content type and Error columns describing how each URL was fetched.
+    pub fn urls(&mut self, urls: bool) -> &mut Self {
+        self.urls = urls;
+        self
+    }
 
-        self.lines_enabled = lines_enabled || default;
-        self.bytes_enabled = bytes_enabled || default;
-        self.chars_enabled = chars_enabled || default;
-        self.words_enabled = words_enabled || default;
+    /// Adds the Generated column, which flags files that look machine-generated.
+    pub fn detect_generated(&mut self, detect_generated: bool) -> &mut Self {
+        self.options.detect_generated = detect_generated;
+        self
+    }
+
+    /// Leaves files that look machine-generated out of the totals, so they reflect hand-written
+    /// content. Turns on detection.
+    pub fn exclude_generated(&mut self, exclude_generated: bool) -> &mut Self {
+        self.exclude_generated = exclude_generated;
+        self.options.detect_generated |= exclude_generated;
+        self
+    }
+
+    /// Adds the BOM column, naming the byte order mark each file starts with.
+    pub fn detect_bom(&mut self, detect_bom: bool) -> &mut Self {
+        self.options.detect_bom = detect_bom;
+        self
+    }
+
+    /// Counts a UTF-8 byte order mark as a character, as part of the first word, instead of
+    /// leaving it out of the characters and words.
+    pub fn keep_bom(&mut self, keep_bom: bool) -> &mut Self {
+        self.options.keep_bom = keep_bom;
+        self
+    }
+
+    /// Maps every file into memory to count it, not only the large ones.
+    #[cfg(feature = "mmap")]
+    pub fn mmap(&mut self, mmap: bool) -> &mut Self {
+        self.options.mmap = mmap;
+        self
+    }
+
+    /// Adds the Data column (bytes actually holding data) and the Sparse marker column.
+    pub fn sparse(&mut self, sparse: bool) -> &mut Self {
+        self.options.sparse = sparse;
+        self
+    }
+
+    /// Streams FIFOs and character devices instead of skipping them. Use together with
+    /// [`Builder::read_limit`] to stop reading endless devices.
+    pub fn read_special(&mut self, read_special: bool) -> &mut Self {
+        self.read_special = read_special;
+        self
+    }
+
+    /// Reads at most `read_limit` bytes from stdin, FIFOs and character devices.
+    pub fn read_limit(&mut self, read_limit: Option<usize>) -> &mut Self {
+        self.read_limit = read_limit;
+        self
+    }
+
+    /// Shows files by their path relative to the current directory with `/` separators, so
+    /// the output does not depend on where or on which platform it was produced.
+    pub fn deterministic(&mut self, deterministic: bool) -> &mut Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    /// Prints one row per column and one column per file (or group) instead, which reads better
+    /// when comparing a few files across many metrics.
+    pub fn pivot(&mut self, pivot: bool) -> &mut Self {
+        self.pivot = pivot;
+        self
+    }
+
+    /// Leaves out the File column (or the group column), so the output is bare counts.
+    pub fn no_filename(&mut self, no_filename: bool) -> &mut Self {
+        self.no_filename = no_filename;
+        self
+    }
+
+    /// Sets how the table columns are aligned and how wide they are at least.
+    pub fn layout(&mut self, layout: Layout) -> &mut Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Draws the table with ASCII characters only, whatever its format.
+    pub fn ascii(&mut self, ascii: bool) -> &mut Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// Numbers the rows of the table and CSV output in a first `#` (`row`) column.
+    pub fn row_numbers(&mut self, row_numbers: bool) -> &mut Self {
+        self.row_numbers = row_numbers;
+        self
+    }
+
+    /// Sets the label of the totals row, `total` by default.
+    pub fn total_label(&mut self, total_label: &str) -> &mut Self {
+        self.total_label = total_label.to_string();
         self
     }
 
     /// Updates table format configuration value, which will be updated onto the actual table once
     /// Builder::build is called
-    pub fn table_format(&mut self, format: &String) -> &mut Self {
-        let format = match format.as_str() {
+    pub fn table_format(&mut self, format: &str) -> &mut Self {
+        let format = match format {
             "default" => *format::consts::FORMAT_DEFAULT,
             "no_title" => *format::consts::FORMAT_NO_TITLE,
             "no_linesep_with_title" => *format::consts::FORMAT_NO_LINESEP_WITH_TITLE,
@@ -138,11 +1171,18 @@ impl Builder {
         self
     }
 
+    /// Sets the per-file limits each analyzed file is checked against.
+    pub fn thresholds(&mut self, thresholds: Thresholds) -> &mut Self {
+        self.thresholds = thresholds;
+        self
+    }
+
     /// Creates the `Table` and sets its format if one was provided. Uses the enable flags to
-    /// correctly insert titles into the header row of the `Table`.
+    /// decide which columns the table has and inserts their titles into the header row.
     ///
-    /// Then creates the configured `TableManager`, which houses the enable flags, `Table`, and
-    /// `TotalsCounter`. The returned `TableManager` can now be used to add more rows to the table.
+    /// Then creates the configured `TableManager`, which houses the analysis options, columns,
+    /// `Table`, and `TotalsCounter`. The returned `TableManager` can now be used to add more rows
+    /// to the table.
     ///
     pub fn build(&mut self, files_len: usize) -> TableManager {
         let totals_counter: TotalsCounter = TotalsCounter::new(files_len);
@@ -150,178 +1190,1877 @@ impl Builder {
         let mut table: Table = Table::new();
 
         if let Some(table_format) = self.table_format {
-            table.set_format(table_format);
+            table.set_format(match self.ascii {
+                true => ascii_format(table_format),
+                false => table_format,
+            });
         }
 
-        let headers: TableHeaders = TableHeaders {
-            lines: String::from("Lines"),
-            bytes: String::from("Bytes"),
-            words: String::from("Words"),
-            chars: String::from("Chars"),
-            file: String::from("File"),
+        let candidates = [
+            (self.options.lines, Column::Lines),
+            (self.options.bytes, Column::Bytes),
+            (self.options.chars, Column::Chars),
+            (self.options.words, Column::Words),
+            (self.options.max_line_length, Column::MaxLineLength),
+            (self.options.wrap_width.is_some(), Column::DisplayLines),
+            (self.options.page_size.is_some(), Column::Pages),
+            (
+                self.options.record_delim.is_some() || self.options.record_size.is_some(),
+                Column::Records,
+            ),
+            (self.options.record_size.is_some(), Column::PartialRecord),
+            (self.options.fields_delim.is_some(), Column::MinFields),
+            (self.options.fields_delim.is_some(), Column::MaxFields),
+            (self.options.fields_delim.is_some(), Column::AvgFields),
+            (
+                self.derived.contains(&Derived::AvgWordsPerLine),
+                Column::AvgWordsPerLine,
+            ),
+            (
+                self.derived.contains(&Derived::AvgWordLen),
+                Column::AvgWordLen,
+            ),
+            (self.options.longest_word, Column::LongestWord),
+            (self.options.longest_word_text, Column::LongestWordText),
+            (self.options.whitespace_stats, Column::Spaces),
+            (self.options.whitespace_stats, Column::Tabs),
+            (self.options.whitespace_stats, Column::Nbsp),
+            (self.options.whitespace_stats, Column::OtherWhitespace),
+            (self.options.suspicious_chars, Column::SuspiciousChars),
+            (self.options.confusables, Column::Confusables),
+            (self.options.spells(), Column::Misspelled),
+            (self.options.banned_words.is_some(), Column::Banned),
+            (self.options.balance, Column::Unbalanced),
+            (self.options.sparse, Column::DataBytes),
+            (self.options.sparse, Column::Sparse),
+            (self.options.detect_generated, Column::Generated),
+            (self.options.detect_bom, Column::Bom),
+            (self.urls, Column::Status),
+            (self.urls, Column::ContentType),
+            (self.urls, Column::Error),
+        ];
+        let columns: Vec<Column> = match self.columns.is_empty() {
+            true => candidates
+                .into_iter()
+                .filter_map(|(enabled, column)| enabled.then_some(column))
+                .chain(
+                    stats::columns(self.mode)
+                        .iter()
+                        .map(|stat| Column::Stat(*stat)),
+                )
+                .collect(),
+            false => self.columns.clone(),
         };
 
-        let mut headers_buffer: Vec<Cell> = Vec::new();
-
-        if self.lines_enabled {
-            headers_buffer.push(Cell::new(&headers.lines).style_spec("b"));
-        };
+        let mut headers_buffer: Vec<Cell> = columns
+            .iter()
+            .map(|column| {
+                let title = column.title(self.options.byte_size);
+                let title = self.layout.pad_title(SortKey::Column(*column), title);
+                Cell::new(&title).style_spec("b")
+            })
+            .collect();
 
-        if self.bytes_enabled {
-            headers_buffer.push(Cell::new(&headers.bytes).style_spec("b"));
+        if !self.no_filename {
+            let label_title = self.group_by.map_or("File", |group_by| group_by.title());
+            let label_title = self.layout.pad_title(SortKey::Label, label_title);
+            headers_buffer.push(Cell::new(&label_title).style_spec("b"));
         }
-
-        if self.chars_enabled {
-            headers_buffer.push(Cell::new(&headers.chars).style_spec("b"));
+        for (cell, key) in headers_buffer
+            .iter_mut()
+            .zip(column_keys(&columns, self.no_filename))
+        {
+            if let Some(align) = self.layout.align(key) {
+                cell.align(align.alignment());
+            }
         }
-
-        if self.words_enabled {
-            headers_buffer.push(Cell::new(&headers.words).style_spec("b"));
+        if self.row_numbers {
+            headers_buffer.insert(0, Cell::new("#").style_spec("b"));
         }
-
-        headers_buffer.push(Cell::new(&headers.file).style_spec("b"));
         table.set_titles(Row::new(headers_buffer));
 
+        let mut options = self.options.clone();
+        if !self.columns.is_empty() {
+            let thresholds = &self.thresholds;
+            options.lines = columns.contains(&Column::Lines) || thresholds.max_lines.is_some();
+            options.bytes = columns.contains(&Column::Bytes) || thresholds.max_bytes.is_some();
+            options.chars = columns.contains(&Column::Chars) || thresholds.max_chars.is_some();
+            options.words = columns.contains(&Column::Words) || thresholds.max_words.is_some();
+            options.sparse |=
+                columns.contains(&Column::DataBytes) || columns.contains(&Column::Sparse);
+            options.detect_generated |= columns.contains(&Column::Generated);
+            options.detect_bom |= columns.contains(&Column::Bom);
+            options.longest_word_text |= columns.contains(&Column::LongestWordText);
+            options.suspicious_chars |= columns.contains(&Column::SuspiciousChars);
+            options.confusables |= columns.contains(&Column::Confusables);
+            options.balance |= columns.contains(&Column::Unbalanced);
+            options.max_line_length |= columns.contains(&Column::MaxLineLength);
+            if columns.contains(&Column::DisplayLines) {
+                options.wrap_width.get_or_insert(wrap::DEFAULT_WRAP_WIDTH);
+            }
+            if columns.contains(&Column::Pages) {
+                options.page_size.get_or_insert_with(PageSize::default);
+            }
+            options.whitespace_stats |= columns.iter().any(|column| {
+                matches!(
+                    column,
+                    Column::Spaces | Column::Tabs | Column::Nbsp | Column::OtherWhitespace
+                )
+            });
+            options.longest_word |=
+                options.longest_word_text || columns.contains(&Column::LongestWord);
+        }
+        // Lower limits are checked whether their metric is shown or not.
+        for min_count in &self.thresholds.fail_under {
+            match min_count.metric {
+                Metric::Lines => options.lines = true,
+                Metric::Bytes => options.bytes = true,
+                Metric::Chars => options.chars = true,
+                Metric::Words => options.words = true,
+            }
+        }
+        for column in &columns {
+            match column {
+                Column::AvgWordsPerLine => {
+                    options.lines = true;
+                    options.words = true;
+                }
+                Column::AvgWordLen => {
+                    options.words = true;
+                    options.word_chars = true;
+                }
+                _ => {}
+            }
+        }
+
         TableManager {
-            lines_enabled: self.lines_enabled,
-            bytes_enabled: self.bytes_enabled,
-            chars_enabled: self.chars_enabled,
-            words_enabled: self.words_enabled,
+            options,
+            read_special: self.read_special,
+            read_limit: self.read_limit,
+            deterministic: self.deterministic,
+            stable_root: PathBuf::new(),
+            pivot: self.pivot,
+            no_filename: self.no_filename,
+            total_label: self.total_label.clone(),
+            layout: self.layout.clone(),
+            row_numbers: self.row_numbers,
+            exclude_generated: self.exclude_generated,
+            group_by: self.group_by,
+            archives: self.archives,
+            archive_password: self.archive_password.clone(),
+            mode: self.mode,
+            measure_options: self.measure_options,
+            selector: self.selector.clone(),
+            groups: BTreeMap::new(),
+            columns,
+            rows: Vec::new(),
             table,
             totals_counter,
+            analyzed: 0,
+            thresholds: self.thresholds.clone(),
+            violations: Vec::new(),
+            resume: None,
+            stdin_read: AtomicBool::new(false),
         }
     }
 }
 
 /// The TableManager is responsible for orchestrating the process building each row of the table
-/// according to the enabled flags that are set, and using it's TotalsCounter to build the final row if needed
+/// according to its columns, and using it's TotalsCounter to build the final row if needed
 ///
 pub struct TableManager {
-    pub lines_enabled: bool,
-    pub bytes_enabled: bool,
-    pub chars_enabled: bool,
-    pub words_enabled: bool,
+    pub options: AnalyzeOptions,
+    pub read_special: bool,
+    pub read_limit: Option<usize>,
+    pub deterministic: bool,
+    /// What `--deterministic` shows paths outside the current directory relative to; see
+    /// [`paths::stable`].
+    pub stable_root: PathBuf,
+    pub pivot: bool,
+    pub no_filename: bool,
+    pub total_label: String,
+    pub layout: Layout,
+    pub row_numbers: bool,
+    pub exclude_generated: bool,
+    pub group_by: Option<GroupBy>,
+    pub archives: Option<ArchiveLimits>,
+    pub archive_password: Option<String>,
+    pub mode: Mode,
+    pub measure_options: MeasureOptions,
+    pub selector: Option<Selector>,
+    /// The summed counts of every group, when grouping.
+    pub groups: BTreeMap<String, FileResult>,
+    pub columns: Vec<Column>,
+    /// The label (file or group name) and counts of every row, for the machine-readable outputs.
+    pub rows: Vec<(String, FileResult)>,
     pub table: Table,
     pub totals_counter: TotalsCounter,
+    /// How many files, archive entries and URLs were counted.
+    pub analyzed: usize,
+    pub thresholds: Thresholds,
+    pub violations: Vec<String>,
+    /// The files counted by an earlier, interrupted run with `--resume`, and where to record
+    /// the files counted by this one.
+    pub resume: Option<Resume>,
+    /// Whether stdin was counted already, as it can only be read once.
+    pub stdin_read: AtomicBool,
+}
+
+/// The counts of a file, or of one entry of an archive, waiting to be added as a row.
+pub struct Counted {
+    label: String,
+    file: PathBuf,
+    source: Source,
+    /// The content of archive entries, kept only with `--group-by`, which may need it to tell
+    /// their group.
+    content: Option<Vec<u8>>,
+    file_result: FileResult,
+}
+
+impl Counted {
+    /// The counts of content counted some other way, such as content already in memory, to be
+    /// added as the row `label`.
+    pub fn new(label: String, source: Source, file_result: FileResult) -> Counted {
+        Counted {
+            file: PathBuf::from(&label),
+            label,
+            source,
+            content: None,
+            file_result,
+        }
+    }
 }
 
 impl TableManager {
     pub fn set_table_row(&mut self, file: &PathBuf) -> anyhow::Result<()> {
-        let mut row_values: Vec<Cell> = Vec::new();
+        let counted = self.count_file(file)?;
+        self.add_counted(counted);
+        Ok(())
+    }
 
-        let file_result: FileResult = analyze_file(
-            file,
-            self.lines_enabled,
-            self.bytes_enabled,
-            self.chars_enabled,
-            self.words_enabled,
-        )?;
+    /// Counts `file`, or every entry of it when it is an archive, without adding any rows yet.
+    /// Only reads the table manager, so several files can be counted at the same time and
+    /// added in order afterwards with [`TableManager::add_counted`].
+    pub fn count_file(&self, file: &PathBuf) -> anyhow::Result<Vec<Counted>> {
+        let label = match self.deterministic {
+            true => paths::stable(file, &self.stable_root),
+            false => paths::display(file),
+        };
 
-        if self.totals_counter.enabled {
-            self.totals_counter.add_to_totals(&file_result);
+        if paths::is_stdin(file) {
+            return self.count_stdin(file, label);
         }
 
-        self.set_row_values(&mut row_values, file, &file_result);
-        self.table.add_row(Row::new(row_values));
+        if let Some(limits) = self
+            .archives
+            .filter(|limits| limits.depth > 0 && archive::is_archive(file))
+        {
+            // One unreadable entry (such as an image when counting words, or an entry encrypted
+            // with another password) does not stop the rest of the archive from being counted.
+            // Entries are only kept for grouping, as they wait for every file to be counted.
+            let mut counted = Vec::new();
+            let password = self.archive_password.as_deref();
+            archive::expand(file, &label, &limits, password, |entry| {
+                let result = entry.content.and_then(|content| {
+                    let file_result = self.analyze_content(&entry.label, &content)?;
+                    Ok((content, file_result))
+                });
 
-        Ok(())
+                match result {
+                    Ok((content, file_result)) => counted.push(Counted {
+                        label: entry.label,
+                        file: PathBuf::from(entry.name),
+                        source: Source::Archive,
+                        content: self.group_by.is_some().then_some(content),
+                        file_result,
+                    }),
+                    Err(err) => eprintln!("wcx: skipping {}: {:#}", entry.label, err),
+                }
+            })?;
+
+            return Ok(counted);
+        }
+
+        if let Some(file_result) = self.resume.as_ref().and_then(|resume| resume.get(file)) {
+            return Ok(vec![Counted {
+                label,
+                file: file.clone(),
+                source: Source::Local,
+                content: None,
+                file_result: file_result.clone(),
+            }]);
+        }
+
+        let metadata = analyze::metadata(file)?;
+
+        let reads_content = self.selector.is_some() || !stats::columns(self.mode).is_empty();
+        let special = SpecialFile::detect(&metadata);
+        let file_result: FileResult = match special {
+            None if reads_content => {
+                let content = analyze::read(file)?;
+                self.analyze_content(&label, &content)?
+            }
+            None => analyze_file(file, &self.options)?,
+            Some(special) if self.read_special && special.streamable() => {
+                let stream = File::open(file).map_err(WcxError::io(file))?;
+                match reads_content {
+                    true => {
+                        let content = self
+                            .read_capped(file, stream, |reader| read_reader(file, reader, None))?;
+                        self.analyze_content(&label, &content)?
+                    }
+                    false => self.read_capped(file, stream, |reader| {
+                        analyze_reader(file, reader, &self.options, None)
+                    })?,
+                }
+            }
+            Some(special) => {
+                skip_special(file, special);
+                return Ok(Vec::new());
+            }
+        };
+
+        if let Some(resume) = &self.resume {
+            resume.record(file, &metadata, &file_result)?;
+        }
+        let source = match special {
+            Some(_) => Source::Stream,
+            None => Source::Local,
+        };
+        Ok(vec![Counted {
+            label,
+            file: file.clone(),
+            source,
+            content: None,
+            file_result,
+        }])
     }
 
-    pub fn set_table_totals(&mut self) {
-        let mut totals: Vec<Cell> = Vec::new();
+    /// Adds the rows of a file counted with [`TableManager::count_file`].
+    pub fn add_counted(&mut self, counted: Vec<Counted>) {
+        // An archive of several entries gets a total like several files do.
+        self.totals_counter.enabled |= counted.len() > 1;
+
+        for counted in counted {
+            self.add_result(
+                &counted.label,
+                &counted.file,
+                counted.source,
+                counted.content.as_deref(),
+                counted.file_result,
+            );
+        }
+    }
+
+    /// Reads a stream with `read`, stopping after `--read-limit` bytes or, without one, one byte
+    /// past `--max-bytes` so that the check still sees the stream go over. Warns when
+    /// `--read-limit` cuts the stream short, as its counts then only cover the start of it.
+    fn read_capped<T>(
+        &self,
+        file: &Path,
+        reader: impl Read,
+        read: impl FnOnce(&mut dyn Read) -> Result<T, WcxError>,
+    ) -> Result<T, WcxError> {
+        let over_max_bytes = self.thresholds.max_bytes.map(|max| max.saturating_add(1));
+        let mut capped = Capped {
+            reader,
+            left: self.read_limit.or(over_max_bytes).unwrap_or(usize::MAX),
+            cut: false,
+        };
+        let read = read(&mut capped)?;
+
+        if let Some(read_limit) = self.read_limit.filter(|_| capped.cut) {
+            eprintln!(
+                "wcx: {}: stopped reading after --read-limit {} bytes, so only those are counted",
+                paths::display(file),
+                read_limit
+            );
+        }
+        Ok(read)
+    }
+
+    /// Counts stdin, given as the `-` file, like a FIFO read with `--read-special`. Stdin can
+    /// only be read once, so a second `-` is skipped.
+    fn count_stdin(&self, file: &Path, label: String) -> anyhow::Result<Vec<Counted>> {
+        if self.stdin_read.swap(true, atomic::Ordering::Relaxed) {
+            eprintln!("wcx: skipping -: stdin was already counted");
+            return Ok(Vec::new());
+        }
+
+        let stdin = std::io::stdin().lock();
+        let file_result = match self.selector.is_some() || !stats::columns(self.mode).is_empty() {
+            true => {
+                let content =
+                    self.read_capped(file, stdin, |reader| read_reader(file, reader, None))?;
+                self.analyze_content(&label, &content)?
+            }
+            false => self.read_capped(file, stdin, |reader| {
+                analyze_reader(file, reader, &self.options, None)
+            })?,
+        };
+
+        Ok(vec![Counted {
+            label,
+            file: file.to_path_buf(),
+            source: Source::Stream,
+            content: None,
+            file_result,
+        }])
+    }
+
+    /// Counts the body of the URL `url` as [`remote::fetch_all`] reads it, the way stdin is
+    /// counted. The body is only kept in memory when a selector or a mode needs all of it, or
+    /// grouping needs it to tell the language apart; it is returned for grouping.
+    #[cfg(feature = "remote")]
+    pub fn count_url(
+        &self,
+        url: &str,
+        body: &mut dyn Read,
+    ) -> anyhow::Result<(Option<Vec<u8>>, FileResult)> {
+        let whole = self.selector.is_some() || !stats::columns(self.mode).is_empty();
+        if !whole && self.group_by.is_none() {
+            return Ok((
+                None,
+                analyze_reader(Path::new(url), body, &self.options, None)?,
+            ));
+        }
+
+        let content = read_reader(Path::new(url), body, None)?;
+        let file_result = self.analyze_content(url, &content)?;
+        Ok((self.group_by.is_some().then_some(content), file_result))
+    }
+
+    /// Adds a row for every URL counted with [`TableManager::count_url`]. URLs that failed to
+    /// download or to count get a row with their error instead of aborting the others.
+    #[cfg(feature = "remote")]
+    pub fn set_url_rows(&mut self, fetched: Vec<remote::Fetched<(Option<Vec<u8>>, FileResult)>>) {
+        for fetched in fetched {
+            match fetched.body {
+                Ok((body, file_result)) => {
+                    let file_result = FileResult {
+                        status: fetched.status,
+                        content_type: fetched.content_type,
+                        ..file_result
+                    };
+                    self.add_result(
+                        &fetched.url,
+                        Path::new(&fetched.url),
+                        Source::Remote,
+                        Some(body.as_deref().unwrap_or_default()),
+                        file_result,
+                    );
+                }
+                Err(err) if self.group_by.is_some() => {
+                    eprintln!("wcx: skipping {}: {:#}", fetched.url, err)
+                }
+                Err(err) => {
+                    let file_result = FileResult {
+                        status: fetched.status,
+                        content_type: fetched.content_type,
+                        error: Some(format!("{:#}", err)),
+                        ..Default::default()
+                    };
+                    let mut row_values: Vec<Cell> = Vec::new();
+                    self.set_row_values(&mut row_values, &fetched.url, &file_result);
+                    self.table.add_row(Row::new(row_values));
+                }
+            }
+        }
+    }
+
+    /// Counts `files` as one stream in a single row, as if they had been concatenated first.
+    /// The files are counted as they are read, in constant memory, unless a selector or a mode
+    /// needs all of the content at once. Files that cannot be read are left out of the stream
+    /// and returned with their error, unless `fail_fast`; what a file failing halfway through
+    /// read before it failed stays counted.
+    pub fn set_concatenated_row<'a>(
+        &mut self,
+        files: &'a [PathBuf],
+        fail_fast: bool,
+    ) -> anyhow::Result<Vec<(&'a PathBuf, anyhow::Error)>> {
+        let whole = self.selector.is_some() || !stats::columns(self.mode).is_empty();
+        let mut session = Session::new(CONCATENATED, &self.options);
+        let mut content: Vec<u8> = Vec::new();
+        let mut add = |file: &Path, reader: &mut dyn Read| match whole {
+            true => read_reader(file, reader, None).map(|read| content.extend(read)),
+            false => session.push_reader(file, reader, None),
+        };
 
-        let TotalsCounter {
-            lines_total,
-            bytes_total,
-            chars_total,
-            words_total,
-            ..
-        } = self.totals_counter;
+        let mut failures: Vec<(&PathBuf, anyhow::Error)> = Vec::new();
+        for file in files {
+            let added = match paths::is_stdin(file) {
+                true if self.stdin_read.swap(true, atomic::Ordering::Relaxed) => Ok(()),
+                true => self.read_capped(file, std::io::stdin().lock(), |reader| add(file, reader)),
+                false => analyze::metadata(file).and_then(|metadata| {
+                    let reader = File::open(file).map_err(WcxError::io(file));
+                    match SpecialFile::detect(&metadata) {
+                        None => add(file, &mut reader?),
+                        Some(special) if self.read_special && special.streamable() => {
+                            self.read_capped(file, reader?, |reader| add(file, reader))
+                        }
+                        Some(special) => {
+                            skip_special(file, special);
+                            Ok(())
+                        }
+                    }
+                }),
+            };
 
-        if self.lines_enabled {
-            Self::push_totals_row_value(&lines_total, &mut totals);
+            match added {
+                Ok(()) => {}
+                Err(err) if fail_fast => return Err(err.into()),
+                Err(err) => failures.push((file, err.into())),
+            }
+        }
+
+        let file_result = match whole {
+            true => self.analyze_content(CONCATENATED, &content)?,
+            false => session.finish()?,
         };
+        self.add_result(
+            CONCATENATED,
+            Path::new(CONCATENATED),
+            Source::Local,
+            whole.then_some(content.as_slice()),
+            file_result,
+        );
+
+        Ok(failures)
+    }
+
+    /// Counts content that is already in memory, applying the selector if there is one and
+    /// taking the measurements of the mode.
+    fn analyze_content(&self, name: &str, content: &[u8]) -> anyhow::Result<FileResult> {
+        let mut file_result = match &self.selector {
+            Some(selector) => {
+                let selected = selector.select(name, content)?;
+                analyze_selected(name, content, &selected, &self.options)?
+            }
+            None => analyze_content(name, content, &self.options)?,
+        };
+        file_result.stats = stats::measure(self.mode, name, content, &self.measure_options)?;
+
+        Ok(file_result)
+    }
+
+    /// Adds the counts of one file to the totals, checks them against the thresholds, and adds
+    /// them as a row labeled `label`, or to the group `file` belongs to.
+    fn add_result(
+        &mut self,
+        label: &str,
+        file: &Path,
+        source: Source,
+        content: Option<&[u8]>,
+        file_result: FileResult,
+    ) {
+        self.analyzed += 1;
+        if !(self.exclude_generated && file_result.generated.is_some()) {
+            self.totals_counter.add_to_totals(&file_result, source);
+        }
+        self.violations
+            .extend(self.thresholds.violations(Path::new(label), &file_result));
+
+        match self.group_by {
+            Some(group_by) => {
+                let group = self.groups.entry(group_by.key(file, content)).or_default();
+                group.add(&file_result);
+            }
+            None => {
+                let mut row_values: Vec<Cell> = Vec::new();
+                self.set_row_values(&mut row_values, label, &file_result);
+                self.table.add_row(Row::new(row_values));
+            }
+        }
+    }
+
+    /// Adds one row per group, in name order.
+    pub fn set_group_rows(&mut self) {
+        let groups = std::mem::take(&mut self.groups);
+
+        for (group, group_result) in &groups {
+            let mut row_values: Vec<Cell> = Vec::new();
+            self.set_row_values(&mut row_values, group, group_result);
+            self.table.add_row(Row::new(row_values));
+        }
 
-        if self.bytes_enabled {
-            Self::push_totals_row_value(&bytes_total, &mut totals);
+        self.groups = groups;
+    }
+
+    /// Leaves out the rows for which `keep` returns false, given their label and counts. The
+    /// totals are left as they are.
+    pub fn retain_rows(&mut self, keep: impl Fn(&str, &FileResult) -> bool) {
+        let kept: Vec<bool> = self
+            .rows
+            .iter()
+            .map(|(label, file_result)| keep(label, file_result))
+            .collect();
+
+        // The table has no retain: the rows kept move to the front, and the rest are cut off the
+        // end, where removing them shifts nothing.
+        let table_rows: Vec<Row> = self
+            .table
+            .row_iter_mut()
+            .zip(&kept)
+            .filter(|(_, kept)| **kept)
+            .map(|(row, _)| std::mem::take(row))
+            .collect();
+        let len = table_rows.len();
+        for (row, kept_row) in self.table.row_iter_mut().zip(table_rows) {
+            *row = kept_row;
+        }
+        while self.table.len() > len {
+            self.table.remove_row(self.table.len() - 1);
+        }
+
+        let mut kept = kept.into_iter();
+        self.rows.retain(|_| kept.next().unwrap_or(true));
+    }
+
+    /// Sums the totals again from the rows left, e.g. after [`Self::retain_rows`]. The subtotals
+    /// by kind of source are dropped, as rows do not record their source.
+    pub fn recount_totals(&mut self) {
+        let mut totals_counter = TotalsCounter::new(0);
+        totals_counter.enabled = self.totals_counter.enabled;
+        for (_, file_result) in &self.rows {
+            if !(self.exclude_generated && file_result.generated.is_some()) {
+                totals_counter.add_to_totals(file_result, Source::Local);
+            }
+        }
+        self.totals_counter = totals_counter;
+    }
+
+    /// Deletes the `--resume` file once every file has been counted. An interrupted run keeps
+    /// it, so that the next run picks up where this one stopped.
+    pub fn finish_resume(&mut self, interrupted: bool) -> anyhow::Result<()> {
+        match self.resume.take() {
+            Some(resume) if !interrupted => resume.finish(),
+            _ => Ok(()),
         }
+    }
 
-        if self.chars_enabled {
-            Self::push_totals_row_value(&chars_total, &mut totals);
+    /// Orders the rows by `sort`, keeping the order they were added in among equal values.
+    pub fn sort_rows(&mut self, sort: &SortOrder) {
+        let mut order: Vec<usize> = (0..self.rows.len()).collect();
+        order.sort_by(|a, b| sort.compare(&self.rows[*a], &self.rows[*b]));
+
+        let table_rows: Vec<Row> = order
+            .iter()
+            .filter_map(|index| self.table.get_row(*index).cloned())
+            .collect();
+        for (row, sorted) in self.table.row_iter_mut().zip(table_rows) {
+            *row = sorted;
         }
+        self.rows = order
+            .into_iter()
+            .map(|index| self.rows[index].clone())
+            .collect();
+    }
 
-        if self.words_enabled {
-            Self::push_totals_row_value(&words_total, &mut totals);
+    /// Returns the title of the label column: "File", or the name of the grouping.
+    pub fn label_title(&self) -> &'static str {
+        self.group_by.map_or("File", |group_by| group_by.title())
+    }
+
+    pub fn set_table_totals(&mut self) {
+        for (source, subtotals) in self.totals_counter.subtotals() {
+            let mut row: Vec<Cell> = self
+                .columns
+                .iter()
+                .map(|column| Cell::new(&column.total(&subtotals)).style_spec("b"))
+                .collect();
+            if !self.no_filename {
+                row.push(Cell::new(&subtotal_label(&self.total_label, source)).style_spec("b"));
+            }
+            self.table.add_row(Row::new(row));
         }
 
-        let total_out = "total";
-        totals.push(Cell::new(&total_out).style_spec("bFg"));
+        let mut totals: Vec<Cell> = Vec::new();
+        let totals_result = self.totals_counter.totals();
+
+        for column in &self.columns {
+            Self::push_totals_row_value(&column.total(&totals_result), &mut totals);
+        }
+
+        if !self.no_filename {
+            totals.push(Cell::new(&self.total_label).style_spec("bFg"));
+        }
 
         self.table.add_row(Row::new(totals));
     }
 
+    /// Adds the `--footer` rows under the table, each one cell spanning every column. `failures`
+    /// is the number of files that could not be counted; URLs that failed are found in the rows.
+    pub fn set_footer_rows(&mut self, footer: &[Footer], failures: usize, elapsed: Duration) {
+        let width = self.columns.len() + usize::from(!self.no_filename);
+        let errors = failures
+            + self
+                .rows
+                .iter()
+                .filter(|(_, file_result)| file_result.error.is_some())
+                .count();
+
+        for row in footer {
+            let text = match row {
+                Footer::Files => format!("files analyzed: {}", self.analyzed),
+                Footer::Errors => format!("errors: {}", errors),
+                Footer::Elapsed if self.deterministic => continue,
+                Footer::Elapsed => format!("elapsed: {:.1}s", elapsed.as_secs_f64()),
+            };
+            self.table
+                .add_row(Row::new(vec![Cell::new(&text).with_hspan(width)]));
+        }
+    }
+
     pub fn set_row_values(
         &mut self,
         row_values: &mut Vec<Cell>,
-        file: &PathBuf,
+        label: &str,
         file_result: &FileResult,
     ) {
-        if self.lines_enabled {
-            Self::push_row_value(&file_result.lines, row_values);
+        for column in &self.columns {
+            Self::push_row_value(&column.value(file_result), row_values);
         }
 
-        if self.bytes_enabled {
-            Self::push_row_value(&file_result.bytes, row_values);
+        if !self.no_filename {
+            row_values.push(Cell::new(label));
         }
+        self.rows.push((label.to_string(), file_result.clone()));
+    }
 
-        if self.chars_enabled {
-            Self::push_row_value(&file_result.chars, row_values);
-        }
+    pub fn push_row_value(value: &str, row_values: &mut Vec<Cell>) {
+        row_values.push(Cell::new(value));
+    }
 
-        if self.words_enabled {
-            Self::push_row_value(&file_result.words, row_values);
+    pub fn push_totals_row_value(value: &str, row_values: &mut Vec<Cell>) {
+        row_values.push(Cell::new(value).style_spec("bFg"));
+    }
+
+    /// Returns what every column of the table holds, from left to right.
+    pub fn column_keys(&self) -> Vec<SortKey> {
+        column_keys(&self.columns, self.no_filename)
+    }
+
+    /// Returns the table to print, with the cells of every column aligned as `--align` says.
+    /// Cells spanning several columns, like the footer rows, are left as they are. With
+    /// `--row-numbers` the file (or group) rows are numbered, in the order they are printed.
+    pub fn printed_table(&self) -> Table {
+        let keys = self.column_keys();
+        let mut table = self.table.clone();
+
+        for (index, row) in table.row_iter_mut().enumerate() {
+            for (cell, key) in row.iter_mut().zip(&keys) {
+                match self.layout.align(*key) {
+                    Some(align) if cell.get_hspan() == 1 => cell.align(align.alignment()),
+                    _ => {}
+                }
+            }
+
+            if self.row_numbers {
+                let number = match index < self.rows.len() {
+                    true => (index + 1).to_string(),
+                    false => String::new(),
+                };
+                row.insert_cell(0, Cell::new(&number));
+            }
         }
 
-        let filename = format!("{}", file.display());
-        row_values.push(Cell::new(&filename));
+        table
     }
+}
 
-    pub fn push_row_value(count: &usize, row_values: &mut Vec<Cell>) {
-        let out = format!("{}", *count);
-        row_values.push(Cell::new(&out));
+/// Returns `table_format` drawn with `+`, `-` and `|`. The box-drawing format is the only one
+/// using other characters; it becomes the default format, which has the same lines.
+fn ascii_format(table_format: TableFormat) -> TableFormat {
+    match table_format == *format::consts::FORMAT_BOX_CHARS {
+        true => *format::consts::FORMAT_DEFAULT,
+        false => table_format,
     }
+}
 
-    pub fn push_totals_row_value(count: &usize, row_values: &mut Vec<Cell>) {
-        let out = format!("{}", *count);
-        row_values.push(Cell::new(&out).style_spec("bFg"));
+/// Returns what the columns of a table with `columns` hold: the counts, then the labels.
+fn column_keys(columns: &[Column], no_filename: bool) -> Vec<SortKey> {
+    let mut keys: Vec<SortKey> = columns
+        .iter()
+        .map(|column| SortKey::Column(*column))
+        .collect();
+    if !no_filename {
+        keys.push(SortKey::Label);
     }
+    keys
+}
 
-    pub fn print_table(&self) {
-        self.table.printstd();
+/// Reads at most `left` bytes of a stream, and notes whether it had more.
+struct Capped<R> {
+    reader: R,
+    left: usize,
+    cut: bool,
+}
+
+impl<R: Read> Read for Capped<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        if self.left == 0 {
+            // One more byte tells a stream that was cut from one that ended right at the limit.
+            if !buffer.is_empty() && !self.cut {
+                self.cut = self.reader.read(&mut [0])? > 0;
+            }
+            return Ok(0);
+        }
+
+        let len = buffer.len().min(self.left);
+        let read = self.reader.read(&mut buffer[..len])?;
+        self.left -= read;
+        Ok(read)
     }
 }
 
-pub fn invoke(
-    lines_enabled: bool,
-    bytes_enabled: bool,
-    chars_enabled: bool,
-    words_enabled: bool,
-    format: &String,
-    files: &Vec<PathBuf>,
-) -> anyhow::Result<()> {
-    let mut table_manager = Builder::new()
+/// Warns that a special file is left out, and how to count it if it can be.
+fn skip_special(file: &Path, special: SpecialFile) {
+    let hint = match special.streamable() {
+        true => " (use --read-special to count it)",
+        false => "",
+    };
+    eprintln!(
+        "wcx: skipping {}: {}{}",
+        paths::display(file),
+        special,
+        hint
+    );
+}
+
+/// Cancels `cancel` on the first Ctrl-C and exits on the second, for when a file never finishes.
+#[cfg(any(unix, windows))]
+fn cancel_on_interrupt(cancel: &CancellationToken) {
+    let cancel = cancel.clone();
+    // Only fails if a handler is already set, which then stays in charge.
+    let _ = ctrlc::set_handler(move || {
+        if cancel.is_cancelled() {
+            std::process::exit(130);
+        }
+        cancel.cancel();
+    });
+}
+
+#[cfg(not(any(unix, windows)))]
+fn cancel_on_interrupt(_cancel: &CancellationToken) {}
+
+pub fn invoke(args: &Args) -> anyhow::Result<ExitCode> {
+    let started = Instant::now();
+
+    let mut files: Vec<PathBuf> = args.files.clone();
+    if let Some(list) = &args.files_from {
+        files.extend(read_file_list(list)?);
+    }
+    if let Some(list) = &args.files0_from {
+        files.extend(read_file0_list(list)?);
+    }
+    if args.recursive {
+        let walk_options = WalkOptions {
+            hidden: args.hidden,
+            respect_gitignore: args.respect_gitignore || !args.no_ignore,
+            max_depth: args.max_depth,
+            follow_symlinks: args.follow_symlinks && !args.no_follow,
+            skip_unreadable: false,
+        };
+        files = files
+            .iter()
+            .map(|file| walk_files(file, walk_options))
+            .collect::<anyhow::Result<Vec<_>>>()?
+            .concat();
+    }
+
+    #[cfg(feature = "remote")]
+    let urls: Vec<String> = match &args.urls_from {
+        Some(list) => read_url_list(list)?,
+        None => Vec::new(),
+    };
+    #[cfg(not(feature = "remote"))]
+    let urls: Vec<String> = match &args.urls_from {
+        Some(_) => {
+            anyhow::bail!("Cannot fetch --urls-from: wcx was built without the `remote` feature")
+        }
+        None => Vec::new(),
+    };
+
+    if args.files.is_empty()
+        && args.files_from.is_none()
+        && args.files0_from.is_none()
+        && args.urls_from.is_none()
+    {
+        anyhow::bail!("No files given; pass FILES, --files-from, --files0-from or --urls-from");
+    }
+    let lists = [&args.files_from, &args.files0_from, &args.urls_from];
+    if files.iter().any(|file| paths::is_stdin(file))
+        && lists
+            .iter()
+            .any(|list| list.as_deref().is_some_and(paths::is_stdin))
+    {
+        anyhow::bail!("Stdin cannot be both counted as `-` and read as a list of files or URLs");
+    }
+
+    let thresholds = Thresholds {
+        max_lines: args.max_lines,
+        max_bytes: args.max_bytes,
+        max_chars: args.max_chars,
+        max_words: args.max_words,
+        fail_under: args.fail_under.clone(),
+        per_file: args.per_file,
+        max_field_spread: args.max_field_spread,
+        max_suspicious_chars: args.max_suspicious_chars,
+        fail_over: args.fail_over.clone(),
+    };
+
+    // In hook mode only the limited metrics are counted (plus any explicitly requested), so a
+    // byte limit does not trip over the UTF-8 requirement of the character and word counts.
+    let (lines_enabled, bytes_enabled, chars_enabled, words_enabled) = if args.hook {
+        (
+            args.lines_enabled || thresholds.max_lines.is_some(),
+            args.bytes_enabled || thresholds.max_bytes.is_some(),
+            args.chars_enabled || thresholds.max_chars.is_some(),
+            args.words_enabled || thresholds.max_words.is_some(),
+        )
+    } else {
+        (
+            args.lines_enabled,
+            args.bytes_enabled,
+            args.chars_enabled,
+            args.words_enabled,
+        )
+    };
+
+    let archive_password = match args.archive_password.as_deref() {
+        Some("") => Some(archive::prompt_password()?),
+        password => password.map(str::to_string),
+    };
+
+    let selector = match args.mode {
+        Mode::Csv => Some(Selector::CsvColumns(args.csv_column.clone())),
+        Mode::Json => {
+            let path = args.select.as_deref().unwrap_or("$");
+            let path = serde_json_path::JsonPath::parse(path)
+                .with_context(|| format!("Invalid --select path `{}`", path))?;
+            Some(Selector::Json(path))
+        }
+        Mode::Html => {
+            let selector = match args.select_css.as_deref() {
+                Some(css) => Some(scraper::Selector::parse(css).map_err(|err| {
+                    anyhow::anyhow!("Invalid --select-css selector `{}`: {}", css, err)
+                })?),
+                None => None,
+            };
+            Some(Selector::Html(selector))
+        }
+        Mode::Text | Mode::Config | Mode::Subtitle | Mode::Idl | Mode::Patch | Mode::Mail => None,
+    };
+    if !args.csv_column.is_empty() && args.mode != Mode::Csv {
+        anyhow::bail!("--csv-column only applies with --mode csv");
+    }
+    if args.select.is_some() && args.mode != Mode::Json {
+        anyhow::bail!("--select only applies with --mode json");
+    }
+    if args.select_css.is_some() && args.mode != Mode::Html {
+        anyhow::bail!("--select-css only applies with --mode html");
+    }
+    if args.exclude_quoted && args.mode != Mode::Mail {
+        anyhow::bail!("--exclude-quoted only applies with --mode mail");
+    }
+
+    let columns = args
+        .columns
+        .iter()
+        .map(|name| Column::parse(name))
+        .collect::<anyhow::Result<Vec<Column>>>()?;
+    for column in &columns {
+        if let Column::Stat(stat) = column {
+            if !stats::columns(args.mode).contains(stat) {
+                let modes: Vec<String> = Mode::value_variants()
+                    .iter()
+                    .filter(|mode| stats::columns(**mode).contains(stat))
+                    .filter_map(|mode| Some(mode.to_possible_value()?.get_name().to_string()))
+                    .collect();
+                anyhow::bail!(
+                    "Column `{}` only applies with --mode {}",
+                    stat.key(),
+                    modes.join(" or --mode ")
+                );
+            }
+        }
+    }
+    let record_delim = match args.records {
+        Some(Records::Nul) => Some(b'\0'),
+        None => args.record_delim.map(|Delimiter(delimiter)| delimiter),
+    };
+    if columns.contains(&Column::Records) && record_delim.is_none() && args.record_size.is_none() {
+        anyhow::bail!("Column `records` needs --records, --record-delim or --record-size");
+    }
+    if columns.contains(&Column::PartialRecord) && args.record_size.is_none() {
+        anyhow::bail!("Column `partial_record` needs --record-size");
+    }
+    #[cfg(feature = "spell")]
+    let spell = args.spell.as_deref();
+    #[cfg(not(feature = "spell"))]
+    let spell: Option<&str> = None;
+    if columns.contains(&Column::Misspelled) && spell.is_none() {
+        anyhow::bail!("Column `misspelled` needs --spell");
+    }
+    // The dictionary and banned words are shared by every file of the run.
+    #[cfg(feature = "spell")]
+    let dictionary = spell.map(Dictionary::load).transpose()?.map(Arc::new);
+    if columns.contains(&Column::Banned) && args.banned_words_file.is_none() {
+        anyhow::bail!("Column `banned` needs --banned-words-file");
+    }
+    let banned_words = args
+        .banned_words_file
+        .as_deref()
+        .map(BannedWords::load)
+        .transpose()?
+        .map(Arc::new);
+    for column in [Column::MinFields, Column::MaxFields, Column::AvgFields] {
+        if columns.contains(&column) && args.fields_delim.is_none() {
+            anyhow::bail!(
+                "Column `{}` needs --fields-delim",
+                column.key(args.byte_size)
+            );
+        }
+    }
+    let sort = args
+        .sort
+        .as_deref()
+        .map(|keys| SortOrder::parse(keys, args.group_by))
+        .transpose()?;
+
+    let mut builder = Builder::new();
+    builder
+        .max_line_length(args.max_line_length)
         .enable_flags(lines_enabled, bytes_enabled, chars_enabled, words_enabled)
-        .table_format(format)
-        .build(files.len());
+        .byte_size(args.byte_size)
+        .word_mode(args.word_mode)
+        .incomplete_line(args.count_incomplete_line)
+        .eol_style(args.eol_style)
+        .record_delim(record_delim)
+        .record_size(args.record_size)
+        .fields_delim(args.fields_delim.map(|Delimiter(delimiter)| delimiter))
+        .longest_word(args.longest_word)
+        .whitespace_stats(args.whitespace_stats)
+        .suspicious_chars(args.suspicious_chars)
+        .confusables(args.confusables)
+        .banned_words(banned_words)
+        .balance(args.balance)
+        .wrap_width(match (args.logical_lines, args.wrap_width) {
+            (_, Some(width)) => Some(width.get()),
+            (true, None) => Some(wrap::DEFAULT_WRAP_WIDTH),
+            (false, None) => None,
+        })
+        .print_pages(
+            (args.print_pages || args.page_lines.is_some() || args.page_width.is_some()).then(
+                || PageSize {
+                    lines: args
+                        .page_lines
+                        .map_or(wrap::DEFAULT_PAGE_LINES, NonZeroUsize::get),
+                    width: args
+                        .page_width
+                        .map_or(wrap::DEFAULT_WRAP_WIDTH, NonZeroUsize::get),
+                },
+            ),
+        )
+        .derived(&args.derived)
+        .columns(&columns)
+        .group_by(args.group_by)
+        .archive_password(archive_password)
+        .urls(!urls.is_empty())
+        .mode(args.mode)
+        .exclude_quoted(args.exclude_quoted)
+        .selector(selector)
+        .archives(args.archives.then_some(ArchiveLimits {
+            depth: args.archive_depth,
+            max_expanded_size: args.max_expanded_size,
+        }))
+        .detect_generated(args.detect_generated)
+        .exclude_generated(args.exclude_generated)
+        .detect_bom(args.detect_bom)
+        .keep_bom(args.keep_bom)
+        .sparse(args.sparse)
+        .read_special(args.read_special)
+        .read_limit(args.read_limit)
+        .deterministic(args.deterministic)
+        .pivot(args.pivot)
+        .no_filename(args.no_filename)
+        .total_label(&args.total_label)
+        .layout(Layout::parse(&args.align, &args.min_width, args.group_by)?)
+        .table_format(&args.format)
+        .ascii(args.ascii)
+        .row_numbers(args.row_numbers)
+        .thresholds(thresholds.clone());
+    #[cfg(feature = "spell")]
+    builder.spell(dictionary);
+    #[cfg(feature = "mmap")]
+    builder.mmap(args.mmap);
+    let mut table_manager = builder.build(match args.concat {
+        true => 1,
+        false => files.len() + urls.len(),
+    });
+
+    for limit in &args.fail_over {
+        if !table_manager.columns.contains(&limit.column) {
+            let key = limit.column.key(args.byte_size);
+            anyhow::bail!(
+                "--fail-over {} needs the `{}` column, which is not counted",
+                key,
+                key
+            );
+        }
+    }
+
+    if args.raw && table_manager.columns.len() != 1 {
+        anyhow::bail!(
+            "--raw prints a single number per file; choose one column, e.g. -l or --columns words"
+        );
+    }
+
+    // Only the columns shown are counted.
+    if let Some(predicate) = &args.where_ {
+        for column in predicate.columns() {
+            if !table_manager.columns.contains(&column) {
+                let key = column.key(args.byte_size);
+                anyhow::bail!(
+                    "--where compares `{}`, which is not counted; show it with --columns or its \
+                     flag (such as -w)",
+                    key
+                );
+            }
+        }
+    }
+
+    if args.deterministic {
+        table_manager.stable_root = paths::common_root(&files);
+        files.sort_by_key(|file| paths::stable(file, &table_manager.stable_root));
+    }
 
-    for file in files {
-        table_manager.set_table_row(file)?;
+    if let Some(path) = &args.resume {
+        // Counts are only reused by a run that would count the same way.
+        let settings = format!(
+            "{:?} {:?} {:?} {:?}",
+            table_manager.options, table_manager.mode, table_manager.measure_options, args.select
+        );
+        table_manager.resume = Some(Resume::open(path, &settings)?);
+    }
+
+    // Unless failing fast, files that cannot be counted are reported after the results instead
+    // of ending the run.
+    let mut failures: Vec<(&PathBuf, anyhow::Error)> = Vec::new();
+    let mut interrupted = false;
+    if args.concat {
+        failures = table_manager.set_concatenated_row(&files, args.fail_fast)?;
+    } else {
+        // Ctrl-C stops between two files, and the files counted by then are still shown; a
+        // second one ends wcx right away.
+        let cancel = CancellationToken::default();
+        cancel_on_interrupt(&cancel);
+        // Redrawing the progress line only works in a terminal; in a log it is clutter.
+        let progress = args.progress && std::io::stderr().is_terminal();
+
+        // Files are counted several at a time, then added as rows in the order they were given.
+        let jobs = args
+            .jobs
+            .or_else(|| std::thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get);
+        let counted = Analyzer::new(&files).jobs(jobs).analyze_with(
+            |file| {
+                if cancel.is_cancelled() {
+                    return Ok(Ok(Vec::new()));
+                }
+                match table_manager.count_file(file) {
+                    Ok(counted) => Ok(Ok(counted)),
+                    Err(err) if args.fail_fast => Err(err),
+                    Err(err) => Ok(Err((file, err))),
+                }
+            },
+            |update| {
+                if progress {
+                    eprint!(
+                        "\r\x1b[Kwcx: counted {} of {} files, last {}",
+                        update.done,
+                        update.total,
+                        paths::display(update.file)
+                    );
+                }
+            },
+            // The files left when Ctrl-C comes are skipped above instead, which keeps the rows
+            // of the ones already counted.
+            &CancellationToken::default(),
+        );
+        if progress {
+            eprint!("\r\x1b[K");
+        }
+        interrupted = cancel.is_cancelled();
+        for counted in counted? {
+            match counted {
+                Ok(counted) => table_manager.add_counted(counted),
+                Err(failure) => failures.push(failure),
+            }
+        }
+
+        table_manager.finish_resume(interrupted)?;
+    }
+
+    #[cfg(feature = "remote")]
+    if !interrupted {
+        let fetched = remote::fetch_all(
+            &urls,
+            &remote::RemoteOptions {
+                timeout: Some(std::time::Duration::from_secs(args.timeout)),
+                body_timeout: Some(std::time::Duration::from_secs(args.body_timeout)),
+                retries: args.retries,
+                limit_rate: args.limit_rate,
+            },
+            |url, body| table_manager.count_url(url, body),
+        );
+        table_manager.set_url_rows(fetched);
+    }
+
+    let totals = table_manager.totals_counter.totals();
+    let total_violations = thresholds.total_violations(&table_manager.total_label, &totals);
+    table_manager.violations.extend(total_violations);
+
+    if table_manager.group_by.is_some() {
+        table_manager.set_group_rows();
+    }
+
+    if args.filter_name.is_some() || args.filter_name_regex.is_some() || args.where_.is_some() {
+        table_manager.retain_rows(|label, file_result| {
+            args.filter_name
+                .as_deref()
+                .is_none_or(|text| label.contains(text))
+                && args
+                    .filter_name_regex
+                    .as_ref()
+                    .is_none_or(|regex| regex.is_match(label))
+                && args
+                    .where_
+                    .as_ref()
+                    .is_none_or(|predicate| predicate.matches(file_result))
+        });
+
+        if args.filtered_totals {
+            table_manager.recount_totals();
+        }
+    }
+
+    if let Some(sort) = sort {
+        table_manager.sort_rows(&sort);
     }
 
     if table_manager.totals_counter.enabled {
         table_manager.set_table_totals();
     }
 
-    table_manager.print_table();
-    Ok(())
+    if !args.footer.is_empty() {
+        table_manager.set_footer_rows(&args.footer, failures.len(), started.elapsed());
+    }
+
+    if args.hook {
+        for violation in &table_manager.violations {
+            println!("{}", violation);
+        }
+    } else if args.raw {
+        output::print_raw(&table_manager);
+    } else {
+        let paging = match (args.pager, args.no_pager) {
+            (true, _) => Paging::Always,
+            (_, true) => Paging::Never,
+            _ => Paging::Auto,
+        };
+        let output = match args.screen_reader {
+            true => Output::ScreenReader,
+            false => args.output,
+        };
+        output::print(&table_manager, output, paging, started.elapsed())?;
+
+        for violation in &table_manager.violations {
+            eprintln!("{}", violation);
+        }
+    }
+
+    for (file, err) in &failures {
+        eprintln!("{}", diagnostic::render_file(file, err));
+    }
+
+    if interrupted {
+        eprintln!("wcx: interrupted, so only some of the files were counted");
+        return Ok(ExitCode::from(130));
+    }
+
+    if args.record {
+        history::record(&table_manager, files.len() + urls.len())?;
+    }
+
+    if table_manager.violations.is_empty() && failures.is_empty() {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+#[test]
+fn test_derived_columns_divide_the_counts() {
+    let file_result = FileResult {
+        lines: 4,
+        words: 10,
+        word_chars: 45,
+        ..Default::default()
+    };
+
+    assert_eq!(Column::AvgWordsPerLine.value(&file_result), "2.50");
+    assert_eq!(Column::AvgWordLen.value(&file_result), "4.50");
+    assert_eq!(Column::AvgWordLen.value(&Default::default()), "-");
+}
+
+#[test]
+fn test_thresholds_report_each_exceeded_limit() {
+    let thresholds = Thresholds {
+        max_lines: Some(5),
+        max_words: Some(100),
+        max_bytes: Some(10),
+        ..Default::default()
+    };
+    let file_result = FileResult {
+        lines: 6,
+        bytes: 10,
+        words: 101,
+        ..Default::default()
+    };
+
+    let violations = thresholds.violations(Path::new("notes.md"), &file_result);
+
+    assert_eq!(
+        violations,
+        vec![
+            "notes.md: 6 lines exceeds --max-lines 5",
+            "notes.md: 101 words exceeds --max-words 100",
+        ]
+    );
+}
+
+#[test]
+fn test_fail_under_checks_files_or_totals() {
+    let mut thresholds = Thresholds {
+        fail_under: vec!["words=300".parse().unwrap()],
+        ..Default::default()
+    };
+    let file_result = FileResult {
+        words: 120,
+        ..Default::default()
+    };
+
+    assert!(thresholds
+        .violations(Path::new("intro.md"), &file_result)
+        .is_empty());
+    assert_eq!(
+        thresholds.total_violations("total", &file_result),
+        vec!["total: 120 words is under --fail-under words=300"]
+    );
+
+    thresholds.per_file = true;
+    assert_eq!(
+        thresholds.violations(Path::new("intro.md"), &file_result),
+        vec!["intro.md: 120 words is under --fail-under words=300"]
+    );
+    assert!(thresholds
+        .total_violations("total", &file_result)
+        .is_empty());
+    assert!("pages=3".parse::<MinCount>().is_err());
+}
+
+#[test]
+fn test_failed_sources_have_blank_counts() {
+    let file_result = FileResult {
+        status: Some(404),
+        error: Some(String::from("HTTP 404 Not Found")),
+        ..Default::default()
+    };
+
+    assert_eq!(Column::Words.value(&file_result), "");
+    assert_eq!(Column::Words.json(&file_result), serde_json::Value::Null);
+    assert_eq!(Column::Status.value(&file_result), "404");
+    assert_eq!(Column::Error.value(&file_result), "HTTP 404 Not Found");
+}
+
+#[test]
+fn test_concatenated_row_counts_files_as_one_stream() {
+    let files = [
+        PathBuf::from("assets/test_1.txt"),
+        PathBuf::from("assets/test_2.txt"),
+    ];
+    let mut table_manager = Builder::new()
+        .enable_flags(true, true, false, true)
+        .build(1);
+
+    table_manager
+        .set_concatenated_row(&files, false)
+        .expect("Failed to count");
+
+    let (label, file_result) = &table_manager.rows[0];
+    let content = [
+        std::fs::read(&files[0]).unwrap(),
+        std::fs::read(&files[1]).unwrap(),
+    ]
+    .concat();
+    assert_eq!(label, CONCATENATED);
+    assert_eq!(table_manager.rows.len(), 1);
+    assert_eq!(file_result.bytes, content.len());
+    assert_eq!(
+        file_result.words,
+        wcx_core::count_words(std::str::from_utf8(&content).unwrap())
+    );
+}
+
+#[test]
+fn test_concatenated_files_join_characters_split_between_them() {
+    // "café au lait" cut in the middle of the two bytes of `é`.
+    let dir = std::env::temp_dir().join(format!("wcx-concat-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("Failed to create dir");
+    let files = [dir.join("head"), dir.join("tail")];
+    std::fs::write(&files[0], b"caf\xc3").expect("Failed to write");
+    std::fs::write(&files[1], b"\xa9 au lait").expect("Failed to write");
+
+    let mut table_manager = Builder::new()
+        .enable_flags(false, false, true, true)
+        .build(1);
+    table_manager
+        .set_concatenated_row(&files, false)
+        .expect("Failed to count");
+    std::fs::remove_dir_all(&dir).expect("Failed to clean up");
+
+    let (_, file_result) = &table_manager.rows[0];
+    assert_eq!((file_result.chars, file_result.words), (12, 3));
+}
+
+#[test]
+fn test_concatenated_files_skip_unreadable_ones() {
+    let files = [
+        PathBuf::from("assets/test_1.txt"),
+        PathBuf::from("assets/missing.txt"),
+        PathBuf::from("assets/test_1.txt"),
+    ];
+
+    let mut table_manager = Builder::new()
+        .enable_flags(false, false, false, true)
+        .build(1);
+    let failures = table_manager
+        .set_concatenated_row(&files, false)
+        .expect("Failed to count");
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0, &files[1]);
+    assert_eq!(table_manager.rows[0].1.words, 140);
+
+    assert!(table_manager.set_concatenated_row(&files, true).is_err());
+}
+
+#[test]
+fn test_columns_and_sort_keys_resolve_aliases_and_prefixes() {
+    assert_eq!(Column::parse("loc").unwrap(), Column::Lines);
+    assert_eq!(Column::parse("avg-word-len").unwrap(), Column::AvgWordLen);
+    assert_eq!(Column::parse("wo").unwrap(), Column::Words);
+    assert!(Column::parse("lines_").is_err());
+
+    assert_eq!(
+        SortKey::parse("lang", Some(GroupBy::Language)).unwrap(),
+        SortKey::Label
+    );
+    assert_eq!(
+        SortKey::parse("size", None).unwrap(),
+        SortKey::Column(Column::Bytes)
+    );
+}
+
+#[test]
+fn test_sort_rows_orders_table_and_rows_alike() {
+    let mut table_manager = Builder::new()
+        .enable_flags(false, false, false, true)
+        .build(3);
+    for (label, words) in [("b.md", 30), ("a.md", 10), ("c.md", 20)] {
+        let file_result = FileResult {
+            words,
+            ..Default::default()
+        };
+        table_manager.add_result(label, Path::new(label), Source::Local, None, file_result);
+    }
+
+    table_manager.sort_rows(&SortOrder::parse("words", None).unwrap());
+
+    let labels: Vec<&str> = table_manager
+        .rows
+        .iter()
+        .map(|(label, _)| label.as_str())
+        .collect();
+    assert_eq!(labels, vec!["a.md", "c.md", "b.md"]);
+    assert_eq!(
+        table_manager
+            .table
+            .get_row(0)
+            .unwrap()
+            .get_cell(1)
+            .unwrap()
+            .get_content(),
+        "a.md"
+    );
+}
+
+#[test]
+fn test_footer_rows_follow_the_totals() {
+    let mut table_manager = Builder::new()
+        .enable_flags(false, false, false, true)
+        .deterministic(true)
+        .total_label("sum")
+        .build(2);
+    for file in ["assets/test_1.txt", "assets/test_2.txt"] {
+        table_manager
+            .set_table_row(&PathBuf::from(file))
+            .expect("Failed to count");
+    }
+    table_manager.set_table_totals();
+    table_manager.set_footer_rows(
+        &[Footer::Files, Footer::Errors, Footer::Elapsed],
+        1,
+        Duration::from_millis(1200),
+    );
+
+    let last_cells: Vec<String> = table_manager
+        .table
+        .row_iter()
+        .skip(2)
+        .map(|row| row.iter().last().unwrap().get_content())
+        .collect();
+    assert_eq!(last_cells, vec!["sum", "files analyzed: 2", "errors: 1"]);
+}
+
+#[test]
+fn test_ascii_replaces_box_drawing() {
+    let mut table_manager = Builder::new()
+        .table_format("box_chars")
+        .ascii(true)
+        .build(1);
+    assert_eq!(
+        *table_manager.table.get_format(),
+        *format::consts::FORMAT_DEFAULT
+    );
+
+    let mut table_manager = Builder::new()
+        .table_format("no_colsep")
+        .ascii(true)
+        .build(1);
+    assert_eq!(
+        *table_manager.table.get_format(),
+        *format::consts::FORMAT_NO_COLSEP
+    );
+}
+
+#[test]
+fn test_retain_rows_keeps_the_totals() {
+    let mut table_manager = Builder::new()
+        .enable_flags(false, false, false, true)
+        .build(2);
+    for file in ["assets/test_1.txt", "assets/test_2.txt"] {
+        table_manager
+            .set_table_row(&PathBuf::from(file))
+            .expect("Failed to count");
+    }
+
+    table_manager.retain_rows(|label, _| label.ends_with("2.txt"));
+
+    assert_eq!(table_manager.rows.len(), 1);
+    assert_eq!(table_manager.table.len(), 1);
+    assert_eq!(table_manager.rows[0].0, "assets/test_2.txt");
+    assert_eq!(table_manager.totals_counter.totals().words, 71);
+}
+
+#[test]
+fn test_retain_rows_keeps_the_table_in_step() {
+    let mut table_manager = Builder::new()
+        .enable_flags(false, false, false, true)
+        .build(3);
+    for file in [
+        "assets/test_1.txt",
+        "assets/test_2.txt",
+        "assets/test_3.txt",
+    ] {
+        table_manager
+            .set_table_row(&PathBuf::from(file))
+            .expect("Failed to count");
+    }
+
+    table_manager.retain_rows(|label, _| !label.ends_with("2.txt"));
+
+    let files: Vec<String> = table_manager
+        .table
+        .row_iter()
+        .map(|row| row.get_cell(1).unwrap().get_content())
+        .collect();
+    assert_eq!(files, vec!["assets/test_1.txt", "assets/test_3.txt"]);
+    assert_eq!(table_manager.rows[1].0, "assets/test_3.txt");
+}
+
+#[test]
+fn test_sort_order_breaks_ties_with_later_keys() {
+    let rows = [("b.rs", 10), ("a.md", 10), ("c.md", 30), ("Makefile", 5)];
+    let mut rows: Vec<(String, FileResult)> = rows
+        .iter()
+        .map(|(label, lines)| {
+            let file_result = FileResult {
+                lines: *lines,
+                ..Default::default()
+            };
+            (label.to_string(), file_result)
+        })
+        .collect();
+
+    let sort = SortOrder::parse("ext,-lines", None).unwrap();
+    rows.sort_by(|a, b| sort.compare(a, b));
+
+    let labels: Vec<&str> = rows.iter().map(|(label, _)| label.as_str()).collect();
+    assert_eq!(labels, vec!["Makefile", "c.md", "a.md", "b.rs"]);
+    assert!(SortOrder::parse("ext,-chapters", None).is_err());
+}
+
+#[test]
+fn test_sort_order_compares_file_names_naturally() {
+    let mut rows: Vec<(String, FileResult)> = ["log.10", "log.2", "log.1"]
+        .iter()
+        .map(|label| (label.to_string(), FileResult::default()))
+        .collect();
+
+    let sort = SortOrder::parse("-file:natural", None).unwrap();
+    rows.sort_by(|a, b| sort.compare(a, b));
+
+    let labels: Vec<&str> = rows.iter().map(|(label, _)| label.as_str()).collect();
+    assert_eq!(labels, vec!["log.10", "log.2", "log.1"]);
+    assert!(SortOrder::parse("lines:natural", None).is_err());
+}
+
+#[test]
+fn test_max_field_spread_flags_ragged_files() {
+    let thresholds = Thresholds {
+        max_field_spread: Some(1),
+        ..Default::default()
+    };
+    let file_result = |min_fields, max_fields| FileResult {
+        min_fields: Some(min_fields),
+        max_fields,
+        ..Default::default()
+    };
+
+    assert!(thresholds
+        .violations(Path::new("a.tsv"), &file_result(3, 4))
+        .is_empty());
+    assert_eq!(
+        thresholds.violations(Path::new("b.tsv"), &file_result(2, 5)),
+        vec!["b.tsv: lines have 2 to 5 fields, more apart than --max-field-spread 1"]
+    );
+}
+
+#[test]
+fn test_fail_over_limits_any_column() {
+    let thresholds = Thresholds {
+        fail_over: vec![
+            "banned=0".parse().expect("Failed to parse"),
+            "avg-word-len=8".parse().expect("Failed to parse"),
+        ],
+        ..Default::default()
+    };
+    let file_result = FileResult {
+        words: 10,
+        word_chars: 90,
+        banned: 2,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        thresholds.violations(Path::new("guide.md"), &file_result),
+        vec![
+            "guide.md: 2 banned exceeds --fail-over banned=0",
+            "guide.md: 9 avg_word_len exceeds --fail-over avg_word_len=8"
+        ]
+    );
+    assert!("chapters=3".parse::<MaxCount>().is_err());
+}
+
+#[test]
+fn test_max_line_length_totals_the_longest_line() {
+    let mut table_manager = Builder::new()
+        .max_line_length(true)
+        .enable_flags(false, false, false, false)
+        .build(2);
+    assert_eq!(table_manager.columns, vec![Column::MaxLineLength]);
+
+    for file in ["assets/test_1.txt", "assets/test_2.txt"] {
+        table_manager
+            .set_table_row(&PathBuf::from(file))
+            .expect("Failed to count");
+    }
+    assert_eq!(table_manager.totals_counter.totals().max_line_length, 448);
+}
+
+#[cfg(feature = "remote")]
+#[test]
+fn test_url_bodies_are_only_kept_for_grouping() {
+    let table_manager = Builder::new()
+        .enable_flags(false, false, false, true)
+        .build(1);
+    let (body, file_result) = table_manager
+        .count_url("https://example.com/a.txt", &mut &b"one two\nthree"[..])
+        .expect("Failed to count");
+    assert_eq!(body, None);
+    assert_eq!(file_result.words, 3);
+
+    let table_manager = Builder::new()
+        .enable_flags(false, false, false, true)
+        .group_by(Some(GroupBy::Language))
+        .build(1);
+    let (body, _) = table_manager
+        .count_url("https://example.com/run", &mut &b"#!/bin/sh\necho\n"[..])
+        .expect("Failed to count");
+    assert_eq!(body.as_deref(), Some(&b"#!/bin/sh\necho\n"[..]));
+}
+
+#[test]
+fn test_archive_entries_are_only_kept_for_grouping() {
+    let path = std::env::temp_dir().join(format!("wcx-entries-{}.zip", std::process::id()));
+    std::fs::write(&path, archive::zip_of(&[("a.txt", b"one two\n")])).unwrap();
+    let limits = Some(ArchiveLimits {
+        depth: 1,
+        max_expanded_size: 1024,
+    });
+
+    let table_manager = Builder::new()
+        .enable_flags(false, false, false, true)
+        .archives(limits)
+        .build(1);
+    let counted = table_manager.count_file(&path).expect("Failed to count");
+    assert_eq!(counted[0].file_result.words, 2);
+    assert!(counted[0].content.is_none());
+
+    let table_manager = Builder::new()
+        .archives(limits)
+        .group_by(Some(GroupBy::Language))
+        .build(1);
+    let counted = table_manager.count_file(&path).expect("Failed to count");
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(counted[0].content.as_deref(), Some(&b"one two\n"[..]));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_read_limit_caps_streams_apart_from_max_bytes() {
+    let thresholds = Thresholds {
+        max_bytes: Some(2048),
+        ..Default::default()
+    };
+    let table_manager = Builder::new()
+        .enable_flags(false, true, false, false)
+        .read_special(true)
+        .read_limit(Some(1024))
+        .thresholds(thresholds.clone())
+        .build(1);
+
+    let counted = table_manager
+        .count_file(&PathBuf::from("/dev/zero"))
+        .expect("Failed to count");
+
+    assert_eq!(counted[0].file_result.bytes, 1024);
+    assert!(thresholds
+        .violations(Path::new("/dev/zero"), &counted[0].file_result)
+        .is_empty());
+}
+
+#[test]
+fn test_archive_depth_zero_counts_archives_as_files() {
+    let zip = archive::zip_of(&[("a.txt", b"one two\n")]);
+    let path = std::env::temp_dir().join(format!("wcx-depth-{}.zip", std::process::id()));
+    std::fs::write(&path, &zip).unwrap();
+
+    let table_manager = Builder::new()
+        .enable_flags(false, true, false, false)
+        .archives(Some(ArchiveLimits {
+            depth: 0,
+            max_expanded_size: 1024,
+        }))
+        .build(1);
+    let counted = table_manager.count_file(&path).expect("Failed to count");
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(counted.len(), 1);
+    assert_eq!(counted[0].file_result.bytes, zip.len());
+}
+
+#[test]
+fn test_marker_columns_are_blank_in_totals() {
+    let totals = FileResult {
+        words: 2,
+        partial_record: 3,
+        sparse: true,
+        ..Default::default()
+    };
+
+    assert_eq!(Column::Words.total(&totals), "2");
+    assert_eq!(Column::PartialRecord.total(&totals), "");
+    assert_eq!(Column::Sparse.total(&totals), "");
+}
+
+#[test]
+fn test_interrupted_runs_keep_their_resume_file() {
+    let path = std::env::temp_dir().join(format!("wcx-interrupted-{}.jsonl", std::process::id()));
+    let mut table_manager = Builder::new().build(1);
+
+    table_manager.resume = Some(Resume::open(&path, "words").expect("Failed to open"));
+    table_manager.finish_resume(true).expect("Failed to finish");
+    assert!(path.exists());
+
+    table_manager.resume = Some(Resume::open(&path, "words").expect("Failed to reopen"));
+    table_manager
+        .finish_resume(false)
+        .expect("Failed to finish");
+    assert!(!path.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_max_bytes_stops_endless_streams() {
+    let thresholds = Thresholds {
+        max_bytes: Some(1024),
+        ..Default::default()
+    };
+    let table_manager = Builder::new()
+        .enable_flags(false, true, false, false)
+        .read_special(true)
+        .thresholds(thresholds.clone())
+        .build(1);
+
+    let counted = table_manager
+        .count_file(&PathBuf::from("/dev/zero"))
+        .expect("Failed to count");
+
+    assert_eq!(counted[0].file_result.bytes, 1025);
+    assert_eq!(
+        thresholds
+            .violations(Path::new("/dev/zero"), &counted[0].file_result)
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_capped_streams_tell_cut_from_ended() {
+    let read = |left| {
+        let mut capped = Capped {
+            reader: &b"12345"[..],
+            left,
+            cut: false,
+        };
+        let mut content = Vec::new();
+        capped.read_to_end(&mut content).unwrap();
+        (content.len(), capped.cut)
+    };
+
+    assert_eq!(read(3), (3, true));
+    assert_eq!(read(5), (5, false));
+    assert_eq!(read(9), (5, false));
 }
@@ -0,0 +1,166 @@
+use crate::stats::{Stat, Stats};
+use anyhow::Context;
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Yaml,
+    Toml,
+    Ini,
+}
+
+fn format(name: &str) -> Option<Format> {
+    let name = name.to_lowercase();
+    let extension = name.rsplit_once('.')?.1;
+
+    match extension {
+        "yaml" | "yml" => Some(Format::Yaml),
+        "toml" => Some(Format::Toml),
+        "ini" | "cfg" | "conf" | "properties" => Some(Format::Ini),
+        _ => None,
+    }
+}
+
+/// Counts the keys, the nesting depth (levels of nested tables and lists, or sections in INI
+/// files) and the comment lines of a YAML, TOML or INI file.
+pub fn measure(name: &str, content: &[u8]) -> anyhow::Result<Stats> {
+    let format = format(name).with_context(|| {
+        format!(
+            "{} is not a config file (.yaml, .yml, .toml, .ini, .cfg, .conf, .properties)",
+            name
+        )
+    })?;
+    let text = std::str::from_utf8(content)
+        .with_context(|| format!("Failed to read {}. Note: config files must be UTF-8.", name))?;
+
+    let comment_lines = text
+        .lines()
+        .map(str::trim_start)
+        .filter(|line| line.starts_with('#') || (format == Format::Ini && line.starts_with(';')))
+        .count();
+
+    let (keys, depth) = match format {
+        Format::Yaml => {
+            let mut shape = Shape::default();
+            for document in serde_yaml_ng::Deserializer::from_str(text) {
+                let value = serde_yaml_ng::Value::deserialize(document)
+                    .with_context(|| format!("Failed to parse {} as YAML", name))?;
+                shape.merge(yaml_shape(&value));
+            }
+            (shape.keys, shape.depth)
+        }
+        Format::Toml => {
+            let table: toml::Table = text
+                .parse()
+                .with_context(|| format!("Failed to parse {} as TOML", name))?;
+            let shape = toml_shape(&toml::Value::Table(table));
+            (shape.keys, shape.depth)
+        }
+        Format::Ini => ini_shape(text),
+    };
+
+    Ok(Stats::from([
+        (Stat::Keys, keys),
+        (Stat::Depth, depth),
+        (Stat::CommentLines, comment_lines),
+    ]))
+}
+
+/// The number of keys below a value, and how many levels of tables and lists it nests.
+#[derive(Default)]
+struct Shape {
+    keys: usize,
+    depth: usize,
+}
+
+impl Shape {
+    fn merge(&mut self, other: Shape) {
+        self.keys += other.keys;
+        self.depth = self.depth.max(other.depth);
+    }
+
+    /// The shape of a table or list holding `children`, with `keys` keys of its own.
+    fn container(keys: usize, children: impl Iterator<Item = Shape>) -> Shape {
+        let mut shape = Shape::default();
+        for child in children {
+            shape.merge(child);
+        }
+        Shape {
+            keys: keys + shape.keys,
+            depth: shape.depth + 1,
+        }
+    }
+}
+
+fn yaml_shape(value: &serde_yaml_ng::Value) -> Shape {
+    use serde_yaml_ng::Value;
+
+    match value {
+        Value::Mapping(mapping) => {
+            Shape::container(mapping.len(), mapping.values().map(yaml_shape))
+        }
+        Value::Sequence(sequence) => Shape::container(0, sequence.iter().map(yaml_shape)),
+        Value::Tagged(tagged) => yaml_shape(&tagged.value),
+        _ => Shape::default(),
+    }
+}
+
+fn toml_shape(value: &toml::Value) -> Shape {
+    match value {
+        toml::Value::Table(table) => Shape::container(table.len(), table.values().map(toml_shape)),
+        toml::Value::Array(array) => Shape::container(0, array.iter().map(toml_shape)),
+        _ => Shape::default(),
+    }
+}
+
+/// INI files nest at most once: keys before the first `[section]` are at depth 1, keys inside a
+/// section at depth 2.
+fn ini_shape(text: &str) -> (usize, usize) {
+    let mut keys = 0;
+    let mut depth = 0;
+    let mut in_section = false;
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            in_section = true;
+        } else if line.contains('=') || line.contains(':') {
+            keys += 1;
+            depth = depth.max(if in_section { 2 } else { 1 });
+        }
+    }
+
+    (keys, depth)
+}
+
+#[test]
+fn test_measure_yaml() {
+    let content = b"# service\nname: api\nports:\n  - 80\n  - 443\nenv:\n  # secrets elsewhere\n  debug: true\n  db:\n    host: localhost\n";
+
+    let stats = measure("deploy.yml", content).expect("Failed to measure");
+
+    assert_eq!(stats[&Stat::Keys], 6);
+    assert_eq!(stats[&Stat::Depth], 3);
+    assert_eq!(stats[&Stat::CommentLines], 2);
+}
+
+#[test]
+fn test_measure_toml_and_ini() {
+    let toml =
+        b"title = \"x\"\n\n[server]\n# local only\nport = 8080\n[[server.routes]]\npath = \"/\"\n";
+    let stats = measure("Config.toml", toml).expect("Failed to measure");
+    assert_eq!(stats[&Stat::Keys], 5);
+    assert_eq!(stats[&Stat::Depth], 4);
+    assert_eq!(stats[&Stat::CommentLines], 1);
+
+    let ini = b"; global\nroot = /srv\n[db]\nhost = localhost\nport = 5432\n";
+    let stats = measure("app.ini", ini).expect("Failed to measure");
+    assert_eq!(stats[&Stat::Keys], 3);
+    assert_eq!(stats[&Stat::Depth], 2);
+    assert_eq!(stats[&Stat::CommentLines], 1);
+
+    assert!(measure("notes.md", b"").is_err());
+}
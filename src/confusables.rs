@@ -0,0 +1,26 @@
+use unicode_security::skeleton;
+
+/// Whether `char` is a letter or digit outside ASCII that looks like ASCII ones, such as the
+/// Cyrillic `е` passing for `e`: its skeleton in the Unicode confusables data (UTS #39) is made
+/// of ASCII letters and digits only. Accented letters like `é` are not, so prose is not flagged.
+pub fn is_confusable(char: char) -> bool {
+    if char.is_ascii() || !char.is_alphanumeric() {
+        return false;
+    }
+
+    let mut buffer = [0; 4];
+    let mut lookalike = skeleton(char.encode_utf8(&mut buffer)).peekable();
+    lookalike.peek().is_some() && lookalike.all(|char| char.is_ascii_alphanumeric())
+}
+
+/// Counts the characters of `text` confusable with ASCII, for `--confusables`.
+pub fn count(text: &str) -> usize {
+    text.chars().filter(|&char| is_confusable(char)).count()
+}
+
+#[test]
+fn test_count_finds_lookalikes_of_ascii() {
+    // A Cyrillic `а` and `е`, and a Greek `ο`.
+    assert_eq!(count("p\u{430}yp\u{430}l s\u{435}cure l\u{3bf}gin"), 4);
+    assert_eq!(count("naïve café, “quoted” — déjà vu"), 0);
+}
@@ -0,0 +1,127 @@
+use crate::paths;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use wcx_core::WcxError;
+
+/// Renders an error for the terminal: `wcx:`, the error and its causes on one line, then a hint
+/// when the cause is one there is a way around, like a misspelled path.
+pub fn render(err: &anyhow::Error) -> String {
+    render_labelled(None, err)
+}
+
+/// Renders an error that came up counting `file` like [`render`], naming the file first unless
+/// the error already does.
+pub fn render_file(file: &Path, err: &anyhow::Error) -> String {
+    match wcx_error(err) {
+        Some(_) => render_labelled(None, err),
+        None => render_labelled(Some(&paths::display(file)), err),
+    }
+}
+
+fn render_labelled(label: Option<&str>, err: &anyhow::Error) -> String {
+    let mut rendered = match label {
+        Some(label) => format!("wcx: {}: {:#}", label, err),
+        None => format!("wcx: {:#}", err),
+    };
+
+    if let Some(hint) = wcx_error(err).and_then(hint) {
+        rendered.push_str("\n  hint: ");
+        rendered.push_str(&hint);
+    }
+
+    rendered
+}
+
+fn wcx_error(err: &anyhow::Error) -> Option<&WcxError> {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<WcxError>())
+}
+
+fn hint(err: &WcxError) -> Option<String> {
+    match err {
+        WcxError::Io { path, source } => match source.kind() {
+            ErrorKind::NotFound => similar_file(path)
+                .map(|similar| format!("did you mean {}?", paths::display(&similar))),
+            ErrorKind::IsADirectory => Some(format!(
+                "directories are only walked with --recursive; count the files inside with \
+                 `wcx -r {}`",
+                paths::display(path)
+            )),
+            ErrorKind::PermissionDenied => Some(String::from(
+                "wcx needs permission to read the file; check its owner and mode with `ls -l`",
+            )),
+            _ => None,
+        },
+        WcxError::Decode { .. } => Some(String::from(
+            "characters (-m) and words (-w) are only counted in UTF-8 text; lines (-l) and \
+             bytes (-c) work on any file",
+        )),
+        WcxError::UnsupportedInput { .. } | WcxError::Cancelled { .. } => None,
+    }
+}
+
+/// Looks for a file next to a missing `path` whose name is a typo or two away from its name.
+fn similar_file(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let most_typos = (name.chars().count() / 3).max(1);
+
+    std::fs::read_dir(parent)
+        .ok()?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .map(|candidate| (edit_distance(name, &candidate), candidate))
+        .filter(|(distance, _)| *distance <= most_typos)
+        .min()
+        .map(|(_, candidate)| path.with_file_name(candidate))
+}
+
+/// The Levenshtein distance between `a` and `b`, in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[test]
+fn test_render_suggests_a_similar_file() {
+    let err =
+        anyhow::Error::from(crate::analyze::read(Path::new("assets/test_1.txtt")).unwrap_err());
+
+    let rendered = render(&err);
+
+    assert!(rendered.starts_with("wcx: Failed to read assets/test_1.txtt: "));
+    assert!(rendered.ends_with("\n  hint: did you mean assets/test_1.txt?"));
+    assert_eq!(
+        render(&anyhow::anyhow!("Invalid --select path")),
+        "wcx: Invalid --select path"
+    );
+}
+
+#[test]
+fn test_render_file_names_the_file_once() {
+    let err = anyhow::Error::from(crate::analyze::read(Path::new("src")).unwrap_err());
+    assert!(render_file(Path::new("src"), &err).starts_with("wcx: Failed to read src: "));
+    assert!(render(&err).ends_with(
+        "hint: directories are only walked with --recursive; count \
+         the files inside with `wcx -r src`"
+    ));
+
+    let err = anyhow::anyhow!("notes.md is not a config file");
+    assert_eq!(
+        render_file(Path::new("notes.md"), &err),
+        "wcx: notes.md: notes.md is not a config file"
+    );
+}
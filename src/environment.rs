@@ -0,0 +1,82 @@
+use anyhow::Context;
+use clap::{ArgAction, Command};
+
+/// Prefix of the environment variables that set flags: `--output csv` can be given as
+/// `WCX_OUTPUT=csv`, and `--max-lines` as `WCX_MAX_LINES`.
+pub const PREFIX: &str = "WCX_";
+
+/// Returns the command-line arguments the `WCX_*` variables set for the flags of `command`, as
+/// read by `var`. Switches take `true`/`false` (or `1`/`0`); other flags take their value as
+/// given.
+pub fn arguments_from(
+    command: &Command,
+    var: impl Fn(&str) -> Option<String>,
+) -> anyhow::Result<Vec<String>> {
+    let mut arguments: Vec<String> = Vec::new();
+
+    for arg in command.get_arguments() {
+        let Some(long) = arg.get_long() else {
+            continue;
+        };
+        // `--again` repeats a run as it was, so the environment cannot turn it on.
+        if matches!(arg.get_action(), ArgAction::Help | ArgAction::Version) || long == "again" {
+            continue;
+        }
+
+        let name = format!("{}{}", PREFIX, long.to_uppercase().replace('-', "_"));
+        let Some(value) = var(&name) else {
+            continue;
+        };
+
+        match arg.get_action() {
+            ArgAction::SetTrue => {
+                if switch(&value).with_context(|| format!("Invalid {}", name))? {
+                    arguments.push(format!("--{}", long));
+                }
+            }
+            _ => arguments.push(format!("--{}={}", long, value)),
+        }
+    }
+
+    Ok(arguments)
+}
+
+fn switch(value: &str) -> anyhow::Result<bool> {
+    match value.to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" | "" => Ok(false),
+        _ => anyhow::bail!("`{}` is not true or false", value),
+    }
+}
+
+#[test]
+fn test_arguments_from_variables() {
+    use clap::CommandFactory;
+
+    let command = crate::cli::Args::command();
+    let vars = |name: &str| match name {
+        "WCX_OUTPUT" => Some(String::from("csv")),
+        "WCX_MAX_LINES" => Some(String::from("800")),
+        "WCX_DETERMINISTIC" => Some(String::from("1")),
+        "WCX_PIVOT" => Some(String::from("false")),
+        "WCX_AGAIN" => Some(String::from("true")),
+        _ => None,
+    };
+
+    let mut arguments = arguments_from(&command, vars).expect("Failed to read variables");
+    arguments.sort();
+
+    assert_eq!(
+        arguments,
+        vec!["--deterministic", "--max-lines=800", "--output=csv"]
+    );
+
+    let error = arguments_from(&command, |name| {
+        (name == "WCX_PIVOT").then(|| String::from("maybe"))
+    })
+    .expect_err("Invalid switch");
+    assert_eq!(
+        format!("{:#}", error),
+        "Invalid WCX_PIVOT: `maybe` is not true or false"
+    );
+}
@@ -0,0 +1,110 @@
+use crate::cli::{EolStyle, IncompleteLine};
+
+/// Counts the line endings of content pushed piece by piece, so a `\r\n` split between two
+/// pushes is still seen as one ending. Only the endings `style` needs are counted.
+#[derive(Clone, Copy, Debug)]
+pub struct LineEndings {
+    style: EolStyle,
+    lf: usize,
+    cr: usize,
+    /// `\n` right after a `\r`.
+    crlf: usize,
+    last: Option<u8>,
+    before_last: Option<u8>,
+}
+
+impl LineEndings {
+    pub fn new(style: EolStyle) -> LineEndings {
+        LineEndings {
+            style,
+            lf: 0,
+            cr: 0,
+            crlf: 0,
+            last: None,
+            before_last: None,
+        }
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        let Some(&last) = bytes.last() else {
+            return;
+        };
+
+        if self.style != EolStyle::Cr {
+            self.lf += wcx_core::count_newlines(bytes);
+        }
+        if matches!(self.style, EolStyle::Cr | EolStyle::Any | EolStyle::Auto) {
+            self.cr += wcx_core::count_byte(bytes, b'\r');
+        }
+        if matches!(self.style, EolStyle::Crlf | EolStyle::Any) {
+            self.crlf += bytes.windows(2).filter(|pair| pair == b"\r\n").count()
+                + usize::from(self.last == Some(b'\r') && bytes[0] == b'\n');
+        }
+
+        self.before_last = match bytes.len() {
+            1 => self.last,
+            length => Some(bytes[length - 2]),
+        };
+        self.last = Some(last);
+    }
+
+    /// Returns the number of lines in everything pushed: one per line ending, plus the last
+    /// line when it has no ending and `incomplete_line` says to count it.
+    pub fn lines(&self, incomplete_line: IncompleteLine) -> usize {
+        // `auto` reads files without any `\n` as classic Mac files, ending lines with `\r`.
+        let style = match self.style {
+            EolStyle::Auto if self.lf == 0 && self.cr > 0 => EolStyle::Cr,
+            EolStyle::Auto => EolStyle::Lf,
+            style => style,
+        };
+        let (endings, ended) = match style {
+            EolStyle::Lf | EolStyle::Auto => (self.lf, self.last == Some(b'\n')),
+            EolStyle::Crlf => (
+                self.crlf,
+                self.before_last == Some(b'\r') && self.last == Some(b'\n'),
+            ),
+            EolStyle::Cr => (self.cr, self.last == Some(b'\r')),
+            EolStyle::Any => (
+                self.cr + self.lf - self.crlf,
+                matches!(self.last, Some(b'\n' | b'\r')),
+            ),
+        };
+
+        let incomplete = self.last.is_some() && !ended;
+        endings + usize::from(incomplete && incomplete_line == IncompleteLine::Yes)
+    }
+}
+
+/// Counts the lines of `content`; see [`LineEndings::lines`].
+pub fn count_lines(content: &[u8], style: EolStyle, incomplete_line: IncompleteLine) -> usize {
+    let mut line_endings = LineEndings::new(style);
+    line_endings.push(content);
+    line_endings.lines(incomplete_line)
+}
+
+#[test]
+fn test_styles_count_their_own_line_endings() {
+    let mixed = b"a\r\nb\rc\nd\r\n";
+    let lines = |content: &[u8], style| count_lines(content, style, IncompleteLine::No);
+
+    assert_eq!(lines(mixed, EolStyle::Lf), 3);
+    assert_eq!(lines(mixed, EolStyle::Crlf), 2);
+    assert_eq!(lines(mixed, EolStyle::Cr), 3);
+    assert_eq!(lines(mixed, EolStyle::Any), 4);
+    assert_eq!(lines(mixed, EolStyle::Auto), 3);
+    assert_eq!(lines(b"a\rb\rc", EolStyle::Auto), 2);
+    assert_eq!(
+        count_lines(b"a\rb\rc", EolStyle::Auto, IncompleteLine::Yes),
+        3
+    );
+    assert_eq!(
+        count_lines(b"a\r\n", EolStyle::Crlf, IncompleteLine::Yes),
+        1
+    );
+
+    // A `\r\n` split between two pushes is one ending.
+    let mut line_endings = LineEndings::new(EolStyle::Any);
+    line_endings.push(b"a\r");
+    line_endings.push(b"\nb\r");
+    assert_eq!(line_endings.lines(IncompleteLine::Yes), 2);
+}
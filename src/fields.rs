@@ -0,0 +1,86 @@
+use crate::analyze::FileResult;
+
+/// Counts the fields of every line of content pushed piece by piece, for `--fields-delim`.
+/// A line has one field more than it has delimiters; blank lines are not counted.
+#[derive(Clone, Copy, Debug)]
+pub struct FieldCounter {
+    delimiter: u8,
+    /// Delimiters in the line read so far.
+    delimiters: usize,
+    /// Whether the line read so far has any content.
+    in_line: bool,
+    lines: usize,
+    fields: usize,
+    min: Option<usize>,
+    max: usize,
+}
+
+impl FieldCounter {
+    pub fn new(delimiter: u8) -> FieldCounter {
+        FieldCounter {
+            delimiter,
+            delimiters: 0,
+            in_line: false,
+            lines: 0,
+            fields: 0,
+            min: None,
+            max: 0,
+        }
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            match byte {
+                b'\n' => self.end_line(),
+                // The `\r` of CRLF endings does not make a blank line a field.
+                b'\r' if self.delimiter != b'\r' => {}
+                byte => {
+                    self.in_line = true;
+                    self.delimiters += usize::from(byte == self.delimiter);
+                }
+            }
+        }
+    }
+
+    /// Writes the field counts of everything pushed into `file_result`.
+    pub fn finish(mut self, file_result: &mut FileResult) {
+        self.end_line();
+        file_result.field_lines = self.lines;
+        file_result.fields = self.fields;
+        file_result.min_fields = self.min;
+        file_result.max_fields = self.max;
+    }
+
+    fn end_line(&mut self) {
+        if std::mem::take(&mut self.in_line) {
+            let fields = std::mem::take(&mut self.delimiters) + 1;
+            self.lines += 1;
+            self.fields += fields;
+            self.min = Some(self.min.map_or(fields, |min| min.min(fields)));
+            self.max = self.max.max(fields);
+        }
+        self.delimiters = 0;
+    }
+}
+
+/// Writes the field counts of `content` into `file_result`; see [`FieldCounter`].
+pub fn count_fields(content: &[u8], delimiter: u8, file_result: &mut FileResult) {
+    let mut counter = FieldCounter::new(delimiter);
+    counter.push(content);
+    counter.finish(file_result);
+}
+
+#[test]
+fn test_ragged_lines_spread_the_field_counts() {
+    let mut file_result = FileResult::default();
+    count_fields(
+        b"a\tb\tc\r\n\r\nd\te\n\nf\tg\th\ti",
+        b'\t',
+        &mut file_result,
+    );
+
+    assert_eq!(file_result.field_lines, 3);
+    assert_eq!(file_result.fields, 9);
+    assert_eq!(file_result.min_fields, Some(2));
+    assert_eq!(file_result.max_fields, 4);
+}
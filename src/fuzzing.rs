@@ -0,0 +1,58 @@
+//! Entry points for the fuzz targets in `fuzz/`. Not a stable API.
+
+use std::time::Duration;
+
+use crate::analyze::{analyze_content, FileResult};
+use crate::cli::{LongestWord, Output};
+use crate::command::{Builder, Counted, Source};
+use crate::output;
+
+/// Counts `files`, each a name and its content, and renders them in every output format.
+/// `layout` picks pivoted (bit 0), row numbers (bit 1) and no file names (bit 2). Odd files are
+/// counted as archive entries, so the subtotals are rendered too.
+///
+/// Panics if rendering fails or the JSON output is not valid JSON.
+pub fn render_every_format(layout: u8, files: &[(&[u8], &[u8])]) {
+    let mut table_manager = Builder::new()
+        .max_line_length(true)
+        .enable_flags(true, true, true, true)
+        .longest_word(Some(LongestWord::Text))
+        .deterministic(true)
+        .pivot(layout & 1 != 0)
+        .row_numbers(layout & 2 != 0)
+        .no_filename(layout & 4 != 0)
+        .build(files.len());
+
+    for (index, (name, content)) in files.iter().enumerate() {
+        let label = String::from_utf8_lossy(name).into_owned();
+
+        // Content that cannot be counted gets a row with its error, like a failed URL.
+        let file_result =
+            analyze_content(&label, content, &table_manager.options).unwrap_or_else(|err| {
+                FileResult {
+                    error: Some(err.to_string()),
+                    ..Default::default()
+                }
+            });
+        let source = match index % 2 {
+            0 => Source::Local,
+            _ => Source::Archive,
+        };
+        table_manager.add_counted(vec![Counted::new(label, source, file_result)]);
+    }
+    table_manager.set_table_totals();
+
+    for format in [
+        Output::Table,
+        Output::Csv,
+        Output::Json,
+        Output::Markdown,
+        Output::ScreenReader,
+    ] {
+        let rendered =
+            output::render(&table_manager, format, Duration::ZERO).expect("Failed to render");
+        if format == Output::Json {
+            serde_json::from_str::<serde_json::Value>(&rendered).expect("Rendered invalid JSON");
+        }
+    }
+}
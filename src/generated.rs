@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Lines longer than this many bytes only come out of minifiers and code generators.
+const LONG_LINE: usize = 5000;
+
+/// How many lines from the top are searched for an `@generated` marker.
+const HEADER_LINES: usize = 5;
+
+/// Why a file looks machine-generated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Generated {
+    /// An `@generated` marker near the top, as left by protoc, lockfile writers and others.
+    Marker,
+    /// A `sourceMappingURL` comment pointing at the source the file was built from.
+    SourceMap,
+    /// A single enormous line, as produced by minifiers.
+    Minified,
+}
+
+impl fmt::Display for Generated {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Generated::Marker => "@generated",
+            Generated::SourceMap => "sourcemap",
+            Generated::Minified => "minified",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The marker of generated files, searched for in the header lines.
+const MARKER: &[u8] = b"@generated";
+
+/// The comments pointing at a source map, after any indentation.
+const SOURCE_MAP_COMMENTS: [&[u8]; 2] = [b"//# sourceMappingURL=", b"/*# sourceMappingURL="];
+
+/// Tells whether content pushed piece by piece looks machine-generated, without holding more
+/// than the start of the current line.
+#[derive(Clone, Debug, Default)]
+pub struct Detector {
+    /// The index of the current line.
+    line: usize,
+    /// The bytes of the current line so far.
+    length: usize,
+    /// The start of the current line after its indentation, as long as a source map comment.
+    start: Vec<u8>,
+    /// The last bytes of the current line, in case a marker is cut between two pushes.
+    tail: Vec<u8>,
+    marker: bool,
+    source_map: bool,
+    minified: bool,
+}
+
+impl Detector {
+    pub fn push(&mut self, bytes: &[u8]) {
+        let mut lines = bytes.split(|&byte| byte == b'\n').peekable();
+
+        while let Some(line) = lines.next() {
+            self.push_line(line);
+            if lines.peek().is_some() {
+                self.end_line();
+            }
+        }
+    }
+
+    /// Returns why everything pushed looks machine-generated, or `None` if it looks hand-written.
+    pub fn finish(mut self) -> Option<Generated> {
+        self.end_line();
+
+        if self.marker {
+            Some(Generated::Marker)
+        } else if self.source_map {
+            Some(Generated::SourceMap)
+        } else if self.minified {
+            Some(Generated::Minified)
+        } else {
+            None
+        }
+    }
+
+    fn push_line(&mut self, bytes: &[u8]) {
+        if self.line < HEADER_LINES && !self.marker {
+            let mut searched = std::mem::take(&mut self.tail);
+            searched.extend_from_slice(bytes);
+            self.marker = contains(&searched, MARKER);
+            let kept = searched.len().saturating_sub(MARKER.len() - 1);
+            self.tail = searched.split_off(kept);
+        }
+
+        self.length += bytes.len();
+        let wanted = SOURCE_MAP_COMMENTS[0].len() - self.start.len();
+        let bytes = match self.start.is_empty() {
+            true => bytes.trim_ascii_start(),
+            false => bytes,
+        };
+        self.start
+            .extend_from_slice(&bytes[..wanted.min(bytes.len())]);
+    }
+
+    fn end_line(&mut self) {
+        self.source_map |= SOURCE_MAP_COMMENTS
+            .iter()
+            .any(|comment| self.start.starts_with(comment));
+        self.minified |= self.length > LONG_LINE;
+
+        self.line += 1;
+        self.length = 0;
+        self.start.clear();
+        self.tail.clear();
+    }
+}
+
+/// Returns why `content` looks machine-generated, or `None` if it looks hand-written.
+pub fn detect(content: &[u8]) -> Option<Generated> {
+    let mut detector = Detector::default();
+    detector.push(content);
+    detector.finish()
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+#[test]
+fn test_detect_generated_files() {
+    assert_eq!(
+        detect(b"// Code generated by protoc. @generated\nfn main() {}\n"),
+        Some(Generated::Marker)
+    );
+    assert_eq!(
+        detect(b"var a=1;\n//# sourceMappingURL=app.js.map\n"),
+        Some(Generated::SourceMap)
+    );
+    assert_eq!(
+        detect(&b"x".repeat(LONG_LINE + 1)),
+        Some(Generated::Minified)
+    );
+    assert_eq!(detect(b"# Notes\n\nWritten by hand.\n"), None);
+
+    // Pushed a few bytes at a time, the marker and the comment are cut between pushes.
+    let pushed = |content: &[u8]| {
+        let mut detector = Detector::default();
+        for piece in content.chunks(3) {
+            detector.push(piece);
+        }
+        detector.finish()
+    };
+    assert_eq!(
+        pushed(b"var a=1;\n  //# sourceMappingURL=app.js.map\n"),
+        Some(Generated::SourceMap)
+    );
+    assert_eq!(
+        pushed(b"// Code generated. @generated\n"),
+        Some(Generated::Marker)
+    );
+}
@@ -0,0 +1,244 @@
+use crate::cache::Cache;
+use crate::cli::GoalAction;
+use crate::state::state_dir;
+use anyhow::Context;
+use chrono::{Local, NaiveDate};
+use prettytable::{format, Cell, Row, Table};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{read_to_string, rename, write};
+use std::path::{absolute, Path, PathBuf};
+
+const GOALS_FILE: &str = "goals.json";
+
+/// A word goal for one project, along with the word count recorded on each day `wcx goal` ran.
+#[derive(Serialize, Deserialize)]
+struct Goal {
+    target: usize,
+    started: NaiveDate,
+    start_words: usize,
+    deadline: Option<NaiveDate>,
+    daily: BTreeMap<NaiveDate, usize>,
+}
+
+/// Progress figures derived from a [`Goal`] as of a given day.
+#[derive(Debug, PartialEq)]
+struct Progress {
+    words: usize,
+    percent: f64,
+    delta_today: i64,
+    daily_average: f64,
+    remaining: usize,
+    projected: Option<NaiveDate>,
+    needed_per_day: Option<usize>,
+}
+
+impl Goal {
+    fn progress(&self, today: NaiveDate) -> Progress {
+        let words = self.daily.get(&today).copied().unwrap_or_default();
+        let previous_words = self
+            .daily
+            .range(..today)
+            .next_back()
+            .map_or(self.start_words, |(_, words)| *words);
+
+        let days_elapsed = (today - self.started).num_days().max(0) + 1;
+        let daily_average = (words as f64 - self.start_words as f64) / days_elapsed as f64;
+        let remaining = self.target.saturating_sub(words);
+
+        let projected = if remaining == 0 {
+            Some(today)
+        } else if daily_average > 0.0 {
+            let days_left = (remaining as f64 / daily_average).ceil() as u64;
+            today.checked_add_days(chrono::Days::new(days_left))
+        } else {
+            None
+        };
+
+        let needed_per_day = self.deadline.map(|deadline| {
+            let days_left = (deadline - today).num_days().max(0) as usize + 1;
+            remaining.div_ceil(days_left)
+        });
+
+        Progress {
+            words,
+            percent: words as f64 * 100.0 / self.target.max(1) as f64,
+            delta_today: words as i64 - previous_words as i64,
+            daily_average,
+            remaining,
+            projected,
+            needed_per_day,
+        }
+    }
+}
+
+pub fn invoke(action: GoalAction) -> anyhow::Result<()> {
+    let mut goals = load_goals()?;
+    let today = Local::now().date_naive();
+
+    match action {
+        GoalAction::Set {
+            target,
+            project,
+            deadline,
+        } => {
+            let key = absolute(&project)?;
+            let words = count_words(&project)?;
+
+            let mut goal = Goal {
+                target,
+                started: today,
+                start_words: words,
+                deadline,
+                daily: BTreeMap::new(),
+            };
+            goal.daily.insert(today, words);
+            goals.insert(key, goal);
+            save_goals(&goals)?;
+
+            println!(
+                "Goal set: {} words for {} (currently {}).",
+                target,
+                project.display(),
+                words
+            );
+        }
+        GoalAction::Status { project } => {
+            let key = absolute(&project)?;
+            let words = count_words(&project)?;
+
+            let Some(goal) = goals.get_mut(&key) else {
+                anyhow::bail!(
+                    "No goal set for {}. Set one with `wcx goal set <WORDS> --project {}`.",
+                    project.display(),
+                    project.display()
+                );
+            };
+            goal.daily.insert(today, words);
+            print_status(&project, goal, &goal.progress(today));
+            save_goals(&goals)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn count_words(project: &Path) -> anyhow::Result<usize> {
+    let mut cache = Cache::load();
+    let totals = cache.count_path(project)?;
+    let _ = cache.save();
+
+    Ok(totals.words)
+}
+
+fn print_status(project: &Path, goal: &Goal, progress: &Progress) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_CLEAN);
+
+    let projected = match progress.projected {
+        Some(date) => date.to_string(),
+        None => String::from("n/a (no progress yet)"),
+    };
+
+    let mut rows = vec![
+        ("Project", format!("{}", project.display())),
+        ("Goal", format!("{} words", goal.target)),
+        (
+            "Written",
+            format!("{} ({:.1}%)", progress.words, progress.percent),
+        ),
+        ("Today", format!("{:+}", progress.delta_today)),
+        ("Daily avg", format!("{:.0}", progress.daily_average)),
+        ("Remaining", format!("{}", progress.remaining)),
+        ("Projected", projected),
+    ];
+
+    if let (Some(deadline), Some(needed)) = (goal.deadline, progress.needed_per_day) {
+        rows.push(("Deadline", format!("{} ({}/day needed)", deadline, needed)));
+    }
+
+    for (label, value) in rows {
+        table.add_row(Row::new(vec![
+            Cell::new(label).style_spec("b"),
+            Cell::new(&value),
+        ]));
+    }
+
+    table.printstd();
+}
+
+fn load_goals() -> anyhow::Result<HashMap<PathBuf, Goal>> {
+    let path = state_dir()?.join(GOALS_FILE);
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let goals = serde_json::from_str(&read_to_string(&path)?)
+        .with_context(|| format!("Failed to parse goals file {}", path.display()))?;
+    Ok(goals)
+}
+
+fn save_goals(goals: &HashMap<PathBuf, Goal>) -> anyhow::Result<()> {
+    let path = state_dir()?.join(GOALS_FILE);
+    let tmp = path.with_extension("json.tmp");
+    write(&tmp, serde_json::to_vec_pretty(goals)?)?;
+    rename(&tmp, &path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+fn test_goal(daily: &[(NaiveDate, usize)]) -> Goal {
+    Goal {
+        target: 50_000,
+        started: daily[0].0,
+        start_words: daily[0].1,
+        deadline: None,
+        daily: daily.iter().copied().collect(),
+    }
+}
+
+#[test]
+fn test_goal_progress_projects_completion() {
+    let start = NaiveDate::from_ymd_opt(2026, 11, 1).unwrap();
+    let today = NaiveDate::from_ymd_opt(2026, 11, 5).unwrap();
+    let goal = test_goal(&[
+        (start, 0),
+        (NaiveDate::from_ymd_opt(2026, 11, 4).unwrap(), 8_000),
+        (today, 10_000),
+    ]);
+
+    let progress = goal.progress(today);
+
+    assert_eq!(progress.delta_today, 2_000);
+    assert_eq!(progress.daily_average, 2_000.0);
+    assert_eq!(progress.remaining, 40_000);
+    assert_eq!(progress.percent, 20.0);
+    assert_eq!(progress.projected, NaiveDate::from_ymd_opt(2026, 11, 25));
+}
+
+#[test]
+fn test_goal_progress_without_progress_has_no_projection() {
+    let today = NaiveDate::from_ymd_opt(2026, 11, 1).unwrap();
+    let mut goal = test_goal(&[(today, 0)]);
+    goal.deadline = NaiveDate::from_ymd_opt(2026, 11, 30);
+
+    let progress = goal.progress(today);
+
+    assert_eq!(progress.delta_today, 0);
+    assert_eq!(progress.remaining, 50_000);
+    assert_eq!(progress.projected, None);
+    assert_eq!(progress.needed_per_day, Some(1_667));
+}
+
+#[test]
+fn test_goal_progress_counts_words_written_since_set_on_the_same_day() {
+    let today = NaiveDate::from_ymd_opt(2026, 11, 1).unwrap();
+    let mut goal = test_goal(&[(today, 400)]);
+    goal.daily.insert(today, 1_000);
+
+    let progress = goal.progress(today);
+
+    assert_eq!(progress.delta_today, 600);
+    assert_eq!(progress.daily_average, 600.0);
+}
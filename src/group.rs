@@ -0,0 +1,263 @@
+use crate::language;
+use chrono::{DateTime, Local};
+use std::fmt;
+use std::path::{Component, Path};
+use std::str::FromStr;
+
+/// Group of files whose owner or modification time is unknown, such as archive entries and URLs.
+const UNKNOWN: &str = "-";
+
+/// How `--group-by` combines files into one row per group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupBy {
+    /// The language a file is written in, from its extension, name or shebang line.
+    Language,
+    /// The user owning the file (Unix only).
+    Owner,
+    /// The period the file was last modified in.
+    Modified(Period),
+    /// The first N directories of the file's path, e.g. `src/parser` for `src/parser/lex.rs`
+    /// with N = 2.
+    Directory(usize),
+}
+
+/// A time bucket of `--group-by mtime:PERIOD`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl Period {
+    /// Formats `time` as the name of its bucket, e.g. `2024-03-15`, `2024-W11` (ISO week),
+    /// `2024-03` or `2024`, so that the buckets sort chronologically.
+    fn bucket(&self, time: DateTime<Local>) -> String {
+        let format = match self {
+            Period::Day => "%Y-%m-%d",
+            Period::Week => "%G-W%V",
+            Period::Month => "%Y-%m",
+            Period::Year => "%Y",
+        };
+        time.format(format).to_string()
+    }
+}
+
+impl FromStr for Period {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "day" => Ok(Period::Day),
+            "week" => Ok(Period::Week),
+            "month" => Ok(Period::Month),
+            "year" => Ok(Period::Year),
+            _ => Err(format!(
+                "unknown period `{}` (expected: day, week, month, year)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Period::Day => "day",
+            Period::Week => "week",
+            Period::Month => "month",
+            Period::Year => "year",
+        };
+        f.write_str(name)
+    }
+}
+
+impl GroupBy {
+    /// Returns the title of the column holding the group names.
+    pub fn title(&self) -> &'static str {
+        match self {
+            GroupBy::Language => "Language",
+            GroupBy::Owner => "Owner",
+            GroupBy::Modified(_) => "Modified",
+            GroupBy::Directory(_) => "Directory",
+        }
+    }
+
+    /// Returns the name of the group `file` belongs to. `content` is given when the file is not
+    /// on disk, such as an archive entry.
+    pub fn key(&self, file: &Path, content: Option<&[u8]>) -> String {
+        match self {
+            GroupBy::Language => language::detect(file, content).to_string(),
+            GroupBy::Owner => match content {
+                Some(_) => String::from(UNKNOWN),
+                None => owner(file),
+            },
+            GroupBy::Modified(period) => match (content, modified(file)) {
+                (None, Some(time)) => period.bucket(time),
+                _ => String::from(UNKNOWN),
+            },
+            GroupBy::Directory(depth) => directory(file, *depth),
+        }
+    }
+}
+
+/// Returns the first `depth` directories of the path of `file`, or all of them when there are
+/// fewer. Files in the current directory are grouped as `.`.
+fn directory(file: &Path, depth: usize) -> String {
+    let mut root = String::new();
+    let mut names: Vec<String> = Vec::new();
+
+    for component in file.parent().unwrap_or(Path::new("")).components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => {
+                root.push_str(&component.as_os_str().to_string_lossy())
+            }
+            Component::CurDir => {}
+            Component::ParentDir | Component::Normal(_) if names.len() < depth => {
+                names.push(component.as_os_str().to_string_lossy().into_owned())
+            }
+            _ => break,
+        }
+    }
+
+    match (root.is_empty(), names.is_empty()) {
+        (true, true) => String::from("."),
+        _ => format!("{}{}", root, names.join("/")),
+    }
+}
+
+fn modified(file: &Path) -> Option<DateTime<Local>> {
+    let modified = std::fs::metadata(file).ok()?.modified().ok()?;
+    Some(modified.into())
+}
+
+/// Returns the name of the user owning `file`, or their numeric id when it has no name.
+#[cfg(unix)]
+fn owner(file: &Path) -> String {
+    use std::ffi::CStr;
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(metadata) = std::fs::metadata(file) else {
+        return String::from(UNKNOWN);
+    };
+    let uid = metadata.uid();
+
+    // SAFETY: `passwd` is a plain C struct, for which all zeroes is a valid value.
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buffer = vec![0 as libc::c_char; 4096];
+    let mut found: *mut libc::passwd = std::ptr::null_mut();
+
+    // SAFETY: every pointer refers to memory owned here that outlives the call, and `buffer` is
+    // passed with its real length.
+    let status = unsafe {
+        libc::getpwuid_r(
+            uid,
+            &mut passwd,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &mut found,
+        )
+    };
+
+    if status != 0 || found.is_null() {
+        return uid.to_string();
+    }
+
+    // SAFETY: on success `pw_name` points to a NUL-terminated string inside `buffer`.
+    unsafe { CStr::from_ptr(passwd.pw_name) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(not(unix))]
+fn owner(_file: &Path) -> String {
+    String::from(UNKNOWN)
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "language" => Ok(GroupBy::Language),
+            "owner" if cfg!(unix) => Ok(GroupBy::Owner),
+            "owner" => Err(String::from("grouping by owner is only supported on Unix")),
+            "mtime" => Ok(GroupBy::Modified(Period::Month)),
+            "dir" => Ok(GroupBy::Directory(1)),
+            _ => {
+                if let Some(period) = s.strip_prefix("mtime:") {
+                    Ok(GroupBy::Modified(period.parse()?))
+                } else if let Some(depth) = s.strip_prefix("dir:") {
+                    match depth.parse() {
+                        Ok(depth) if depth > 0 => Ok(GroupBy::Directory(depth)),
+                        _ => Err(format!(
+                            "invalid directory depth `{}` (expected: 1 or more)",
+                            depth
+                        )),
+                    }
+                } else {
+                    Err(format!(
+                        "unknown grouping `{}` (expected: language, owner, mtime:PERIOD, dir:N)",
+                        s
+                    ))
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for GroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GroupBy::Language => f.write_str("language"),
+            GroupBy::Owner => f.write_str("owner"),
+            GroupBy::Modified(period) => write!(f, "mtime:{}", period),
+            GroupBy::Directory(depth) => write!(f, "dir:{}", depth),
+        }
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_owner_of_own_file() {
+    use std::os::unix::fs::MetadataExt;
+
+    let file = Path::new("Cargo.toml");
+    let uid = std::fs::metadata(file).expect("Failed to stat").uid();
+
+    let owner = GroupBy::Owner.key(file, None);
+
+    assert!(!owner.is_empty());
+    assert!(uid != 0 || owner == "root");
+    assert_eq!(GroupBy::Owner.key(file, Some(b"")), UNKNOWN);
+}
+
+#[test]
+fn test_modified_buckets() {
+    use chrono::TimeZone;
+
+    let time = Local.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap();
+
+    assert_eq!(Period::Day.bucket(time), "2024-03-15");
+    assert_eq!(Period::Week.bucket(time), "2024-W11");
+    assert_eq!(Period::Month.bucket(time), "2024-03");
+    assert_eq!(Period::Year.bucket(time), "2024");
+    assert_eq!(
+        "mtime:week".parse::<GroupBy>(),
+        Ok(GroupBy::Modified(Period::Week))
+    );
+    assert!("mtime:hour".parse::<GroupBy>().is_err());
+}
+
+#[test]
+fn test_directory_takes_the_first_n_directories() {
+    let file = Path::new("./src/parser/lex/tokens.rs");
+
+    assert_eq!(directory(file, 1), "src");
+    assert_eq!(directory(file, 2), "src/parser");
+    assert_eq!(directory(file, 9), "src/parser/lex");
+    assert_eq!(directory(Path::new("README.md"), 2), ".");
+    assert_eq!(directory(Path::new("/srv/docs/a.md"), 1), "/srv");
+    assert!("dir:0".parse::<GroupBy>().is_err());
+}
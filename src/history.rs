@@ -0,0 +1,230 @@
+use crate::cli::Metric;
+use crate::command::TableManager;
+use crate::state::state_dir;
+use anyhow::Context;
+use chrono::{DateTime, Local};
+use prettytable::{format, Cell, Row, Table};
+use serde::{Deserialize, Serialize};
+use std::env::current_dir;
+use std::fs::{read_to_string, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+#[cfg(feature = "git")]
+use std::process::Command;
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The totals of one `wcx --record` run. Counts of modes that were not enabled are `None`.
+#[derive(Serialize, Deserialize)]
+struct HistoryEntry {
+    timestamp: DateTime<Local>,
+    commit: Option<String>,
+    cwd: PathBuf,
+    files: usize,
+    lines: Option<usize>,
+    bytes: Option<usize>,
+    chars: Option<usize>,
+    words: Option<usize>,
+}
+
+impl HistoryEntry {
+    fn count(&self, metric: Metric) -> Option<usize> {
+        match metric {
+            Metric::Lines => self.lines,
+            Metric::Bytes => self.bytes,
+            Metric::Chars => self.chars,
+            Metric::Words => self.words,
+        }
+    }
+}
+
+/// Appends the totals of a finished run to the history file in the state directory, along with
+/// the current directory and, when run inside a git work tree, the short HEAD commit.
+pub fn record(table_manager: &TableManager, files_len: usize) -> anyhow::Result<()> {
+    let totals = table_manager.totals_counter.totals();
+
+    let entry = HistoryEntry {
+        timestamp: Local::now(),
+        commit: head_commit(),
+        cwd: current_dir()?,
+        files: files_len,
+        lines: table_manager.options.lines.then_some(totals.lines),
+        bytes: table_manager.options.bytes.then_some(totals.bytes),
+        chars: table_manager.options.chars.then_some(totals.chars),
+        words: table_manager.options.words.then_some(totals.words),
+    };
+
+    let path = state_dir()?.join(HISTORY_FILE);
+    let mut history = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open history file {}", path.display()))?;
+    writeln!(history, "{}", serde_json::to_string(&entry)?)?;
+
+    Ok(())
+}
+
+pub fn invoke(chart: bool, metric: Metric, all: bool, limit: Option<usize>) -> anyhow::Result<()> {
+    let cwd = current_dir()?;
+    let mut entries: Vec<HistoryEntry> = load_history()?
+        .into_iter()
+        .filter(|entry| all || entry.cwd == cwd)
+        .collect();
+
+    if let Some(limit) = limit {
+        let skip = entries.len().saturating_sub(limit);
+        entries.drain(..skip);
+    }
+
+    if entries.is_empty() {
+        println!("No recorded runs yet. Record one with `wcx --record FILES...`.");
+        return Ok(());
+    }
+
+    if chart {
+        let counts: Vec<usize> = entries
+            .iter()
+            .filter_map(|entry| entry.count(metric))
+            .collect();
+        println!("{}", chart_line(&counts, metric));
+    } else {
+        print_table(&entries, all);
+    }
+
+    Ok(())
+}
+
+fn print_table(entries: &[HistoryEntry], all: bool) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+
+    let mut titles = vec![
+        "Date", "Commit", "Files", "Lines", "Bytes", "Chars", "Words",
+    ];
+    if all {
+        titles.push("Directory");
+    }
+    table.set_titles(Row::new(
+        titles
+            .iter()
+            .map(|title| Cell::new(title).style_spec("b"))
+            .collect(),
+    ));
+
+    let metrics = [Metric::Lines, Metric::Bytes, Metric::Chars, Metric::Words];
+    let mut previous: Option<&HistoryEntry> = None;
+
+    for entry in entries {
+        let mut row = vec![
+            Cell::new(&entry.timestamp.format("%Y-%m-%d %H:%M").to_string()),
+            Cell::new(entry.commit.as_deref().unwrap_or("-")),
+            Cell::new(&entry.files.to_string()),
+        ];
+
+        for metric in metrics {
+            let before = previous.and_then(|previous| previous.count(metric));
+            row.push(Cell::new(&count_with_delta(entry.count(metric), before)));
+        }
+
+        if all {
+            row.push(Cell::new(&entry.cwd.display().to_string()));
+        }
+
+        table.add_row(Row::new(row));
+        previous = Some(entry);
+    }
+
+    table.printstd();
+}
+
+/// Formats a recorded count along with its change since the previous run, e.g. `1200 (+50)`.
+fn count_with_delta(count: Option<usize>, before: Option<usize>) -> String {
+    match (count, before) {
+        (None, _) => String::from("-"),
+        (Some(count), Some(before)) if count != before => {
+            format!("{} ({:+})", count, count as i64 - before as i64)
+        }
+        (Some(count), _) => count.to_string(),
+    }
+}
+
+/// Renders the counts as a one-line sparkline, e.g. `words ▁▂▄▇█ 120 → 1930`.
+fn chart_line(counts: &[usize], metric: Metric) -> String {
+    let label = format!("{:?}", metric).to_lowercase();
+
+    let (Some(first), Some(last)) = (counts.first(), counts.last()) else {
+        return format!("{} (not recorded)", label);
+    };
+
+    let min = counts.iter().min().copied().unwrap_or_default();
+    let max = counts.iter().max().copied().unwrap_or_default();
+    let span = (max - min).max(1) as f64;
+
+    let sparkline: String = counts
+        .iter()
+        .map(|count| {
+            let level = ((count - min) as f64 / span * (SPARKS.len() - 1) as f64).round();
+            SPARKS[level as usize]
+        })
+        .collect();
+
+    format!("{} {} {} → {}", label, sparkline, first, last)
+}
+
+#[cfg(feature = "git")]
+fn head_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let commit = String::from_utf8(output.stdout).ok()?;
+    Some(commit.trim().to_string())
+}
+
+/// Builds without git (such as WASI, which cannot spawn processes) record no commit.
+#[cfg(not(feature = "git"))]
+fn head_commit() -> Option<String> {
+    None
+}
+
+fn load_history() -> anyhow::Result<Vec<HistoryEntry>> {
+    let path = state_dir()?.join(HISTORY_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    read_to_string(&path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Failed to parse history file {}", path.display()))
+        })
+        .collect()
+}
+
+#[test]
+fn test_count_with_delta() {
+    assert_eq!(count_with_delta(Some(1200), Some(1150)), "1200 (+50)");
+    assert_eq!(count_with_delta(Some(900), Some(1000)), "900 (-100)");
+    assert_eq!(count_with_delta(Some(900), Some(900)), "900");
+    assert_eq!(count_with_delta(None, Some(900)), "-");
+}
+
+#[test]
+fn test_chart_line_scales_between_min_and_max() {
+    assert_eq!(
+        chart_line(&[100, 200, 300, 800], Metric::Words),
+        "words ▁▂▃█ 100 → 800"
+    );
+    assert_eq!(chart_line(&[5, 5], Metric::Lines), "lines ▁▁ 5 → 5");
+    assert_eq!(chart_line(&[], Metric::Bytes), "bytes (not recorded)");
+}
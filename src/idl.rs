@@ -0,0 +1,189 @@
+use crate::stats::{Stat, Stats};
+use anyhow::Context;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Protobuf,
+    GraphQl,
+    Thrift,
+}
+
+fn format(name: &str) -> Option<Format> {
+    let name = name.to_lowercase();
+    let extension = name.rsplit_once('.')?.1;
+
+    match extension {
+        "proto" => Some(Format::Protobuf),
+        "graphql" | "graphqls" | "gql" => Some(Format::GraphQl),
+        "thrift" => Some(Format::Thrift),
+        _ => None,
+    }
+}
+
+impl Format {
+    /// Keywords that declare a type (or service).
+    fn type_keywords(&self) -> &'static [&'static str] {
+        match self {
+            Format::Protobuf => &["message", "enum", "service"],
+            Format::GraphQl => &["type", "input", "interface", "enum", "union", "scalar"],
+            Format::Thrift => &["struct", "union", "exception", "enum", "service", "typedef"],
+        }
+    }
+
+    /// Statements inside a type that are not fields, like protobuf options.
+    fn ignored_keywords(&self) -> &'static [&'static str] {
+        match self {
+            Format::Protobuf => &["option", "reserved", "extensions", "oneof", "extend"],
+            Format::GraphQl => &[],
+            Format::Thrift => &[],
+        }
+    }
+
+    /// Whether `line` is a comment, or a GraphQL description string, on its own.
+    fn line_comment(&self, line: &str) -> bool {
+        match self {
+            Format::Protobuf => line.starts_with("//"),
+            Format::GraphQl => line.starts_with('#') || line.starts_with('"'),
+            Format::Thrift => line.starts_with("//") || line.starts_with('#'),
+        }
+    }
+
+    /// The delimiter closing the block comment (or GraphQL block description) `line` opens.
+    fn block_end(&self, line: &str) -> Option<&'static str> {
+        match self {
+            Format::GraphQl => line.starts_with("\"\"\"").then_some("\"\"\""),
+            Format::Protobuf | Format::Thrift => line.starts_with("/*").then_some("*/"),
+        }
+    }
+}
+
+/// Counts the declared types (messages, enums, services and the like), the fields inside them
+/// (enum values and service methods included) and the comment lines of a Protocol Buffers,
+/// GraphQL or Thrift schema. GraphQL descriptions count as comment lines, not fields.
+pub fn measure(name: &str, content: &[u8]) -> anyhow::Result<Stats> {
+    let format = format(name).with_context(|| {
+        format!(
+            "{} is not a schema file (.proto, .graphql, .gql, .thrift)",
+            name
+        )
+    })?;
+    let text = std::str::from_utf8(content)
+        .with_context(|| format!("Failed to read {}. Note: schemas must be UTF-8.", name))?;
+
+    let mut types = 0;
+    let mut fields = 0;
+    let mut comment_lines = 0;
+    let mut depth: usize = 0;
+    // The delimiter closing the block comment or description the parser is inside of.
+    let mut block_end: Option<&str> = None;
+
+    for line in text.lines().map(str::trim) {
+        if let Some(end) = block_end {
+            comment_lines += 1;
+            block_end = block_end.filter(|_| !line.contains(end));
+            continue;
+        }
+        if let Some(end) = format.block_end(line) {
+            comment_lines += 1;
+            // The opening delimiter is as long as the closing one.
+            block_end = Some(end).filter(|_| !line[end.len()..].contains(end));
+            continue;
+        }
+        if format.line_comment(line) {
+            comment_lines += 1;
+            continue;
+        }
+
+        let mut words = line.split_whitespace();
+        let first = match words.next() {
+            Some("extend") => words.next(),
+            first => first,
+        };
+
+        match first {
+            None => {}
+            Some(word) if format.type_keywords().contains(&word) => types += 1,
+            Some(word) if word.starts_with('}') || word == "{" => {}
+            Some(word) if format.ignored_keywords().contains(&word) => {}
+            Some(_) if depth > 0 => fields += 1,
+            Some(_) => {}
+        }
+
+        let opened = line.matches('{').count();
+        let closed = line.matches('}').count();
+        depth = (depth + opened).saturating_sub(closed);
+    }
+
+    Ok(Stats::from([
+        (Stat::Types, types),
+        (Stat::Fields, fields),
+        (Stat::CommentLines, comment_lines),
+    ]))
+}
+
+#[test]
+fn test_measure_protobuf() {
+    let proto = r#"syntax = "proto3";
+// A user of the service.
+message User {
+  string name = 1;
+  /* Deprecated:
+     use email */
+  reserved 2;
+  oneof contact {
+    string email = 3;
+    string phone = 4;
+  }
+  enum Role {
+    ROLE_UNSPECIFIED = 0;
+    ROLE_ADMIN = 1;
+  }
+}
+service Users {
+  rpc Get(GetRequest) returns (User);
+}
+"#;
+
+    let stats = measure("user.proto", proto.as_bytes()).expect("Failed to measure");
+
+    assert_eq!(stats[&Stat::Types], 3);
+    assert_eq!(stats[&Stat::Fields], 6);
+    assert_eq!(stats[&Stat::CommentLines], 3);
+}
+
+#[test]
+fn test_measure_graphql_and_thrift() {
+    let graphql = "# Schema\ntype Query {\n  user(id: ID!): User\n}\n\ntype User {\n  id: ID!\n  name: String\n}\nunion Result = User | Error\n";
+    let stats = measure("schema.graphql", graphql.as_bytes()).expect("Failed to measure");
+    assert_eq!(stats[&Stat::Types], 3);
+    assert_eq!(stats[&Stat::Fields], 3);
+    assert_eq!(stats[&Stat::CommentLines], 1);
+
+    let thrift = "struct Point {\n  1: i32 x,\n  2: i32 y\n}\n# origin\nconst Point ORIGIN = {}\n";
+    let stats = measure("geo.thrift", thrift.as_bytes()).expect("Failed to measure");
+    assert_eq!(stats[&Stat::Types], 1);
+    assert_eq!(stats[&Stat::Fields], 2);
+    assert_eq!(stats[&Stat::CommentLines], 1);
+
+    assert!(measure("notes.md", b"").is_err());
+}
+
+#[test]
+fn test_graphql_descriptions_are_not_fields() {
+    let graphql = r#""""
+A person with an account.
+"""
+type User {
+  "How to greet them"
+  name: String
+  """The address they sign in with"""
+  email: String
+}
+"#;
+
+    let stats = measure("schema.graphql", graphql.as_bytes()).expect("Failed to measure");
+
+    assert_eq!(stats[&Stat::Types], 1);
+    assert_eq!(stats[&Stat::Fields], 2);
+    assert_eq!(stats[&Stat::CommentLines], 5);
+}
@@ -0,0 +1,161 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+/// File extensions (lowercase) and the language they are written in.
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("c", "C"),
+    ("h", "C"),
+    ("cc", "C++"),
+    ("cpp", "C++"),
+    ("cxx", "C++"),
+    ("hpp", "C++"),
+    ("cs", "C#"),
+    ("css", "CSS"),
+    ("go", "Go"),
+    ("html", "HTML"),
+    ("htm", "HTML"),
+    ("java", "Java"),
+    ("js", "JavaScript"),
+    ("mjs", "JavaScript"),
+    ("cjs", "JavaScript"),
+    ("json", "JSON"),
+    ("kt", "Kotlin"),
+    ("lua", "Lua"),
+    ("md", "Markdown"),
+    ("markdown", "Markdown"),
+    ("php", "PHP"),
+    ("pl", "Perl"),
+    ("pm", "Perl"),
+    ("py", "Python"),
+    ("rb", "Ruby"),
+    ("rs", "Rust"),
+    ("scss", "CSS"),
+    ("sh", "Shell"),
+    ("bash", "Shell"),
+    ("zsh", "Shell"),
+    ("fish", "Shell"),
+    ("sql", "SQL"),
+    ("swift", "Swift"),
+    ("toml", "TOML"),
+    ("ts", "TypeScript"),
+    ("tsx", "TypeScript"),
+    ("txt", "Text"),
+    ("xml", "XML"),
+    ("yaml", "YAML"),
+    ("yml", "YAML"),
+];
+
+/// Extensionless file names that identify their language on their own.
+const FILE_NAMES: &[(&str, &str)] = &[
+    ("Dockerfile", "Dockerfile"),
+    ("Makefile", "Makefile"),
+    ("makefile", "Makefile"),
+    ("GNUmakefile", "Makefile"),
+    ("Rakefile", "Ruby"),
+    ("Gemfile", "Ruby"),
+];
+
+/// Interpreters named in a shebang line and the language of scripts they run.
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("bash", "Shell"),
+    ("sh", "Shell"),
+    ("dash", "Shell"),
+    ("zsh", "Shell"),
+    ("fish", "Shell"),
+    ("python", "Python"),
+    ("node", "JavaScript"),
+    ("deno", "TypeScript"),
+    ("ruby", "Ruby"),
+    ("perl", "Perl"),
+    ("php", "PHP"),
+    ("lua", "Lua"),
+];
+
+/// Name used for files whose language cannot be told.
+pub const UNKNOWN: &str = "unknown";
+
+/// Returns the language of `file`, judged by its extension or well-known file name. Files without
+/// an extension are classified by the interpreter in their shebang line (`#!/usr/bin/env python3`),
+/// so scripts in `bin/` are recognised too. The shebang is read from `content` when the file is
+/// already in memory (such as an archive entry), and from disk otherwise.
+pub fn detect(file: &Path, content: Option<&[u8]>) -> &'static str {
+    if let Some(name) = file.file_name().and_then(|name| name.to_str()) {
+        if let Some((_, language)) = FILE_NAMES.iter().find(|(known, _)| *known == name) {
+            return language;
+        }
+    }
+
+    match file.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => {
+            let extension = extension.to_lowercase();
+            EXTENSIONS
+                .iter()
+                .find(|(known, _)| *known == extension)
+                .map_or(UNKNOWN, |(_, language)| language)
+        }
+        None => {
+            let line = match content {
+                Some(content) => content
+                    .split(|&byte| byte == b'\n')
+                    .next()
+                    .map(|line| String::from_utf8_lossy(line).into_owned()),
+                None => first_line(file),
+            };
+            line.and_then(|line| from_shebang(&line)).unwrap_or(UNKNOWN)
+        }
+    }
+}
+
+/// Returns the language of the interpreter a shebang line runs, looking through `env` (and its
+/// `-S` and other options) to the actual interpreter. Version suffixes such as `python3.12` are
+/// ignored.
+fn from_shebang(line: &str) -> Option<&'static str> {
+    let command = line.strip_prefix("#!")?;
+    let mut words = command.split_whitespace();
+
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+
+    let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    INTERPRETERS
+        .iter()
+        .find(|(interpreter, _)| *interpreter == name)
+        .map(|(_, language)| *language)
+}
+
+/// Reads the first line of `file`, giving up on files that start with a very long line.
+fn first_line(file: &Path) -> Option<String> {
+    let mut line = String::new();
+    BufReader::new(File::open(file).ok()?.take(256))
+        .read_line(&mut line)
+        .ok()?;
+    Some(line)
+}
+
+#[test]
+fn test_detect_by_extension_and_name() {
+    assert_eq!(detect(Path::new("src/main.rs"), None), "Rust");
+    assert_eq!(detect(Path::new("README.MD"), None), "Markdown");
+    assert_eq!(detect(Path::new("docker/Dockerfile"), None), "Dockerfile");
+    assert_eq!(detect(Path::new("archive.xyz"), None), UNKNOWN);
+    assert_eq!(detect(Path::new("bin/tool"), Some(b"#!/bin/sh\n")), "Shell");
+}
+
+#[test]
+fn test_from_shebang_looks_through_env() {
+    assert_eq!(from_shebang("#!/usr/bin/env python3\n"), Some("Python"));
+    assert_eq!(
+        from_shebang("#!/usr/bin/env -S python3.12 -u"),
+        Some("Python")
+    );
+    assert_eq!(from_shebang("#!/bin/bash -e"), Some("Shell"));
+    assert_eq!(
+        from_shebang("#!/usr/bin/env NODE_ENV=production node"),
+        Some("JavaScript")
+    );
+    assert_eq!(from_shebang("#!/opt/custom/interpreter"), None);
+    assert_eq!(from_shebang("print('no shebang')"), None);
+}
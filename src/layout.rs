@@ -0,0 +1,129 @@
+use crate::command::SortKey;
+use crate::group::GroupBy;
+use anyhow::Context;
+use prettytable::format::Alignment;
+
+/// How `--align` lines up the values of a table column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+impl Align {
+    fn parse(name: &str) -> anyhow::Result<Align> {
+        match name {
+            "left" => Ok(Align::Left),
+            "right" => Ok(Align::Right),
+            _ => anyhow::bail!("Unknown alignment `{}` (expected left or right)", name),
+        }
+    }
+
+    pub fn alignment(&self) -> Alignment {
+        match self {
+            Align::Left => Alignment::LEFT,
+            Align::Right => Alignment::RIGHT,
+        }
+    }
+}
+
+/// The `--align` and `--min-width` settings of the table columns. A setting naming a column
+/// (`words=right`) wins over one for every column (`right`); among those, the last one wins.
+#[derive(Clone, Debug, Default)]
+pub struct Layout {
+    aligns: Vec<(Option<SortKey>, Align)>,
+    min_widths: Vec<(Option<SortKey>, usize)>,
+}
+
+impl Layout {
+    /// Parses settings like `right` or `words=right,file=left` (columns named as in `--sort`).
+    pub fn parse(
+        aligns: &[String],
+        min_widths: &[String],
+        group_by: Option<GroupBy>,
+    ) -> anyhow::Result<Layout> {
+        let aligns = aligns
+            .iter()
+            .map(|setting| {
+                parse_setting(setting, group_by, Align::parse)
+                    .with_context(|| format!("Invalid --align `{}`", setting))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        let min_widths = min_widths
+            .iter()
+            .map(|setting| {
+                parse_setting(setting, group_by, |width| Ok(width.parse::<usize>()?))
+                    .with_context(|| format!("Invalid --min-width `{}`", setting))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Layout { aligns, min_widths })
+    }
+
+    /// Returns how the values of `column` are aligned, when a setting says so.
+    pub fn align(&self, column: SortKey) -> Option<Align> {
+        find(&self.aligns, column)
+    }
+
+    /// Returns how many characters wide `column` is at least.
+    pub fn min_width(&self, column: SortKey) -> usize {
+        find(&self.min_widths, column).unwrap_or(0)
+    }
+
+    /// Pads `title` to the minimum width of `column`, on the side its values are aligned away
+    /// from, so the whole column is at least that wide.
+    pub fn pad_title(&self, column: SortKey, title: &str) -> String {
+        let width = self.min_width(column);
+        match self.align(column) {
+            Some(Align::Right) => format!("{:>width$}", title),
+            Some(Align::Left) | None => format!("{:<width$}", title),
+        }
+    }
+}
+
+fn parse_setting<T>(
+    setting: &str,
+    group_by: Option<GroupBy>,
+    parse_value: impl Fn(&str) -> anyhow::Result<T>,
+) -> anyhow::Result<(Option<SortKey>, T)> {
+    match setting.split_once('=') {
+        Some((column, value)) => match SortKey::parse(column, group_by)? {
+            SortKey::Extension => anyhow::bail!("`{}` is not a column", column),
+            key => Ok((Some(key), parse_value(value)?)),
+        },
+        None => Ok((None, parse_value(setting)?)),
+    }
+}
+
+fn find<T: Copy>(settings: &[(Option<SortKey>, T)], column: SortKey) -> Option<T> {
+    let named = settings.iter().rev().find(|(key, _)| *key == Some(column));
+    let every = settings.iter().rev().find(|(key, _)| key.is_none());
+    named.or(every).map(|(_, value)| *value)
+}
+
+#[test]
+fn test_named_columns_win_over_every_column() {
+    use crate::command::Column;
+
+    let settings = |settings: &[&str]| -> Vec<String> {
+        settings.iter().map(|setting| setting.to_string()).collect()
+    };
+    let layout = Layout::parse(
+        &settings(&["words=left", "right"]),
+        &settings(&["6", "file=20"]),
+        None,
+    )
+    .expect("Failed to parse");
+
+    assert_eq!(
+        layout.align(SortKey::Column(Column::Words)),
+        Some(Align::Left)
+    );
+    assert_eq!(layout.align(SortKey::Label), Some(Align::Right));
+    assert_eq!(layout.min_width(SortKey::Label), 20);
+    assert_eq!(
+        layout.pad_title(SortKey::Column(Column::Lines), "Lines"),
+        " Lines"
+    );
+    assert!(Layout::parse(&settings(&["middle"]), &[], None).is_err());
+}
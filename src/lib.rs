@@ -0,0 +1,152 @@
+//! The counting, rendering and subcommands behind the `wcx` binary, which only calls [`main`].
+//! Nothing else is public except [`fuzzing`], the entry points of the fuzz targets in `fuzz/`,
+//! which is not meant as a stable API.
+
+use std::process::ExitCode;
+
+// memmap2 builds for WASI but cannot map anything there, so every file of 64 MiB or more would
+// fail to count.
+#[cfg(all(target_os = "wasi", feature = "mmap"))]
+compile_error!("the `mmap` feature does not work on WASI; build with --no-default-features");
+
+mod abbrev;
+mod again;
+mod analyze;
+mod archive;
+#[cfg(feature = "git")]
+mod authors;
+mod balance;
+mod banned;
+mod bom;
+mod budget;
+mod cache;
+#[cfg(feature = "git")]
+mod changed;
+mod cli;
+mod collate;
+mod command;
+mod config;
+mod confusables;
+mod diagnostic;
+mod environment;
+mod eol;
+mod fields;
+#[doc(hidden)]
+pub mod fuzzing;
+mod generated;
+mod goal;
+mod group;
+mod history;
+mod idl;
+mod language;
+mod layout;
+mod mail;
+mod manifest;
+mod output;
+mod pager;
+mod patch;
+mod paths;
+#[cfg(feature = "picker")]
+mod picker;
+mod pkg;
+mod predicate;
+mod profile;
+mod prompt;
+#[cfg(feature = "remote")]
+mod remote;
+mod resume;
+mod select;
+#[cfg(feature = "spell")]
+mod spell;
+mod state;
+mod stats;
+mod subtitle;
+mod suspicious;
+#[cfg(feature = "remote")]
+mod throttle;
+mod vs;
+mod walk;
+mod whitespace;
+mod wrap;
+
+/// Runs `wcx` with the arguments of the process and reports any error.
+pub fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("{}", diagnostic::render(&err));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> anyhow::Result<ExitCode> {
+    let mut args = cli::Args::parse_args()?;
+    let mut last_run = again::LastRun::current();
+    if args.again {
+        let (run, again_args) = again::load()?;
+        (last_run, args) = (Some(run), again_args);
+    }
+
+    if let Some(command) = args.command.take() {
+        match command {
+            cli::Command::Prompt { path, metric } => prompt::invoke(&path, metric)?,
+            cli::Command::Goal { action } => goal::invoke(action)?,
+            cli::Command::History {
+                chart,
+                metric,
+                all,
+                limit,
+            } => history::invoke(chart, metric, all, limit)?,
+            #[cfg(feature = "git")]
+            cli::Command::Authors { paths } => authors::invoke(&paths)?,
+            #[cfg(feature = "git")]
+            cli::Command::Changed { base, paths } => changed::invoke(&base, &paths)?,
+            cli::Command::Pkg {
+                package,
+                files,
+                count_incomplete_line,
+                eol_style,
+            } => pkg::invoke(&package, files, eol_style, count_incomplete_line)?,
+            cli::Command::Vs { files } => vs::invoke(&files)?,
+            cli::Command::Manifest { action } => match action {
+                cli::ManifestAction::Create { paths } => manifest::create(&paths)?,
+                cli::ManifestAction::Verify { manifest } => return manifest::verify(&manifest),
+            },
+            cli::Command::Check {
+                budgets,
+                max_warnings,
+                ratchet,
+                update_baseline,
+            } => {
+                return budget::invoke(&budgets, max_warnings, ratchet.as_deref(), update_baseline)
+            }
+        }
+
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    #[cfg(feature = "picker")]
+    if args.files.is_empty()
+        && args.files_from.is_none()
+        && args.files0_from.is_none()
+        && args.urls_from.is_none()
+    {
+        args.files = picker::pick()?;
+        // Picked files are recorded with the run, so `--again` counts them without asking.
+        if let Some(run) = &mut last_run {
+            run.args.extend(
+                args.files
+                    .iter()
+                    .map(|file| file.to_string_lossy().into_owned()),
+            );
+        }
+    }
+
+    // Failing to record the run only breaks a later `--again`, which reports it then.
+    if let Some(run) = &last_run {
+        let _ = again::save(run);
+    }
+
+    command::invoke(&args)
+}
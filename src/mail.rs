@@ -0,0 +1,95 @@
+use crate::stats::{Stat, Stats};
+
+/// Counts the messages of an mbox archive or a single .eml message, their header lines (folded
+/// continuation lines included) and the words of their bodies. With `exclude_quoted`, quoted
+/// lines of replies are left out of the body words. Bodies are counted as they are stored, so
+/// base64 or quoted-printable parts are not decoded first.
+pub fn measure(content: &[u8], exclude_quoted: bool) -> Stats {
+    let text = String::from_utf8_lossy(content);
+    let mut lines = text
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .peekable();
+
+    let mut messages = 0;
+    let mut header_lines = 0;
+    let mut body_words = 0;
+
+    // An mbox starts every message with a `From ` separator line; an .eml file is one message.
+    let mbox = lines.peek().is_some_and(|line| line.starts_with("From "));
+    let mut in_header = false;
+    let mut previous_blank = true;
+
+    if !mbox && lines.peek().is_some() {
+        messages += 1;
+        in_header = true;
+    }
+
+    for line in lines {
+        let blank = line.trim().is_empty();
+
+        if mbox && previous_blank && line.starts_with("From ") {
+            messages += 1;
+            in_header = true;
+        } else if in_header {
+            match blank {
+                true => in_header = false,
+                false => header_lines += 1,
+            }
+        } else if !(exclude_quoted && is_quoted(line)) {
+            body_words += wcx_core::count_words(line);
+        }
+
+        previous_blank = blank;
+    }
+
+    Stats::from([
+        (Stat::Messages, messages),
+        (Stat::HeaderLines, header_lines),
+        (Stat::BodyWords, body_words),
+    ])
+}
+
+/// Whether a body line quotes an earlier message. mboxrd archives escape body lines starting
+/// with `From ` as `>From `, which are the message's own text.
+fn is_quoted(line: &str) -> bool {
+    line.starts_with('>') && !line.trim_start_matches('>').starts_with("From ")
+}
+
+#[test]
+fn test_measure_mbox() {
+    let mbox = "From alice@example.com Mon Jan  1 00:00:00 2024\r
+From: Alice <alice@example.com>\r
+Subject: Release\r
+ notes\r
+\r
+Shipping on Friday.\r
+>From now on, weekly.\r
+\r
+From bob@example.com Mon Jan  1 01:00:00 2024
+From: Bob <bob@example.com>
+
+> Shipping on Friday.
+Sounds good.
+";
+
+    let stats = measure(mbox.as_bytes(), false);
+    assert_eq!(stats[&Stat::Messages], 2);
+    assert_eq!(stats[&Stat::HeaderLines], 4);
+    assert_eq!(stats[&Stat::BodyWords], 13);
+
+    let stats = measure(mbox.as_bytes(), true);
+    assert_eq!(stats[&Stat::BodyWords], 9);
+}
+
+#[test]
+fn test_measure_eml() {
+    let eml = b"From: Carol <carol@example.com>\nTo: list@example.com\n\nHello list.\n";
+
+    let stats = measure(eml, false);
+
+    assert_eq!(stats[&Stat::Messages], 1);
+    assert_eq!(stats[&Stat::HeaderLines], 2);
+    assert_eq!(stats[&Stat::BodyWords], 2);
+    assert_eq!(measure(b"", false)[&Stat::Messages], 0);
+}
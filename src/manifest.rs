@@ -0,0 +1,248 @@
+use crate::analyze::{analyze_content, AnalyzeOptions};
+use crate::paths;
+use crate::walk::collect_files;
+use anyhow::Context;
+use prettytable::{format, Cell, Row, Table};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{read, read_to_string};
+use std::io::stdout;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Lines starting with this prefix name the paths a manifest was created from, so `verify` can walk
+/// them again to find new files.
+const ROOT_PREFIX: &str = "# root: ";
+
+/// One file of a manifest. Words and chars are empty for files that are not valid UTF-8.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct ManifestEntry {
+    path: String,
+    bytes: usize,
+    sha256: String,
+    lines: usize,
+    words: Option<usize>,
+    chars: Option<usize>,
+}
+
+impl ManifestEntry {
+    fn new(file: &Path) -> anyhow::Result<ManifestEntry> {
+        let content = read(file).with_context(|| format!("Failed to read {}", file.display()))?;
+
+        let mut options = AnalyzeOptions {
+            lines: true,
+            bytes: true,
+            chars: true,
+            words: true,
+            ..Default::default()
+        };
+        let text = analyze_content("", &content, &options).ok();
+        options.chars = false;
+        options.words = false;
+        let counts = analyze_content("", &content, &options)?;
+
+        Ok(ManifestEntry {
+            path: paths::slash(file),
+            bytes: counts.bytes,
+            sha256: format!("{:x}", Sha256::digest(&content)),
+            lines: counts.lines,
+            words: text.as_ref().map(|text| text.words),
+            chars: text.as_ref().map(|text| text.chars),
+        })
+    }
+
+    /// Describes how the counts differ from `before`, e.g. `words 120 → 130, lines 10 → 11`.
+    fn changes(&self, before: &ManifestEntry) -> String {
+        let format_count =
+            |count: Option<usize>| count.map_or(String::from("-"), |count| count.to_string());
+        let counts = [
+            ("bytes", Some(before.bytes), Some(self.bytes)),
+            ("lines", Some(before.lines), Some(self.lines)),
+            ("words", before.words, self.words),
+            ("chars", before.chars, self.chars),
+        ];
+
+        let changes: Vec<String> = counts
+            .into_iter()
+            .filter(|(_, before, after)| before != after)
+            .map(|(metric, before, after)| {
+                format!(
+                    "{} {} → {}",
+                    metric,
+                    format_count(before),
+                    format_count(after)
+                )
+            })
+            .collect();
+
+        match changes.is_empty() {
+            true => String::from("content changed"),
+            false => changes.join(", "),
+        }
+    }
+}
+
+/// Writes a manifest of every file below `roots` to stdout: its path, size, SHA-256 hash and
+/// counts, as CSV after a few `#` comment lines recording the roots.
+pub fn create(roots: &[PathBuf]) -> anyhow::Result<()> {
+    println!("# wcx manifest");
+    for root in roots {
+        println!("{}{}", ROOT_PREFIX, paths::display(root));
+    }
+
+    let mut writer = csv::Writer::from_writer(stdout());
+    for root in roots {
+        for file in collect_files(root)? {
+            writer.serialize(ManifestEntry::new(&file)?)?;
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Re-counts the files of `manifest` and reports the ones whose content changed, that are
+/// missing, or that are new below its roots. Fails when there is any difference.
+pub fn verify(manifest: &Path) -> anyhow::Result<ExitCode> {
+    let contents = read_to_string(manifest)
+        .with_context(|| format!("Failed to read manifest {}", manifest.display()))?;
+    let (roots, mut recorded) = parse_manifest(&contents)
+        .with_context(|| format!("Failed to parse manifest {}", manifest.display()))?;
+
+    // A manifest written into one of its own roots (`wcx manifest create . > MANIFEST`) lists
+    // itself while still empty, so it is left out of the comparison.
+    let manifest_file = manifest.canonicalize().ok();
+    let is_manifest =
+        |file: &Path| manifest_file.is_some() && file.canonicalize().ok() == manifest_file;
+
+    let mut current: BTreeMap<String, PathBuf> = BTreeMap::new();
+    for root in &roots {
+        for file in collect_files(Path::new(root))? {
+            if !is_manifest(&file) {
+                current.insert(paths::slash(&file), file);
+            }
+        }
+    }
+
+    recorded.retain(|path, _| !is_manifest(Path::new(path)));
+
+    let mut differences: Vec<(&str, String, String)> = Vec::new();
+
+    for (path, before) in &recorded {
+        match current.get(path) {
+            None => differences.push(("missing", path.clone(), String::new())),
+            Some(file) => {
+                let after = ManifestEntry::new(file)?;
+                if after.sha256 != before.sha256 {
+                    differences.push(("changed", path.clone(), after.changes(before)));
+                }
+            }
+        }
+    }
+
+    for path in current.keys().filter(|path| !recorded.contains_key(*path)) {
+        differences.push(("new", path.clone(), String::new()));
+    }
+
+    if differences.is_empty() {
+        println!("All {} files match {}.", recorded.len(), manifest.display());
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    print_differences(&differences);
+    println!(
+        "{} of {} files differ from {}.",
+        differences.len(),
+        recorded.len(),
+        manifest.display()
+    );
+
+    Ok(ExitCode::FAILURE)
+}
+
+/// Returns the roots and the entries (by path) of a manifest.
+fn parse_manifest(
+    contents: &str,
+) -> anyhow::Result<(Vec<String>, BTreeMap<String, ManifestEntry>)> {
+    let roots: Vec<String> = contents
+        .lines()
+        .filter_map(|line| line.strip_prefix(ROOT_PREFIX))
+        .map(str::to_string)
+        .collect();
+
+    let mut reader = csv::ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .from_reader(contents.as_bytes());
+    let mut entries: BTreeMap<String, ManifestEntry> = BTreeMap::new();
+    for entry in reader.deserialize() {
+        let entry: ManifestEntry = entry?;
+        entries.insert(entry.path.clone(), entry);
+    }
+
+    Ok((roots, entries))
+}
+
+fn print_differences(differences: &[(&str, String, String)]) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+    table.set_titles(Row::new(
+        ["Status", "File", "Changes"]
+            .iter()
+            .map(|title| Cell::new(title).style_spec("b"))
+            .collect(),
+    ));
+
+    for (status, path, changes) in differences {
+        let style = match *status {
+            "missing" => "Fr",
+            "new" => "Fg",
+            _ => "Fy",
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(status).style_spec(style),
+            Cell::new(path),
+            Cell::new(changes),
+        ]));
+    }
+
+    table.printstd();
+}
+
+#[test]
+fn test_manifest_round_trips() {
+    let entry = ManifestEntry::new(Path::new("assets/test_2.txt")).expect("Failed to inventory");
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.serialize(&entry).expect("Failed to write entry");
+    let contents = format!(
+        "# wcx manifest\n{}assets\n{}",
+        ROOT_PREFIX,
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    );
+
+    let (roots, entries) = parse_manifest(&contents).expect("Failed to parse manifest");
+
+    assert_eq!(roots, vec!["assets"]);
+    assert_eq!(entries.get("assets/test_2.txt"), Some(&entry));
+    assert_eq!(entry.sha256.len(), 64);
+}
+
+#[test]
+fn test_changes_lists_counts_that_differ() {
+    let before = ManifestEntry {
+        path: String::from("notes.md"),
+        bytes: 100,
+        sha256: String::from("a"),
+        lines: 10,
+        words: Some(20),
+        chars: Some(100),
+    };
+    let after = ManifestEntry {
+        path: before.path.clone(),
+        sha256: String::from("b"),
+        words: Some(25),
+        ..before
+    };
+
+    assert_eq!(after.changes(&before), "words 20 → 25");
+}
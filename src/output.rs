@@ -0,0 +1,613 @@
+use crate::cli::Output;
+use crate::command::{subtotal_label, Column, SortKey, TableManager};
+use crate::layout::Align;
+use crate::pager::{self, Paging};
+use chrono::Local;
+use prettytable::{Cell, Row, Table};
+use serde_json::{Map, Value};
+use std::time::Duration;
+
+/// Prints the rows and totals of `table_manager` in the chosen output format. Machine-readable
+/// formats use the same columns as the table; `elapsed` is only reported in JSON, and left out
+/// when the table manager is deterministic. Only tables go through the pager.
+pub fn print(
+    table_manager: &TableManager,
+    output: Output,
+    paging: Paging,
+    elapsed: Duration,
+) -> anyhow::Result<()> {
+    match (output, table_manager.pivot) {
+        (Output::Table, false) => pager::print(&table_manager.printed_table(), paging),
+        (Output::Table, true) => pager::print(&pivot_table(table_manager, false), paging),
+        _ => print!("{}", render(table_manager, output, elapsed)?),
+    }
+
+    Ok(())
+}
+
+/// Renders the rows and totals of `table_manager` the way [`print`] prints them, without the
+/// pager.
+pub fn render(
+    table_manager: &TableManager,
+    output: Output,
+    elapsed: Duration,
+) -> anyhow::Result<String> {
+    Ok(match (output, table_manager.pivot) {
+        (Output::Table, false) => table_manager.printed_table().to_string(),
+        (Output::Table, true) => pivot_table(table_manager, false).to_string(),
+        (Output::Csv, false) => csv(table_manager)?,
+        (Output::Csv, true) => pivot_csv(table_manager)?,
+        (Output::Json, _) => format!("{}\n", json(table_manager, elapsed)?),
+        (Output::Markdown, false) => markdown(table_manager),
+        (Output::Markdown, true) => pivot_markdown(table_manager),
+        (Output::ScreenReader, _) => screen_reader(table_manager),
+    })
+}
+
+/// Prints the value of the only column for every row, one per line and without the totals, for
+/// `--raw`.
+pub fn print_raw(table_manager: &TableManager) {
+    print!("{}", raw(table_manager));
+}
+
+fn raw(table_manager: &TableManager) -> String {
+    table_manager
+        .rows
+        .iter()
+        .flat_map(|(_, file_result)| {
+            table_manager
+                .columns
+                .iter()
+                .map(|column| format!("{}\n", column.value(file_result)))
+        })
+        .collect()
+}
+
+/// The results transposed for `--pivot`: the row labels (and `total`) become the columns, and
+/// every column becomes a row of values.
+struct Pivot {
+    labels: Vec<String>,
+    rows: Vec<(Column, Vec<String>)>,
+}
+
+fn pivot(table_manager: &TableManager) -> Pivot {
+    let mut labels: Vec<String> = table_manager
+        .rows
+        .iter()
+        .map(|(label, _)| label.clone())
+        .collect();
+    if table_manager.totals_counter.enabled {
+        labels.push(table_manager.total_label.clone());
+    }
+
+    let totals = table_manager.totals_counter.totals();
+    let rows = table_manager
+        .columns
+        .iter()
+        .map(|column| {
+            let mut values: Vec<String> = table_manager
+                .rows
+                .iter()
+                .map(|(_, file_result)| column.value(file_result))
+                .collect();
+            if table_manager.totals_counter.enabled {
+                values.push(column.total(&totals));
+            }
+            (*column, values)
+        })
+        .collect();
+
+    Pivot { labels, rows }
+}
+
+/// Prints the pivoted table of `table_manager` with the largest value of every metric in green
+/// and the smallest in red, for `wcx vs`.
+pub fn print_comparison(table_manager: &TableManager) {
+    pivot_table(table_manager, true).printstd();
+}
+
+/// Returns the position of the largest and the smallest number among `values`, skipping values
+/// that are not numbers (like `-` for undefined ratios), or `None` when they are all equal.
+fn extremes(values: &[String]) -> Option<(usize, usize)> {
+    let numbers: Vec<(usize, f64)> = values
+        .iter()
+        .enumerate()
+        .filter_map(|(index, value)| Some((index, value.parse().ok()?)))
+        .collect();
+
+    let largest = numbers
+        .iter()
+        .copied()
+        .reduce(|best, next| match next.1 > best.1 {
+            true => next,
+            false => best,
+        })?;
+    let smallest = numbers
+        .iter()
+        .copied()
+        .reduce(|best, next| match next.1 < best.1 {
+            true => next,
+            false => best,
+        })?;
+
+    (largest.1 != smallest.1).then_some((largest.0, smallest.0))
+}
+
+fn pivot_table(table_manager: &TableManager, highlight: bool) -> Table {
+    let byte_size = table_manager.options.byte_size;
+    let pivot = pivot(table_manager);
+    let totals_enabled = table_manager.totals_counter.enabled;
+
+    let mut table = Table::new();
+    table.set_format(*table_manager.table.clone().get_format());
+
+    let mut titles = vec![Cell::new(table_manager.label_title()).style_spec("b")];
+    for (index, label) in pivot.labels.iter().enumerate() {
+        let is_total = totals_enabled && index == pivot.labels.len() - 1;
+        titles.push(Cell::new(label).style_spec(if is_total { "bFg" } else { "b" }));
+    }
+    table.set_titles(Row::new(titles));
+
+    for (column, values) in &pivot.rows {
+        let files = match totals_enabled {
+            true => &values[..values.len() - 1],
+            false => &values[..],
+        };
+        let extremes = extremes(files).filter(|_| highlight);
+
+        let mut cells = vec![Cell::new(column.title(byte_size)).style_spec("b")];
+        for (index, value) in values.iter().enumerate() {
+            let is_total = totals_enabled && index == values.len() - 1;
+            cells.push(match (is_total, extremes) {
+                (true, _) => Cell::new(value).style_spec("bFg"),
+                (false, Some((largest, _))) if index == largest => {
+                    Cell::new(value).style_spec("bFg")
+                }
+                (false, Some((_, smallest))) if index == smallest => {
+                    Cell::new(value).style_spec("Fr")
+                }
+                (false, _) => Cell::new(value),
+            });
+        }
+        table.add_row(Row::new(cells));
+    }
+
+    table
+}
+
+fn pivot_csv(table_manager: &TableManager) -> anyhow::Result<String> {
+    let byte_size = table_manager.options.byte_size;
+    let pivot = pivot(table_manager);
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    let mut header = vec![table_manager.label_title().to_lowercase()];
+    header.extend(pivot.labels);
+    writer.write_record(&header)?;
+
+    for (column, values) in pivot.rows {
+        let mut record = vec![column.key(byte_size).to_string()];
+        record.extend(values);
+        writer.write_record(&record)?;
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+fn pivot_markdown(table_manager: &TableManager) -> String {
+    let byte_size = table_manager.options.byte_size;
+    let pivot = pivot(table_manager);
+
+    let mut titles = vec![table_manager.label_title().to_string()];
+    titles.extend(pivot.labels.iter().map(|label| label.replace('|', "\\|")));
+
+    let mut alignments = vec!["---"];
+    alignments.extend(pivot.labels.iter().map(|_| "---:"));
+
+    let mut lines = vec![markdown_row(&titles), markdown_row(&alignments)];
+    for (column, values) in &pivot.rows {
+        let mut cells = vec![column.title(byte_size).to_string()];
+        cells.extend(values.iter().cloned());
+        lines.push(markdown_row(&cells));
+    }
+
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+fn csv(table_manager: &TableManager) -> anyhow::Result<String> {
+    let byte_size = table_manager.options.byte_size;
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    let mut header: Vec<&str> = table_manager
+        .columns
+        .iter()
+        .map(|column| column.key(byte_size))
+        .collect();
+    let label_key = table_manager.label_title().to_lowercase();
+    if !table_manager.no_filename {
+        header.push(&label_key);
+    }
+    if table_manager.row_numbers {
+        header.insert(0, "row");
+    }
+    writer.write_record(&header)?;
+
+    for (index, (file, file_result)) in table_manager.rows.iter().enumerate() {
+        let mut record: Vec<String> = table_manager
+            .columns
+            .iter()
+            .map(|column| column.value(file_result))
+            .collect();
+        if !table_manager.no_filename {
+            record.push(file.clone());
+        }
+        if table_manager.row_numbers {
+            record.insert(0, (index + 1).to_string());
+        }
+        writer.write_record(&record)?;
+    }
+
+    for (source, subtotals) in table_manager.totals_counter.subtotals() {
+        let mut record: Vec<String> = table_manager
+            .columns
+            .iter()
+            .map(|column| column.total(&subtotals))
+            .collect();
+        if !table_manager.no_filename {
+            record.push(subtotal_label(&table_manager.total_label, source));
+        }
+        if table_manager.row_numbers {
+            record.insert(0, String::new());
+        }
+        writer.write_record(&record)?;
+    }
+
+    if table_manager.totals_counter.enabled {
+        let totals = table_manager.totals_counter.totals();
+        let mut record: Vec<String> = table_manager
+            .columns
+            .iter()
+            .map(|column| column.total(&totals))
+            .collect();
+        if !table_manager.no_filename {
+            record.push(table_manager.total_label.clone());
+        }
+        if table_manager.row_numbers {
+            record.insert(0, String::new());
+        }
+        writer.write_record(&record)?;
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+fn json(table_manager: &TableManager, elapsed: Duration) -> anyhow::Result<String> {
+    let byte_size = table_manager.options.byte_size;
+    let mut document = Map::new();
+
+    if !table_manager.deterministic {
+        document.insert(
+            String::from("generated_at"),
+            Local::now().to_rfc3339().into(),
+        );
+        document.insert(
+            String::from("elapsed_ms"),
+            (elapsed.as_millis() as u64).into(),
+        );
+    }
+
+    let label_key = table_manager.label_title().to_lowercase();
+    let files: Vec<Value> = table_manager
+        .rows
+        .iter()
+        .map(|(file, file_result)| {
+            let mut entry = Map::new();
+            if !table_manager.no_filename {
+                entry.insert(label_key.clone(), file.clone().into());
+            }
+            for column in &table_manager.columns {
+                entry.insert(column.key(byte_size).to_string(), column.json(file_result));
+            }
+            Value::Object(entry)
+        })
+        .collect();
+    let rows_key = match table_manager.group_by {
+        Some(_) => "groups",
+        None => "files",
+    };
+    document.insert(String::from(rows_key), files.into());
+
+    if table_manager.totals_counter.enabled {
+        let totals = table_manager.totals_counter.totals();
+        let mut entry = Map::new();
+        for column in table_manager
+            .columns
+            .iter()
+            .filter(|column| column.has_total())
+        {
+            entry.insert(column.key(byte_size).to_string(), column.json(&totals));
+        }
+        document.insert(String::from("total"), Value::Object(entry));
+    }
+
+    let subtotals = table_manager.totals_counter.subtotals();
+    if !subtotals.is_empty() {
+        let mut by_source = Map::new();
+        for (source, subtotals) in subtotals {
+            let mut entry = Map::new();
+            for column in table_manager
+                .columns
+                .iter()
+                .filter(|column| column.has_total())
+            {
+                entry.insert(column.key(byte_size).to_string(), column.json(&subtotals));
+            }
+            by_source.insert(source.name().to_string(), Value::Object(entry));
+        }
+        document.insert(String::from("subtotals"), Value::Object(by_source));
+    }
+
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+fn markdown(table_manager: &TableManager) -> String {
+    let byte_size = table_manager.options.byte_size;
+
+    let mut titles: Vec<&str> = table_manager
+        .columns
+        .iter()
+        .map(|column| column.title(byte_size))
+        .collect();
+    if !table_manager.no_filename {
+        titles.push(table_manager.label_title());
+    }
+    let alignments: Vec<&str> = table_manager
+        .column_keys()
+        .into_iter()
+        .map(|key| match (table_manager.layout.align(key), key) {
+            (Some(Align::Right), _) | (None, SortKey::Column(_)) => "---:",
+            (Some(Align::Left), _) => ":---",
+            (None, _) => "---",
+        })
+        .collect();
+
+    let mut lines = vec![markdown_row(&titles), markdown_row(&alignments)];
+
+    for (file, file_result) in &table_manager.rows {
+        let mut cells: Vec<String> = table_manager
+            .columns
+            .iter()
+            .map(|column| column.value(file_result))
+            .collect();
+        if !table_manager.no_filename {
+            cells.push(file.replace('|', "\\|"));
+        }
+        lines.push(markdown_row(&cells));
+    }
+
+    for (source, subtotals) in table_manager.totals_counter.subtotals() {
+        let mut cells: Vec<String> = table_manager
+            .columns
+            .iter()
+            .map(|column| column.total(&subtotals))
+            .collect();
+        if !table_manager.no_filename {
+            cells.push(subtotal_label(&table_manager.total_label, source));
+        }
+        lines.push(markdown_row(&cells));
+    }
+
+    if table_manager.totals_counter.enabled {
+        let totals = table_manager.totals_counter.totals();
+        let mut cells: Vec<String> = table_manager
+            .columns
+            .iter()
+            .map(|column| match column.has_total() {
+                true => format!("**{}**", column.total(&totals)),
+                false => String::new(),
+            })
+            .collect();
+        if !table_manager.no_filename {
+            cells.push(format!(
+                "**{}**",
+                table_manager.total_label.replace('|', "\\|")
+            ));
+        }
+        lines.push(markdown_row(&cells));
+    }
+
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+fn markdown_row<S: AsRef<str>>(cells: &[S]) -> String {
+    let cells: Vec<&str> = cells.iter().map(|cell| cell.as_ref()).collect();
+    format!("| {} |", cells.join(" | "))
+}
+
+/// Describes every row, then the subtotals and the totals, as a block of `metric: value` lines
+/// separated by blank lines, for `--screen-reader`: there is no table drawing to read out, and
+/// every value is read with its own label instead of a column header far above it.
+fn screen_reader(table_manager: &TableManager) -> String {
+    let byte_size = table_manager.options.byte_size;
+    let labeled = |label: &str, value: String| match value.is_empty() {
+        true => format!("{}: none", label),
+        false => format!("{}: {}", label, value),
+    };
+    let mut blocks: Vec<Vec<String>> = Vec::new();
+
+    for (index, (file, file_result)) in table_manager.rows.iter().enumerate() {
+        let mut lines = Vec::new();
+        if !table_manager.no_filename {
+            lines.push(labeled(table_manager.label_title(), file.clone()));
+        }
+        if table_manager.row_numbers {
+            lines.push(labeled("Row", (index + 1).to_string()));
+        }
+        lines.extend(
+            table_manager
+                .columns
+                .iter()
+                .map(|column| labeled(column.title(byte_size), column.value(file_result))),
+        );
+        blocks.push(lines);
+    }
+
+    let mut totals: Vec<_> = table_manager
+        .totals_counter
+        .subtotals()
+        .into_iter()
+        .map(|(source, subtotals)| {
+            (
+                subtotal_label(&table_manager.total_label, source),
+                subtotals,
+            )
+        })
+        .collect();
+    if table_manager.totals_counter.enabled {
+        totals.push((
+            table_manager.total_label.clone(),
+            table_manager.totals_counter.totals(),
+        ));
+    }
+    for (label, totals) in totals {
+        let mut lines = vec![labeled("Totals", label)];
+        lines.extend(
+            table_manager
+                .columns
+                .iter()
+                .filter(|column| column.has_total())
+                .map(|column| labeled(column.title(byte_size), column.total(&totals))),
+        );
+        blocks.push(lines);
+    }
+
+    blocks
+        .iter()
+        .map(|lines| format!("{}\n", lines.join("\n")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn test_extremes_finds_largest_and_smallest() {
+    let values = |values: &[&str]| {
+        values
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(extremes(&values(&["120", "300", "80"])), Some((1, 2)));
+    assert_eq!(extremes(&values(&["2.50", "1.25"])), Some((0, 1)));
+    assert_eq!(extremes(&values(&["7", "7"])), None);
+    assert_eq!(extremes(&values(&["-", "4.1", "3.9"])), Some((1, 2)));
+    assert_eq!(extremes(&values(&["sparse", ""])), None);
+}
+
+#[test]
+fn test_no_filename_leaves_out_the_label_column() {
+    use crate::command::Builder;
+    use std::path::PathBuf;
+
+    let mut table_manager = Builder::new()
+        .enable_flags(true, false, false, true)
+        .no_filename(true)
+        .build(1);
+    table_manager
+        .set_table_row(&PathBuf::from("assets/test_2.txt"))
+        .expect("Failed to count");
+
+    assert_eq!(csv(&table_manager).unwrap(), "lines,words\n1,1\n");
+}
+
+#[test]
+fn test_raw_prints_one_number_per_file() {
+    use crate::command::Builder;
+    use std::path::PathBuf;
+
+    let mut table_manager = Builder::new()
+        .enable_flags(false, false, false, true)
+        .build(2);
+    for file in ["assets/test_1.txt", "assets/test_2.txt"] {
+        table_manager
+            .set_table_row(&PathBuf::from(file))
+            .expect("Failed to count");
+    }
+
+    assert_eq!(raw(&table_manager), "70\n1\n");
+}
+
+#[test]
+fn test_subtotals_split_mixed_sources() {
+    use crate::analyze::FileResult;
+    use crate::command::{Builder, Source};
+    use std::path::PathBuf;
+
+    let mut table_manager = Builder::new()
+        .enable_flags(false, false, false, true)
+        .build(2);
+    table_manager
+        .set_table_row(&PathBuf::from("assets/test_1.txt"))
+        .expect("Failed to count");
+    assert_eq!(table_manager.totals_counter.subtotals().len(), 0);
+
+    let archived = FileResult {
+        words: 5,
+        ..Default::default()
+    };
+    table_manager
+        .totals_counter
+        .add_to_totals(&archived, Source::Archive);
+
+    assert_eq!(
+        csv(&table_manager).unwrap(),
+        "words,file\n70,assets/test_1.txt\n70,total (local)\n5,total (archive)\n75,total\n"
+    );
+}
+
+#[test]
+fn test_row_numbers_lead_the_csv_rows() {
+    use crate::command::Builder;
+    use std::path::PathBuf;
+
+    let mut table_manager = Builder::new()
+        .enable_flags(false, false, false, true)
+        .row_numbers(true)
+        .build(2);
+    for file in ["assets/test_1.txt", "assets/test_2.txt"] {
+        table_manager
+            .set_table_row(&PathBuf::from(file))
+            .expect("Failed to count");
+    }
+
+    assert_eq!(
+        csv(&table_manager).unwrap(),
+        "row,words,file\n1,70,assets/test_1.txt\n2,1,assets/test_2.txt\n,71,total\n"
+    );
+    let table = table_manager.printed_table();
+    assert_eq!(
+        table.get_row(1).unwrap().get_cell(0).unwrap().get_content(),
+        "2"
+    );
+}
+
+#[test]
+fn test_screen_reader_labels_every_count() {
+    use crate::command::Builder;
+    use std::path::PathBuf;
+
+    let mut table_manager = Builder::new()
+        .enable_flags(true, false, false, true)
+        .build(2);
+    for file in ["assets/test_1.txt", "assets/test_2.txt"] {
+        table_manager
+            .set_table_row(&PathBuf::from(file))
+            .expect("Failed to count");
+    }
+
+    assert_eq!(
+        screen_reader(&table_manager),
+        "File: assets/test_1.txt\nLines: 1\nWords: 70\n\n\
+         File: assets/test_2.txt\nLines: 1\nWords: 1\n\n\
+         Totals: total\nLines: 2\nWords: 71\n"
+    );
+}
@@ -0,0 +1,82 @@
+use prettytable::Table;
+use std::io::IsTerminal;
+#[cfg(any(unix, windows))]
+use std::io::{ErrorKind, Write};
+#[cfg(any(unix, windows))]
+use std::process::{Command, Stdio};
+
+/// When the table goes through a pager, chosen with `--pager` and `--no-pager`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Paging {
+    /// When stdout is a terminal and the table is taller than it.
+    Auto,
+    Always,
+    Never,
+}
+
+/// Prints `table` like [`Table::printstd`], through `$PAGER` (`less` by default) when `paging`
+/// calls for it. wcx waits for the pager to exit; a pager that cannot be started is skipped.
+pub fn print(table: &Table, paging: Paging) {
+    match paging == Paging::Never || !std::io::stdout().is_terminal() {
+        true => table.printstd(),
+        false => print_paged(table, paging),
+    }
+}
+
+#[cfg(any(unix, windows))]
+fn print_paged(table: &Table, paging: Paging) {
+    let (rendered, lines) = render(table);
+    let fits = terminal_size::terminal_size()
+        .is_some_and(|(_, terminal_size::Height(height))| lines < usize::from(height));
+    if (paging == Paging::Auto && fits) || !page(&rendered) {
+        let _ = std::io::stdout().write_all(&rendered);
+    }
+}
+
+/// Without processes (on WASI) there is no pager to start.
+#[cfg(not(any(unix, windows)))]
+fn print_paged(table: &Table, _paging: Paging) {
+    table.printstd();
+}
+
+/// Renders `table` with the colors of the terminal, returning the output and its line count.
+#[cfg(any(unix, windows))]
+fn render(table: &Table) -> (Vec<u8>, usize) {
+    if let Some(mut terminal) = term::TerminfoTerminal::new(Vec::new()) {
+        if let Ok(lines) = table.print_term(&mut terminal) {
+            return (term::Terminal::into_inner(terminal), lines);
+        }
+    }
+
+    let mut rendered = Vec::new();
+    let lines = table.print(&mut rendered).unwrap_or(0);
+    (rendered, lines)
+}
+
+/// Writes `rendered` into the pager. Returns false when the pager cannot be started.
+#[cfg(any(unix, windows))]
+fn page(rendered: &[u8]) -> bool {
+    let pager = std::env::var("PAGER").unwrap_or_default();
+    let mut words = pager.split_whitespace();
+    let mut command = Command::new(words.next().unwrap_or("less"));
+    command.args(words).stdin(Stdio::piped());
+    // Like git: quit right away when everything fits, keep colors, and leave the table on the
+    // screen after quitting.
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+
+    let Ok(mut child) = command.spawn() else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager closes its input when it quits before reaching the end of the table.
+        if let Err(err) = stdin.write_all(rendered) {
+            if err.kind() != ErrorKind::BrokenPipe {
+                eprintln!("wcx: failed to write to the pager: {}", err);
+            }
+        }
+    }
+    let _ = child.wait();
+    true
+}
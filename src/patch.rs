@@ -0,0 +1,131 @@
+use crate::stats::{Stat, Stats};
+
+/// Counts the files a unified diff touches, the lines it adds and removes, and its hunks. Works
+/// on `git diff` output as well as plain `diff -u`; lines inside hunks are told apart from file
+/// headers by the line counts of each hunk header, so a removed line starting with `--` is still
+/// a removed line.
+pub fn measure(name: &str, content: &[u8]) -> anyhow::Result<Stats> {
+    let mut files = 0;
+    let mut added = 0;
+    let mut removed = 0;
+    let mut hunks = 0;
+    // Lines of the current hunk still to come, on the old and the new side.
+    let (mut old, mut new) = (0, 0);
+    // Whether a `diff --git` line already counted the file whose `---`/`+++` headers follow.
+    let mut counted = false;
+
+    for (number, line) in content.split(|byte| *byte == b'\n').enumerate() {
+        let longer_than_header =
+            || anyhow::anyhow!("{}:{}: hunk longer than its header", name, number + 1);
+        if old > 0 || new > 0 {
+            match line.first() {
+                Some(b'+') => {
+                    added += 1;
+                    new = usize::checked_sub(new, 1).ok_or_else(longer_than_header)?;
+                }
+                Some(b'-') => {
+                    removed += 1;
+                    old = usize::checked_sub(old, 1).ok_or_else(longer_than_header)?;
+                }
+                // `\ No newline at end of file` belongs to the line before it.
+                Some(b'\\') => {}
+                _ => {
+                    old = usize::saturating_sub(old, 1);
+                    new = usize::saturating_sub(new, 1);
+                }
+            }
+            continue;
+        }
+
+        if line.starts_with(b"diff --git ") {
+            files += 1;
+            counted = true;
+        } else if line.starts_with(b"+++ ") {
+            files += usize::from(!counted);
+            counted = false;
+        } else if line.starts_with(b"@@ ") {
+            let header = String::from_utf8_lossy(line);
+            (old, new) = hunk_lengths(&header).ok_or_else(|| {
+                anyhow::anyhow!("{}:{}: invalid hunk header `{}`", name, number + 1, header)
+            })?;
+            hunks += 1;
+        }
+    }
+
+    Ok(Stats::from([
+        (Stat::FilesTouched, files),
+        (Stat::LinesAdded, added),
+        (Stat::LinesRemoved, removed),
+        (Stat::Hunks, hunks),
+    ]))
+}
+
+/// Reads the old and new line counts of a hunk header like `@@ -12,7 +12,9 @@ fn main()`. A
+/// range without a count (`-12`) spans one line.
+fn hunk_lengths(header: &str) -> Option<(usize, usize)> {
+    let mut ranges = header.strip_prefix("@@ ")?.split_whitespace();
+    let length = |range: &str| {
+        let (start, count) = range.split_once(',').unwrap_or((range, "1"));
+        start.parse::<usize>().ok()?;
+        count.parse().ok()
+    };
+
+    let old = length(ranges.next()?.strip_prefix('-')?)?;
+    let new = length(ranges.next()?.strip_prefix('+')?)?;
+    Some((old, new))
+}
+
+#[test]
+fn test_measure_git_diff() {
+    let diff = b"diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@
+ fn one() {}
+--- decrement
++++ increment
+ fn two() {}
+@@ -10 +10,2 @@ fn ten() {}
+ fn ten() {}
++fn eleven() {}
+diff --git a/old.txt b/new.txt
+similarity index 100%
+rename from old.txt
+rename to new.txt
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+\\ No newline at end of file
+";
+
+    let stats = measure("change.patch", diff).expect("Failed to measure");
+
+    assert_eq!(stats[&Stat::FilesTouched], 3);
+    assert_eq!(stats[&Stat::LinesAdded], 2);
+    assert_eq!(stats[&Stat::LinesRemoved], 2);
+    assert_eq!(stats[&Stat::Hunks], 3);
+}
+
+#[test]
+fn test_measure_plain_unified_diff() {
+    let diff =
+        b"--- a.txt\t2024-01-01\n+++ a.txt\t2024-01-02\n@@ -1,2 +1,2 @@\n-old\n+new\n same\n";
+
+    let stats = measure("a.diff", diff).expect("Failed to measure");
+
+    assert_eq!(stats[&Stat::FilesTouched], 1);
+    assert_eq!(stats[&Stat::LinesAdded], 1);
+    assert_eq!(stats[&Stat::LinesRemoved], 1);
+    assert!(measure("a.diff", b"@@ -x +1 @@\n").is_err());
+}
+
+#[test]
+fn test_hunks_longer_than_their_header_are_errors() {
+    let error = measure("a.diff", b"@@ -1,2 +1,0 @@\n+x\n-y\n").unwrap_err();
+
+    assert_eq!(error.to_string(), "a.diff:2: hunk longer than its header");
+}
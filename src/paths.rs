@@ -0,0 +1,111 @@
+use std::env::current_dir;
+use std::path::{Component, Path, PathBuf};
+
+/// Formats a path for output; see `wcx_core::display_path`.
+pub use wcx_core::display_path as display;
+
+/// Whether `path` is `-`, which stands for stdin in the files to count.
+pub fn is_stdin(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+/// Joins the components of a relative path with `/` on every platform, so glob patterns and
+/// recorded keys (like a ratchet baseline) match the same way on Windows and Unix.
+pub fn slash(path: &Path) -> String {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Formats a path the same way wherever and on whichever platform wcx runs: relative to the
+/// current directory with `/` separators. Paths outside the current directory are shown
+/// relative to `root`, the [`common_root`] of the paths counted with them, so no absolute path
+/// ends up in the output while `/a/x.txt` and `/b/x.txt` still tell apart as `a/x.txt` and
+/// `b/x.txt`.
+pub fn stable(path: &Path, root: &Path) -> String {
+    let cwd = current_dir().unwrap_or_default();
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+
+    match absolute.strip_prefix(&cwd) {
+        Ok(relative) => slash(relative),
+        _ => match absolute.strip_prefix(root) {
+            Ok(relative) if root.has_root() => slash(relative),
+            _ => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        },
+    }
+}
+
+/// Returns the deepest directory that every one of `paths` outside the current directory is
+/// in, which [`stable`] shows them relative to; empty when all of them are inside it.
+pub fn common_root(paths: &[PathBuf]) -> PathBuf {
+    let cwd = current_dir().unwrap_or_default();
+    let mut root: Option<PathBuf> = None;
+
+    for path in paths.iter().filter(|path| !is_stdin(path)) {
+        let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        if absolute.starts_with(&cwd) {
+            continue;
+        }
+
+        let dir = absolute.parent().unwrap_or(&absolute);
+        root = Some(match root {
+            None => dir.to_path_buf(),
+            Some(root) => root
+                .components()
+                .zip(dir.components())
+                .take_while(|(left, right)| left == right)
+                .map(|(component, _)| component)
+                .collect(),
+        });
+    }
+
+    root.unwrap_or_default()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_stable_tells_apart_files_of_the_same_name() {
+    let files = [PathBuf::from("/a/x.txt"), PathBuf::from("/b/x.txt")];
+    let root = common_root(&files);
+    assert_eq!(stable(&files[0], &root), "a/x.txt");
+    assert_eq!(stable(&files[1], &root), "b/x.txt");
+
+    let root = common_root(&files[..1]);
+    assert_eq!(stable(&files[0], &root), "x.txt");
+    assert_eq!(stable(Path::new("src/main.rs"), &root), "src/main.rs");
+}
+
+#[test]
+fn test_slash_joins_components() {
+    assert_eq!(slash(Path::new("./src/main.rs")), "src/main.rs");
+}
+
+#[cfg(windows)]
+#[test]
+fn test_slash_normalizes_backslashes() {
+    assert_eq!(
+        slash(Path::new(r"docs\guide\intro.md")),
+        "docs/guide/intro.md"
+    );
+}
+
+#[test]
+fn test_stable_is_relative_to_the_current_directory() {
+    let cwd = current_dir().expect("Failed to get current directory");
+
+    let root = Path::new("");
+    assert_eq!(
+        stable(&cwd.join("assets/test_1.txt"), root),
+        "assets/test_1.txt"
+    );
+    assert_eq!(
+        stable(Path::new("./assets/test_1.txt"), root),
+        "assets/test_1.txt"
+    );
+    assert_eq!(stable(Path::new("/elsewhere/notes.md"), root), "notes.md");
+}
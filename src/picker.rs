@@ -0,0 +1,30 @@
+use crate::paths;
+use crate::walk::collect_files;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+/// Lets the user pick the files to count below the current directory, typing to fuzzy-filter
+/// the list, when wcx is run in a terminal without any files. Fails with a usage hint otherwise.
+pub fn pick() -> anyhow::Result<Vec<PathBuf>> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("No files given; pass FILES, --files-from, --files0-from or --urls-from");
+    }
+
+    let files = collect_files(Path::new("."))?;
+    let options: Vec<String> = files.iter().map(|file| paths::slash(file)).collect();
+    if options.is_empty() {
+        anyhow::bail!("No files to pick from in the current directory");
+    }
+
+    let picked = inquire::MultiSelect::new("Files to count:", options.clone())
+        .with_help_message("type to filter, space to pick, → to pick all, enter to count")
+        .raw_prompt()?;
+    if picked.is_empty() {
+        anyhow::bail!("No files picked");
+    }
+
+    Ok(picked
+        .into_iter()
+        .map(|option| files[option.index].clone())
+        .collect())
+}
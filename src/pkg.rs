@@ -0,0 +1,229 @@
+use crate::archive::{self, ArchiveLimits};
+use crate::cli::{EolStyle, IncompleteLine};
+use crate::eol;
+use crate::language;
+use anyhow::Context;
+use prettytable::{format, Cell, Row, Table};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Packages are read with the same limits as `--archives` uses by default.
+const LIMITS: ArchiveLimits = ArchiveLimits {
+    depth: 1,
+    max_expanded_size: 1 << 30,
+};
+
+/// Files, lines and bytes of the source files of one language (or one file) in a package.
+#[derive(Default, Debug, PartialEq)]
+struct SourceTotals {
+    files: usize,
+    lines: usize,
+    bytes: usize,
+}
+
+/// A package on a registry, written `crates:serde@1.0.200`, or without a version for the latest.
+#[derive(Debug, PartialEq)]
+struct RemotePackage {
+    registry: Registry,
+    name: String,
+    version: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Registry {
+    Crates,
+    PyPI,
+}
+
+impl RemotePackage {
+    fn parse(spec: &str) -> Option<RemotePackage> {
+        let (registry, package) = match spec.split_once(':')? {
+            ("crates", package) => (Registry::Crates, package),
+            ("pypi", package) => (Registry::PyPI, package),
+            _ => return None,
+        };
+
+        let (name, version) = match package.split_once('@') {
+            Some((name, version)) => (name, Some(version.to_string())),
+            None => (package, None),
+        };
+
+        Some(RemotePackage {
+            registry,
+            name: name.to_string(),
+            version,
+        })
+    }
+}
+
+/// Counts the source files inside a package, to size up a dependency before adopting it. The
+/// package is a local file, or one downloaded from crates.io or PyPI. Entries whose language
+/// cannot be told (binaries, images, `.class` files) are left out. Lines are counted like `-l`
+/// counts them, with `eol_style` and `incomplete_line`.
+pub fn invoke(
+    package: &str,
+    files: bool,
+    eol_style: EolStyle,
+    incomplete_line: IncompleteLine,
+) -> anyhow::Result<()> {
+    let mut rows: BTreeMap<String, SourceTotals> = BTreeMap::new();
+    let mut add = |entry: archive::Entry| {
+        let Ok(content) = entry.content else {
+            return;
+        };
+
+        let language = language::detect(Path::new(&entry.name), Some(&content));
+        if language == language::UNKNOWN {
+            return;
+        }
+
+        let key = match files {
+            true => entry.name,
+            false => language.to_string(),
+        };
+        let totals = rows.entry(key).or_default();
+        totals.files += 1;
+        totals.lines += eol::count_lines(&content, eol_style, incomplete_line);
+        totals.bytes += content.len();
+    };
+
+    match RemotePackage::parse(package) {
+        Some(remote) => {
+            let (file_name, content) = download(&remote)?;
+            archive::expand_bytes(&file_name, &file_name, content, &LIMITS, None, &mut add)
+                .with_context(|| format!("Failed to read package {}", file_name))?
+        }
+        None => {
+            let path = Path::new(package);
+            if !archive::is_archive(path) {
+                anyhow::bail!(
+                    "{} is not a package. Note: `wcx pkg` reads .crate, .jar, .whl, .tar.gz and .zip files, or crates:NAME[@VERSION] and pypi:NAME[@VERSION].",
+                    package
+                );
+            }
+
+            archive::expand(path, package, &LIMITS, None, &mut add)
+                .with_context(|| format!("Failed to read package {}", package))?
+        }
+    }
+
+    print_table(&rows, if files { "File" } else { "Language" });
+    Ok(())
+}
+
+/// Downloads a package archive into memory and returns its file name and content. PyPI
+/// releases are counted from their source distribution, or their first wheel if there is none.
+#[cfg(feature = "remote")]
+fn download(package: &RemotePackage) -> anyhow::Result<(String, Vec<u8>)> {
+    use crate::remote;
+    use serde_json::Value;
+
+    let name = &package.name;
+
+    match package.registry {
+        Registry::Crates => {
+            let version = match &package.version {
+                Some(version) => version.clone(),
+                None => {
+                    let url = format!("https://crates.io/api/v1/crates/{}", name);
+                    let info: Value = serde_json::from_slice(&remote::get(&url)?)?;
+                    info["crate"]["max_stable_version"]
+                        .as_str()
+                        .or(info["crate"]["max_version"].as_str())
+                        .with_context(|| format!("crates.io has no version of {}", name))?
+                        .to_string()
+                }
+            };
+
+            let file_name = format!("{}-{}.crate", name, version);
+            let url = format!("https://static.crates.io/crates/{}/{}", name, file_name);
+            Ok((file_name, remote::get(&url)?))
+        }
+        Registry::PyPI => {
+            let url = match &package.version {
+                Some(version) => format!("https://pypi.org/pypi/{}/{}/json", name, version),
+                None => format!("https://pypi.org/pypi/{}/json", name),
+            };
+            let release: Value = serde_json::from_slice(&remote::get(&url)?)?;
+            let release_files = release["urls"].as_array().cloned().unwrap_or_default();
+
+            let chosen = release_files
+                .iter()
+                .find(|file| file["packagetype"] == "sdist")
+                .or_else(|| {
+                    release_files
+                        .iter()
+                        .find(|file| file["packagetype"] == "bdist_wheel")
+                })
+                .with_context(|| format!("PyPI has no source or wheel files for {}", name))?;
+
+            let file_name = chosen["filename"]
+                .as_str()
+                .context("PyPI file without a name")?;
+            let file_url = chosen["url"].as_str().context("PyPI file without a URL")?;
+            Ok((file_name.to_string(), remote::get(file_url)?))
+        }
+    }
+}
+
+#[cfg(not(feature = "remote"))]
+fn download(package: &RemotePackage) -> anyhow::Result<(String, Vec<u8>)> {
+    anyhow::bail!(
+        "Cannot download {}: wcx was built without the `remote` feature",
+        package.name
+    )
+}
+
+fn print_table(rows: &BTreeMap<String, SourceTotals>, label_title: &str) {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+    table.set_titles(Row::new(
+        [label_title, "Files", "Lines", "Bytes"]
+            .iter()
+            .map(|title| Cell::new(title).style_spec("b"))
+            .collect(),
+    ));
+
+    let mut total: SourceTotals = Default::default();
+    for (label, totals) in rows {
+        total.files += totals.files;
+        total.lines += totals.lines;
+        total.bytes += totals.bytes;
+
+        table.add_row(Row::new(vec![
+            Cell::new(label),
+            Cell::new(&totals.files.to_string()),
+            Cell::new(&totals.lines.to_string()),
+            Cell::new(&totals.bytes.to_string()),
+        ]));
+    }
+
+    if rows.len() > 1 {
+        table.add_row(Row::new(vec![
+            Cell::new("total").style_spec("bFg"),
+            Cell::new(&total.files.to_string()).style_spec("bFg"),
+            Cell::new(&total.lines.to_string()).style_spec("bFg"),
+            Cell::new(&total.bytes.to_string()).style_spec("bFg"),
+        ]));
+    }
+
+    table.printstd();
+}
+
+#[test]
+fn test_parse_remote_package() {
+    assert_eq!(
+        RemotePackage::parse("crates:serde@1.0.200"),
+        Some(RemotePackage {
+            registry: Registry::Crates,
+            name: String::from("serde"),
+            version: Some(String::from("1.0.200")),
+        })
+    );
+    assert_eq!(
+        RemotePackage::parse("pypi:requests").map(|package| package.version),
+        Some(None)
+    );
+    assert_eq!(RemotePackage::parse("serde-1.0.200.crate"), None);
+    assert_eq!(RemotePackage::parse("C:/packages/a.zip"), None);
+}
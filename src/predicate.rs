@@ -0,0 +1,290 @@
+use crate::analyze::FileResult;
+use crate::command::Column;
+use std::str::FromStr;
+
+/// How a column value is compared with a number in a [`Predicate`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    /// Operators in matching order, two-character operators first.
+    const OPERATORS: [(&'static str, Comparison); 6] = [
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ];
+
+    fn holds(&self, value: f64, number: f64) -> bool {
+        match self {
+            Comparison::Less => value < number,
+            Comparison::LessOrEqual => value <= number,
+            Comparison::Greater => value > number,
+            Comparison::GreaterOrEqual => value >= number,
+            Comparison::Equal => value == number,
+            Comparison::NotEqual => value != number,
+        }
+    }
+}
+
+/// A condition on the counts of a row, parsed from `--where` expressions like
+/// `lines>1000 && words<50`: comparisons of a column (named as in `--columns`) with a number,
+/// combined with `!`, `&&`, `||` and parentheses.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Predicate {
+    Compare(Column, Comparison, f64),
+    Not(Box<Predicate>),
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+}
+
+impl Predicate {
+    /// Whether `file_result` satisfies the condition. Blank values (a ratio of a file without
+    /// words, or a source that could not be counted) satisfy no comparison.
+    pub fn matches(&self, file_result: &FileResult) -> bool {
+        match self {
+            Predicate::Compare(column, comparison, number) => column
+                .json(file_result)
+                .as_f64()
+                .is_some_and(|value| comparison.holds(value, *number)),
+            Predicate::Not(predicate) => !predicate.matches(file_result),
+            Predicate::And(a, b) => a.matches(file_result) && b.matches(file_result),
+            Predicate::Or(a, b) => a.matches(file_result) || b.matches(file_result),
+        }
+    }
+
+    /// Returns every column the condition compares.
+    pub fn columns(&self) -> Vec<Column> {
+        match self {
+            Predicate::Compare(column, _, _) => vec![*column],
+            Predicate::Not(predicate) => predicate.columns(),
+            Predicate::And(a, b) | Predicate::Or(a, b) => {
+                let mut columns = a.columns();
+                columns.extend(b.columns());
+                columns
+            }
+        }
+    }
+}
+
+impl FromStr for Predicate {
+    type Err = anyhow::Error;
+
+    fn from_str(expression: &str) -> anyhow::Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(expression)?,
+            position: 0,
+            depth: 0,
+        };
+        let predicate = parser.or()?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(predicate),
+            Some(token) => anyhow::bail!("unexpected `{}`", token.text()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Name(String),
+    Number(f64),
+    Operator(Comparison),
+    Not,
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+impl Token {
+    fn text(&self) -> String {
+        match self {
+            Token::Name(name) => name.clone(),
+            Token::Number(number) => number.to_string(),
+            Token::Operator(comparison) => Comparison::OPERATORS
+                .iter()
+                .find(|(_, operator)| operator == comparison)
+                .map_or_else(String::new, |(text, _)| text.to_string()),
+            Token::Not => String::from("!"),
+            Token::And => String::from("&&"),
+            Token::Or => String::from("||"),
+            Token::Open => String::from("("),
+            Token::Close => String::from(")"),
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = expression.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        let symbols = [
+            ("&&", Token::And),
+            ("||", Token::Or),
+            ("(", Token::Open),
+            (")", Token::Close),
+        ];
+        let length = if let Some((text, comparison)) = Comparison::OPERATORS
+            .iter()
+            .find(|(text, _)| rest.starts_with(text))
+        {
+            tokens.push(Token::Operator(*comparison));
+            text.len()
+        } else if let Some((text, token)) = symbols.iter().find(|(text, _)| rest.starts_with(text))
+        {
+            tokens.push(token.clone());
+            text.len()
+        } else if c == '!' {
+            tokens.push(Token::Not);
+            1
+        } else if c.is_ascii_digit() || c == '.' {
+            let length = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            let number = &rest[..length];
+            let number = number
+                .parse()
+                .map_err(|_| anyhow::anyhow!("`{}` is not a number", number))?;
+            tokens.push(Token::Number(number));
+            length
+        } else if c.is_alphabetic() || c == '_' {
+            let length = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Name(rest[..length].to_string()));
+            length
+        } else {
+            anyhow::bail!("unexpected `{}`", c);
+        };
+
+        rest = rest[length..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+/// How deeply `!` and parentheses may nest, so that a long run of `(` is an error rather than
+/// a stack overflow.
+const MAX_DEPTH: usize = 64;
+
+/// A recursive descent parser: `||` binds loosest, then `&&`, then `!`.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    /// How many `!` and `(` enclose the token at `position`.
+    depth: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn next_is(&mut self, token: &Token) -> bool {
+        let found = self.tokens.get(self.position) == Some(token);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> anyhow::Result<Predicate> {
+        let mut predicate = self.and()?;
+        while self.next_is(&Token::Or) {
+            predicate = Predicate::Or(Box::new(predicate), Box::new(self.and()?));
+        }
+        Ok(predicate)
+    }
+
+    fn and(&mut self) -> anyhow::Result<Predicate> {
+        let mut predicate = self.not()?;
+        while self.next_is(&Token::And) {
+            predicate = Predicate::And(Box::new(predicate), Box::new(self.not()?));
+        }
+        Ok(predicate)
+    }
+
+    fn not(&mut self) -> anyhow::Result<Predicate> {
+        match self.next() {
+            Some(Token::Not) => Ok(Predicate::Not(Box::new(self.nested(Parser::not)?))),
+            Some(Token::Open) => {
+                let predicate = self.nested(Parser::or)?;
+                match self.next() {
+                    Some(Token::Close) => Ok(predicate),
+                    _ => anyhow::bail!("missing `)`"),
+                }
+            }
+            Some(Token::Name(name)) => {
+                let column = Column::parse(&name)?;
+                let (Some(Token::Operator(comparison)), Some(Token::Number(number))) =
+                    (self.next(), self.next())
+                else {
+                    anyhow::bail!("expected a comparison like `{}>1000`", name);
+                };
+                Ok(Predicate::Compare(column, comparison, number))
+            }
+            Some(token) => anyhow::bail!(
+                "expected a comparison like `lines>1000`, found `{}`",
+                token.text()
+            ),
+            None => anyhow::bail!("expected a comparison like `lines>1000`"),
+        }
+    }
+
+    /// Parses what follows a `!` or `(` with `parse`, one level deeper.
+    fn nested(
+        &mut self,
+        parse: fn(&mut Parser) -> anyhow::Result<Predicate>,
+    ) -> anyhow::Result<Predicate> {
+        if self.depth == MAX_DEPTH {
+            anyhow::bail!("nested more than {} levels deep", MAX_DEPTH);
+        }
+        self.depth += 1;
+        let predicate = parse(self);
+        self.depth -= 1;
+        predicate
+    }
+}
+
+#[test]
+fn test_where_expressions_combine_comparisons() {
+    let file_result = FileResult {
+        lines: 1200,
+        words: 30,
+        ..Default::default()
+    };
+    let matches = |expression: &str| {
+        expression
+            .parse::<Predicate>()
+            .expect("Failed to parse")
+            .matches(&file_result)
+    };
+
+    assert!(matches("lines>1000 && words<50"));
+    assert!(!matches("lines>1000 && !(words<50)"));
+    assert!(matches("lines<=10 || words==30 && lines!=0"));
+    assert!("lines>".parse::<Predicate>().is_err());
+    assert!("lines>1 words<2".parse::<Predicate>().is_err());
+    assert!("chapters>1".parse::<Predicate>().is_err());
+}
+
+#[test]
+fn test_where_nesting_is_capped() {
+    let nested = |depth: usize| format!("{}lines>1{}", "(".repeat(depth), ")".repeat(depth));
+
+    assert!(nested(MAX_DEPTH).parse::<Predicate>().is_ok());
+    assert!(nested(MAX_DEPTH + 1).parse::<Predicate>().is_err());
+    assert!("(".repeat(1_000_000).parse::<Predicate>().is_err());
+}
@@ -0,0 +1,113 @@
+use anyhow::Context;
+use std::env::current_dir;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+/// The config file holding the `[profile.NAME]` tables, looked up in the current directory and
+/// then in each parent, so one file at the root of a monorepo serves every project in it.
+pub const CONFIG_FILE: &str = "wcx.toml";
+
+/// Returns the command-line arguments the profile `name` stands for, to be parsed before the
+/// ones given on the command line so those win.
+pub fn arguments(name: &str) -> anyhow::Result<Vec<String>> {
+    let path = find_config().with_context(|| {
+        format!(
+            "No {} found here or in any parent directory to read --profile {} from",
+            CONFIG_FILE, name
+        )
+    })?;
+    let config: toml::Table = read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .parse()
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    profile_arguments(&config, name).with_context(|| format!("In {}", path.display()))
+}
+
+fn find_config() -> Option<PathBuf> {
+    current_dir()
+        .ok()?
+        .ancestors()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Turns the table `[profile.NAME]` into arguments: `words = "strict"` becomes `--words=strict`,
+/// `w = true` becomes `-w`, and a list repeats the flag once per item. `false` leaves the flag
+/// out.
+fn profile_arguments(config: &toml::Table, name: &str) -> anyhow::Result<Vec<String>> {
+    let profiles = config.get("profile").and_then(toml::Value::as_table);
+    let Some(profile) = profiles.and_then(|profiles| profiles.get(name)) else {
+        let known: Vec<&str> = profiles
+            .map(|profiles| profiles.keys().map(String::as_str).collect())
+            .unwrap_or_default();
+        anyhow::bail!(
+            "No [profile.{}] (profiles: {})",
+            name,
+            match known.is_empty() {
+                true => String::from("none"),
+                false => known.join(", "),
+            }
+        );
+    };
+    let profile = profile
+        .as_table()
+        .with_context(|| format!("[profile.{}] is not a table", name))?;
+
+    let mut arguments: Vec<String> = Vec::new();
+    for (key, value) in profile {
+        let flag = match key.chars().count() {
+            1 => format!("-{}", key),
+            _ => format!("--{}", key.replace('_', "-")),
+        };
+        let items = match value {
+            toml::Value::Array(items) => items.iter().collect(),
+            value => vec![value],
+        };
+
+        for item in items {
+            match item {
+                toml::Value::Boolean(true) => arguments.push(flag.clone()),
+                toml::Value::Boolean(false) => {}
+                toml::Value::String(value) => arguments.push(format!("{}={}", flag, value)),
+                toml::Value::Integer(value) => arguments.push(format!("{}={}", flag, value)),
+                toml::Value::Float(value) => arguments.push(format!("{}={}", flag, value)),
+                _ => anyhow::bail!(
+                    "[profile.{}] {} must be a flag value or a list of them",
+                    name,
+                    key
+                ),
+            }
+        }
+    }
+
+    Ok(arguments)
+}
+
+#[test]
+fn test_profile_arguments() {
+    let config: toml::Table = r#"
+        [profile.docs]
+        w = true
+        words = "strict"
+        derived = ["avg-word-len", "avg-words-per-line"]
+        max_lines = 800
+        sparse = false
+    "#
+    .parse()
+    .unwrap();
+
+    assert_eq!(
+        profile_arguments(&config, "docs").expect("Failed to read profile"),
+        vec![
+            "--derived=avg-word-len",
+            "--derived=avg-words-per-line",
+            "--max-lines=800",
+            "-w",
+            "--words=strict",
+        ]
+    );
+
+    let error = profile_arguments(&config, "code").expect_err("No such profile");
+    assert_eq!(error.to_string(), "No [profile.code] (profiles: docs)");
+}
@@ -0,0 +1,75 @@
+use crate::analyze::FileResult;
+use crate::cache::Cache;
+use crate::cli::Metric;
+use std::path::Path;
+
+/// Prints a single compact segment such as `12k words` for `path`, meant to be embedded in a
+/// starship custom module or a PS1 prompt.
+///
+/// Counts come from the [`Cache`], so only files modified since the previous prompt are read.
+pub fn invoke(path: &Path, metric: Metric) -> anyhow::Result<()> {
+    let mut cache = Cache::load();
+    let totals = cache.count_path(path)?;
+
+    // A stale cache only costs speed on the next prompt, so a failed write is not worth reporting.
+    let _ = cache.save();
+
+    println!("{}", segment(&totals, metric));
+    Ok(())
+}
+
+fn segment(totals: &FileResult, metric: Metric) -> String {
+    let label = match metric {
+        Metric::Lines => "LOC",
+        Metric::Bytes => "bytes",
+        Metric::Chars => "chars",
+        Metric::Words => "words",
+    };
+
+    format!("{} {}", compact(totals.count(metric)), label)
+}
+
+/// Abbreviates a count to at most three significant digits: `950`, `1.2k`, `12k`, `3.4M`.
+fn compact(count: usize) -> String {
+    let units = [(1_000_000_000, "G"), (1_000_000, "M"), (1_000, "k")];
+
+    for (scale, suffix) in units {
+        if count >= scale {
+            let value = count as f64 / scale as f64;
+            return if value < 10.0 {
+                let rounded = format!("{:.1}", value);
+                format!("{}{}", rounded.trim_end_matches(".0"), suffix)
+            } else {
+                format!("{}{}", value as usize, suffix)
+            };
+        }
+    }
+
+    format!("{}", count)
+}
+
+#[test]
+fn test_compact_small_counts_are_unchanged() {
+    assert_eq!(compact(0), "0");
+    assert_eq!(compact(999), "999");
+}
+
+#[test]
+fn test_compact_abbreviates_large_counts() {
+    assert_eq!(compact(1_000), "1k");
+    assert_eq!(compact(1_250), "1.2k");
+    assert_eq!(compact(12_345), "12k");
+    assert_eq!(compact(3_400_000), "3.4M");
+}
+
+#[test]
+fn test_segment_uses_metric_label() {
+    let totals = FileResult {
+        lines: 12_000,
+        words: 450,
+        ..Default::default()
+    };
+
+    assert_eq!(segment(&totals, Metric::Lines), "12k LOC");
+    assert_eq!(segment(&totals, Metric::Words), "450 words");
+}
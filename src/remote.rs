@@ -0,0 +1,250 @@
+use crate::throttle::{Rate, Throttle};
+use anyhow::Context;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use ureq::Agent;
+
+/// Identifies wcx to servers; crates.io rejects requests without a user agent.
+const USER_AGENT: &str = concat!("wcx/", env!("CARGO_PKG_VERSION"));
+
+/// The largest response body that is read.
+const MAX_BODY: u64 = 1 << 30;
+
+/// How many URLs [`fetch_all`] downloads at the same time.
+const CONCURRENCY: usize = 8;
+
+/// The delay before the first retry; it doubles with every further retry.
+const BACKOFF: Duration = Duration::from_millis(500);
+
+/// How requests are bounded in time and how often failures are retried.
+#[derive(Clone, Copy, Debug)]
+pub struct RemoteOptions {
+    /// Gives up on a request that has not connected, or has not received the response headers,
+    /// in this time each.
+    pub timeout: Option<Duration>,
+    /// Gives up on a body that has not been read in full in this time, so a server that stops
+    /// sending cannot hang the run. Kept apart from `timeout`, as large bodies and `limit_rate`
+    /// make reading take long.
+    pub body_timeout: Option<Duration>,
+    /// How many times a request is retried after a connection error, a timeout, or a `429` or
+    /// `5xx` status.
+    pub retries: u32,
+    /// Caps the combined download speed of all requests.
+    pub limit_rate: Option<Rate>,
+}
+
+impl Default for RemoteOptions {
+    fn default() -> RemoteOptions {
+        RemoteOptions {
+            timeout: Some(Duration::from_secs(30)),
+            body_timeout: Some(Duration::from_secs(300)),
+            retries: 2,
+            limit_rate: None,
+        }
+    }
+}
+
+/// The outcome of fetching one URL, with what was read from its body. `status` and
+/// `content_type` are known whenever the server answered, also when it answered with an error.
+pub struct Fetched<T> {
+    pub url: String,
+    pub status: Option<u16>,
+    pub content_type: Option<String>,
+    pub body: anyhow::Result<T>,
+}
+
+/// Fetches `url` with the default [`RemoteOptions`] and returns the response body. Responses
+/// with an error status fail.
+pub fn get(url: &str) -> anyhow::Result<Vec<u8>> {
+    let options = RemoteOptions::default();
+
+    fetch(&agent(&options), url, &options, None, &read_to_end)
+        .body
+        .with_context(|| format!("Failed to fetch {}", url))
+}
+
+/// Fetches every URL, several at a time, and returns the outcomes in the order of `urls`. Each
+/// body is handed to `read` (with the URL) as it arrives, so it is not kept unless `read` keeps
+/// it. A URL that fails does not stop the others.
+pub fn fetch_all<T: Send>(
+    urls: &[String],
+    options: &RemoteOptions,
+    read: impl Fn(&str, &mut dyn Read) -> anyhow::Result<T> + Sync,
+) -> Vec<Fetched<T>> {
+    let agent = agent(options);
+    let throttle = options.limit_rate.map(Throttle::new);
+
+    let next = AtomicUsize::new(0);
+    let fetched: Mutex<Vec<Option<Fetched<T>>>> = Mutex::new(urls.iter().map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..CONCURRENCY.min(urls.len()) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(url) = urls.get(index) else {
+                    break;
+                };
+
+                let outcome = fetch(&agent, url, options, throttle.as_ref(), &read);
+                fetched.lock().expect("Fetch results poisoned")[index] = Some(outcome);
+            });
+        }
+    });
+
+    fetched
+        .into_inner()
+        .expect("Fetch results poisoned")
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+fn agent(options: &RemoteOptions) -> Agent {
+    Agent::config_builder()
+        .http_status_as_error(false)
+        .timeout_connect(options.timeout)
+        .timeout_recv_response(options.timeout)
+        .timeout_recv_body(options.body_timeout)
+        .build()
+        .into()
+}
+
+/// Fetches `url`, retrying transient failures with exponential backoff. The last attempt's
+/// outcome is returned, with the number of attempts added to its error.
+fn fetch<T>(
+    agent: &Agent,
+    url: &str,
+    options: &RemoteOptions,
+    throttle: Option<&Throttle>,
+    read: &impl Fn(&str, &mut dyn Read) -> anyhow::Result<T>,
+) -> Fetched<T> {
+    let mut attempt = 0;
+
+    loop {
+        let fetched = fetch_once(agent, url, throttle, read);
+
+        let transient = match (&fetched.body, fetched.status) {
+            (Ok(_), _) => false,
+            (Err(_), None) => true,
+            (Err(_), Some(status)) => status == 429 || status >= 500,
+        };
+        if !transient || attempt >= options.retries {
+            return match attempt {
+                0 => fetched,
+                _ => Fetched {
+                    body: fetched
+                        .body
+                        .with_context(|| format!("gave up after {} attempts", attempt + 1)),
+                    ..fetched
+                },
+            };
+        }
+
+        thread::sleep(backoff(attempt, jitter()));
+        attempt += 1;
+    }
+}
+
+/// Returns the delay before retry number `attempt + 1`: [`BACKOFF`] doubled `attempt` times,
+/// plus up to half of that again depending on `jitter` (0 to 1), so that many failing requests do
+/// not all retry at once.
+fn backoff(attempt: u32, jitter: f64) -> Duration {
+    let delay = BACKOFF * 2u32.saturating_pow(attempt);
+    delay + delay.mul_f64(jitter.clamp(0.0, 1.0) / 2.0)
+}
+
+/// Returns a random number between 0 and 1.
+fn jitter() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn fetch_once<T>(
+    agent: &Agent,
+    url: &str,
+    throttle: Option<&Throttle>,
+    read: &impl Fn(&str, &mut dyn Read) -> anyhow::Result<T>,
+) -> Fetched<T> {
+    let mut fetched = Fetched {
+        url: url.to_string(),
+        status: None,
+        content_type: None,
+        body: Err(anyhow::anyhow!("not fetched")),
+    };
+
+    let mut response = match agent.get(url).header("User-Agent", USER_AGENT).call() {
+        Ok(response) => response,
+        Err(err) => {
+            fetched.body = Err(err.into());
+            return fetched;
+        }
+    };
+
+    let status = response.status();
+    fetched.status = Some(status.as_u16());
+    fetched.content_type = response
+        .headers()
+        .get("Content-Type")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    if !status.is_success() {
+        fetched.body = Err(anyhow::anyhow!("HTTP {}", status));
+        return fetched;
+    }
+
+    let mut reader = response.body_mut().with_config().limit(MAX_BODY).reader();
+    fetched.body = match throttle {
+        Some(throttle) => read(url, &mut throttle.reader(reader)),
+        None => read(url, &mut reader),
+    };
+
+    fetched
+}
+
+fn read_to_end(_url: &str, reader: &mut dyn Read) -> anyhow::Result<Vec<u8>> {
+    let mut body: Vec<u8> = Vec::new();
+    reader.read_to_end(&mut body)?;
+    Ok(body)
+}
+
+#[test]
+fn test_backoff_doubles_with_jitter() {
+    assert_eq!(backoff(0, 0.0), Duration::from_millis(500));
+    assert_eq!(backoff(2, 0.0), Duration::from_millis(2000));
+    assert_eq!(backoff(2, 1.0), Duration::from_millis(3000));
+    assert!((0.0..1.0).contains(&jitter()));
+}
+
+#[test]
+fn test_stalled_bodies_time_out() {
+    use std::io::Write;
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to listen");
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("Failed to accept");
+        let _ = stream.read(&mut [0; 1024]);
+        let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nabc");
+        // Keeps the connection open without sending the rest of the body.
+        thread::sleep(Duration::from_secs(5));
+    });
+
+    let options = RemoteOptions {
+        body_timeout: Some(Duration::from_millis(200)),
+        retries: 0,
+        ..Default::default()
+    };
+    let started = std::time::Instant::now();
+    let fetched = fetch_all(&[url], &options, read_to_end);
+
+    assert_eq!(fetched[0].status, Some(200));
+    assert!(fetched[0].body.is_err());
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
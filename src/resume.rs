@@ -0,0 +1,158 @@
+use crate::analyze::FileResult;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{metadata, read_to_string, remove_file, File, Metadata, OpenOptions};
+use std::io::Write;
+use std::path::{absolute, Path, PathBuf};
+use std::time::SystemTime;
+
+/// The first line of a resume file: how its files were counted.
+#[derive(Serialize, Deserialize)]
+struct Header {
+    settings: String,
+}
+
+/// Every other line of a resume file: a file the run counted, and its size and modification
+/// time when it was counted.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    path: PathBuf,
+    size: u64,
+    modified: Option<SystemTime>,
+    result: FileResult,
+}
+
+impl Entry {
+    /// Whether the file still looks the way it did when it was counted.
+    fn unchanged(&self, metadata: &Metadata) -> bool {
+        self.size == metadata.len() && self.modified == metadata.modified().ok()
+    }
+}
+
+/// The files a `--resume` run has counted so far, so a run that was interrupted can pick up
+/// where it stopped. The resume file holds one JSON line per counted file, appended as soon as
+/// the file is counted; a line cut short by a crash is ignored and its file counted again.
+pub struct Resume {
+    path: PathBuf,
+    counted: HashMap<PathBuf, Entry>,
+    writer: File,
+}
+
+impl Resume {
+    /// Opens the resume file at `path`, creating it if there is none. `settings` describes how
+    /// files are counted: results counted with other settings cannot be reused.
+    pub fn open(path: &Path, settings: &str) -> anyhow::Result<Resume> {
+        let contents = match read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to read {}", path.display()))
+            }
+        };
+        let mut writer = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+
+        let mut lines = contents.lines();
+        match lines.next() {
+            None => writeln!(
+                writer,
+                "{}",
+                serde_json::to_string(&Header {
+                    settings: settings.to_string()
+                })?
+            )?,
+            Some(header) => {
+                let header: Header = serde_json::from_str(header)
+                    .with_context(|| format!("{} is not a wcx resume file", path.display()))?;
+                if header.settings != settings {
+                    anyhow::bail!(
+                        "{} was written by a run counting with other options; delete it or pass \
+                         another --resume file",
+                        path.display()
+                    );
+                }
+            }
+        }
+
+        let counted = lines
+            .filter_map(|line| serde_json::from_str::<Entry>(line).ok())
+            .map(|entry: Entry| (entry.path.clone(), entry))
+            .collect();
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            writeln!(writer)?;
+        }
+
+        Ok(Resume {
+            path: path.to_path_buf(),
+            counted,
+            writer,
+        })
+    }
+
+    /// Returns the counts of `file` if an earlier run already counted it, and it has kept its
+    /// size and modification time since.
+    pub fn get(&self, file: &Path) -> Option<&FileResult> {
+        let entry = self.counted.get(&absolute(file).ok()?)?;
+        let metadata = metadata(file).ok()?;
+        entry.unchanged(&metadata).then_some(&entry.result)
+    }
+
+    /// Records the counts of `file` on disk, with its `metadata` from before it was counted.
+    /// Each entry is written whole in a single append, so files counted at the same time can be
+    /// recorded from several threads.
+    pub fn record(
+        &self,
+        file: &Path,
+        metadata: &Metadata,
+        result: &FileResult,
+    ) -> anyhow::Result<()> {
+        let entry = Entry {
+            path: absolute(file)?,
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            result: result.clone(),
+        };
+        let line = format!("{}\n", serde_json::to_string(&entry)?);
+        (&self.writer)
+            .write_all(line.as_bytes())
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    /// Deletes the resume file once the run has gone through every file.
+    pub fn finish(self) -> anyhow::Result<()> {
+        remove_file(&self.path).with_context(|| format!("Failed to delete {}", self.path.display()))
+    }
+}
+
+#[test]
+fn test_resume_reuses_counted_files() {
+    let path = std::env::temp_dir().join(format!("wcx-resume-{}.jsonl", std::process::id()));
+    let notes = std::env::temp_dir().join(format!("wcx-resume-{}.md", std::process::id()));
+    std::fs::write(&notes, "some notes\n").unwrap();
+    let file_result = FileResult {
+        words: 42,
+        ..Default::default()
+    };
+
+    let resume = Resume::open(&path, "words").expect("Failed to open");
+    resume
+        .record(&notes, &metadata(&notes).unwrap(), &file_result)
+        .expect("Failed to record");
+    drop(resume);
+
+    let resume = Resume::open(&path, "words").expect("Failed to reopen");
+    assert_eq!(resume.get(&notes).map(|r| r.words), Some(42));
+    assert!(resume.get(Path::new("other.md")).is_none());
+    assert!(Resume::open(&path, "lines").is_err());
+
+    std::fs::write(&notes, "some longer notes\n").unwrap();
+    assert!(resume.get(&notes).is_none());
+
+    std::fs::remove_file(&notes).unwrap();
+    resume.finish().expect("Failed to finish");
+    assert!(!path.exists());
+}
@@ -0,0 +1,243 @@
+use anyhow::Context;
+use scraper::{ElementRef, Html, Node};
+use serde_json::Value;
+use serde_json_path::JsonPath;
+
+/// Elements whose content is never shown as text.
+const HIDDEN_ELEMENTS: [&str; 5] = ["head", "script", "style", "noscript", "template"];
+
+/// Elements that start on a new line, so the words on either side of them do not run together.
+const BLOCK_ELEMENTS: [&str; 26] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "td",
+];
+
+/// Picks the parts of a structured file whose words and characters are counted, for the
+/// `--mode` options that look inside a format. Line and byte counts still cover the whole file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Selector {
+    /// The data cells of a CSV file, or only those in the named columns when any are given. The
+    /// header row is never counted.
+    CsvColumns(Vec<String>),
+    /// The strings a JSONPath query selects in a JSON file, including the strings nested inside
+    /// selected arrays and objects. Keys, numbers and booleans are not counted.
+    Json(JsonPath),
+    /// The visible text of an HTML file, or only that of the elements a CSS selector matches
+    /// when one is given. Scripts and styles are not counted.
+    Html(Option<scraper::Selector>),
+}
+
+impl Selector {
+    /// Returns the selected text fragments of `content`. They are counted separately, so words
+    /// never run together across cells.
+    pub fn select(&self, name: &str, content: &[u8]) -> anyhow::Result<Vec<String>> {
+        match self {
+            Selector::CsvColumns(columns) => csv_cells(content, columns)
+                .with_context(|| format!("Failed to read {} as CSV", name)),
+            Selector::Json(path) => {
+                let document: Value = serde_json::from_slice(content)
+                    .with_context(|| format!("Failed to read {} as JSON", name))?;
+                let mut strings: Vec<String> = Vec::new();
+                for value in path.query(&document).all() {
+                    collect_strings(value, &mut strings);
+                }
+                Ok(strings)
+            }
+            Selector::Html(selector) => {
+                let document = std::str::from_utf8(content).with_context(|| {
+                    format!("Failed to read {}. Note: HTML must be valid UTF-8.", name)
+                })?;
+                Ok(html_text(
+                    &Html::parse_document(document),
+                    selector.as_ref(),
+                ))
+            }
+        }
+    }
+}
+
+fn csv_cells(content: &[u8], columns: &[String]) -> anyhow::Result<Vec<String>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(content);
+
+    let headers = reader.headers()?.clone();
+    let indices: Vec<usize> = columns
+        .iter()
+        .map(|column| {
+            headers
+                .iter()
+                .position(|header| header.trim() == column)
+                .with_context(|| {
+                    let found: Vec<&str> = headers.iter().collect();
+                    format!("no column `{}` (columns: {})", column, found.join(", "))
+                })
+        })
+        .collect::<anyhow::Result<_>>()?;
+
+    let mut cells: Vec<String> = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        match indices.is_empty() {
+            true => cells.extend(record.iter().map(str::to_string)),
+            false => cells.extend(
+                indices
+                    .iter()
+                    .filter_map(|index| record.get(*index))
+                    .map(str::to_string),
+            ),
+        }
+    }
+
+    Ok(cells)
+}
+
+/// Returns the visible text of every element `selector` matches (or of the whole document), one
+/// fragment per element. Elements inside another match are not counted twice.
+fn html_text(document: &Html, selector: Option<&scraper::Selector>) -> Vec<String> {
+    let Some(selector) = selector else {
+        let mut text = String::new();
+        visible_text(document.root_element(), &mut text);
+        return vec![text];
+    };
+
+    let matches: Vec<ElementRef> = document.select(selector).collect();
+    matches
+        .iter()
+        .filter(|element| {
+            !element
+                .ancestors()
+                .any(|ancestor| matches.iter().any(|other| other.id() == ancestor.id()))
+        })
+        .map(|element| {
+            let mut text = String::new();
+            visible_text(*element, &mut text);
+            text
+        })
+        .collect()
+}
+
+fn visible_text(element: ElementRef, text: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(fragment) => text.push_str(fragment),
+            Node::Element(child_element) => {
+                let name = child_element.name();
+                if HIDDEN_ELEMENTS.contains(&name) {
+                    continue;
+                }
+
+                let block = BLOCK_ELEMENTS.contains(&name);
+                if block {
+                    text.push('\n');
+                }
+                if let Some(child) = ElementRef::wrap(child) {
+                    visible_text(child, text);
+                }
+                if block {
+                    text.push('\n');
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_strings(value: &Value, strings: &mut Vec<String>) {
+    match value {
+        Value::String(string) => strings.push(string.clone()),
+        Value::Array(values) => values
+            .iter()
+            .for_each(|value| collect_strings(value, strings)),
+        Value::Object(map) => map
+            .values()
+            .for_each(|value| collect_strings(value, strings)),
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+}
+
+#[test]
+fn test_csv_columns_select_named_cells() {
+    let content = b"id,title,body\n1,Hello world,\"Long, quoted\ntext\"\n2,Bye,\n";
+
+    let selected = Selector::CsvColumns(vec![String::from("title")])
+        .select("posts.csv", content)
+        .expect("Failed to select");
+    assert_eq!(selected, vec!["Hello world", "Bye"]);
+
+    let all = Selector::CsvColumns(Vec::new())
+        .select("posts.csv", content)
+        .expect("Failed to select");
+    assert_eq!(all.len(), 6);
+
+    let error = Selector::CsvColumns(vec![String::from("summary")])
+        .select("posts.csv", content)
+        .expect_err("Missing column should fail");
+    assert!(format!("{:#}", error).contains("no column `summary` (columns: id, title, body)"));
+}
+
+#[test]
+fn test_json_path_selects_strings() {
+    let content = br#"{"items": [
+        {"id": 1, "description": "A small red ball"},
+        {"id": 2, "description": "Blue", "tags": ["toy", 3]}
+    ]}"#;
+
+    let path = JsonPath::parse("$.items[*].description").expect("Failed to parse path");
+    let selected = Selector::Json(path)
+        .select("fixture.json", content)
+        .expect("Failed to select");
+    assert_eq!(selected, vec!["A small red ball", "Blue"]);
+
+    let path = JsonPath::parse("$.items[1]").expect("Failed to parse path");
+    let selected = Selector::Json(path)
+        .select("fixture.json", content)
+        .expect("Failed to select");
+    assert_eq!(selected.len(), 2);
+}
+
+#[test]
+fn test_html_selects_visible_text_of_matches() {
+    let content = br#"<html><head><title>Site</title></head><body>
+        <nav>Home About</nav>
+        <article><p>First <b>para</b>graph.</p><p>Second one</p><script>var x = 1;</script></article>
+    </body></html>"#;
+
+    let selector = scraper::Selector::parse("article p").expect("Failed to parse selector");
+    let selected = Selector::Html(Some(selector))
+        .select("post.html", content)
+        .expect("Failed to select");
+    assert_eq!(selected, vec!["First paragraph.", "Second one"]);
+
+    let selected = Selector::Html(None)
+        .select("post.html", content)
+        .expect("Failed to select");
+    assert_eq!(
+        selected[0].split_whitespace().collect::<Vec<_>>(),
+        vec!["Home", "About", "First", "paragraph.", "Second", "one"]
+    );
+}
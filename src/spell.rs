@@ -0,0 +1,154 @@
+use anyhow::Context;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Where Hunspell dictionaries are installed, after the directories in `$DICPATH`.
+const DICTIONARY_DIRECTORIES: [&str; 4] = [
+    "/usr/share/hunspell",
+    "/usr/share/myspell",
+    "/usr/share/myspell/dicts",
+    "/Library/Spelling",
+];
+
+/// Endings stripped from a word missing from the dictionary, with what replaces them, so the
+/// plain word list of a Hunspell dictionary recognizes common English inflections without its
+/// affix rules. A doubled last consonant is undone too, as in `running`.
+const ENDINGS: [(&str, &str); 13] = [
+    ("'s", ""),
+    ("s", ""),
+    ("es", ""),
+    ("ies", "y"),
+    ("ed", ""),
+    ("ed", "e"),
+    ("ied", "y"),
+    ("ing", ""),
+    ("ing", "e"),
+    ("ly", ""),
+    ("ily", "y"),
+    ("er", ""),
+    ("est", ""),
+];
+
+/// The words of a Hunspell dictionary, for `--spell`: a rough spell checker counting the words
+/// of prose it does not know.
+pub struct Dictionary {
+    /// The language or `.dic` file the dictionary was loaded for.
+    name: String,
+    /// Every word, lowercased.
+    words: HashSet<String>,
+}
+
+impl fmt::Debug for Dictionary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Dictionary({})", self.name)
+    }
+}
+
+impl Dictionary {
+    /// Loads the dictionary of `language`, e.g. `en_US`, from `$DICPATH` or the directories
+    /// Hunspell dictionaries are installed in, or from a `.dic` file when given its path.
+    pub fn load(language: &str) -> anyhow::Result<Dictionary> {
+        let path = find(language).with_context(|| {
+            format!(
+                "No dictionary for `{}`: install its Hunspell dictionary or pass the path of a \
+                 .dic file",
+                language
+            )
+        })?;
+        let contents = fs::read(&path)
+            .with_context(|| format!("Failed to read dictionary {}", path.display()))?;
+
+        Ok(Dictionary::parse(
+            language,
+            &String::from_utf8_lossy(&contents),
+        ))
+    }
+
+    /// Reads the words of a `.dic` file: its first line is the number of words, and each word
+    /// may be followed by `/` and its affix flags, and by morphological fields.
+    fn parse(name: &str, contents: &str) -> Dictionary {
+        let words = contents
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split(['/', '\t', ' ']).next())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+
+        Dictionary {
+            name: name.to_string(),
+            words,
+        }
+    }
+
+    /// Counts the words of `text` missing from the dictionary. Only prose is checked: tokens
+    /// that look like code, paths, URLs or addresses are skipped, and so are words with digits,
+    /// acronyms and camelCase names.
+    pub fn misspelled(&self, text: &str) -> usize {
+        text.split_whitespace()
+            .filter(|token| !token.contains(['/', '\\', '@', '_', '=', '<', '>', '{', '}', '`']))
+            .flat_map(|token| token.unicode_words())
+            .filter(|word| {
+                !word.chars().any(|char| char.is_numeric())
+                    && !word.chars().skip(1).any(char::is_uppercase)
+            })
+            .filter(|word| !self.knows(word))
+            .count()
+    }
+
+    fn knows(&self, word: &str) -> bool {
+        let word = word.to_lowercase().replace('’', "'");
+        self.words.contains(&word)
+            || ENDINGS.iter().any(|(ending, replacement)| {
+                let Some(stem) = word.strip_suffix(ending).filter(|stem| stem.len() > 1) else {
+                    return false;
+                };
+                let mut chars = stem.chars().rev();
+                let undoubled = match (chars.next(), chars.next()) {
+                    (Some(last), Some(before)) if last == before => &stem[..stem.len() - 1],
+                    _ => stem,
+                };
+                [stem, undoubled]
+                    .iter()
+                    .any(|stem| self.words.contains(&format!("{}{}", stem, replacement)))
+            })
+    }
+}
+
+fn find(language: &str) -> Option<PathBuf> {
+    let path = Path::new(language);
+    if path.extension().is_some_and(|extension| extension == "dic") {
+        return Some(path.to_path_buf());
+    }
+
+    let mut directories: Vec<PathBuf> = std::env::var_os("DICPATH")
+        .map(|paths| std::env::split_paths(&paths).collect())
+        .unwrap_or_default();
+    directories.extend(DICTIONARY_DIRECTORIES.map(PathBuf::from));
+
+    directories
+        .into_iter()
+        .map(|directory| directory.join(format!("{}.dic", language)))
+        .find(|path| path.is_file())
+}
+
+#[test]
+fn test_misspelled_counts_unknown_prose_words() {
+    let dictionary = Dictionary::parse(
+        "test",
+        "10\nthe/S\nto\nrun/SG\nquick\nbrown\nfox/M\nit's\nParis\nhappy/UY\n",
+    );
+
+    // `brwon`, `is` and `runnning`.
+    assert_eq!(
+        dictionary.misspelled("The quick brwon fox is running to Paris, it’s happily runnning."),
+        3
+    );
+    assert_eq!(
+        dictionary.misspelled("See src/main.rs, `parseArgs`, HTTP 404 to me@example.com"),
+        1
+    );
+}
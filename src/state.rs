@@ -0,0 +1,32 @@
+use anyhow::Context;
+use std::env;
+use std::fs::create_dir_all;
+use std::path::PathBuf;
+
+/// Returns the directory wcx keeps its local state in (cache, goals, history), creating it if
+/// it does not exist yet.
+///
+/// The first of these that is set wins:
+///
+/// * `WCX_STATE_DIR`
+/// * `$XDG_STATE_HOME/wcx`
+/// * `$HOME/.local/state/wcx`
+/// * `%LOCALAPPDATA%\wcx`
+pub fn state_dir() -> anyhow::Result<PathBuf> {
+    let dir = if let Some(dir) = env::var_os("WCX_STATE_DIR") {
+        PathBuf::from(dir)
+    } else if let Some(dir) = env::var_os("XDG_STATE_HOME") {
+        PathBuf::from(dir).join("wcx")
+    } else if let Some(home) = env::var_os("HOME") {
+        PathBuf::from(home).join(".local").join("state").join("wcx")
+    } else if let Some(dir) = env::var_os("LOCALAPPDATA") {
+        PathBuf::from(dir).join("wcx")
+    } else {
+        anyhow::bail!("Could not locate a state directory. Set WCX_STATE_DIR to choose one.");
+    };
+
+    create_dir_all(&dir)
+        .with_context(|| format!("Failed to create state directory {}", dir.display()))?;
+
+    Ok(dir)
+}
@@ -0,0 +1,206 @@
+use crate::cli::Mode;
+use crate::config;
+use crate::idl;
+use crate::mail;
+use crate::patch;
+use crate::subtitle;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A structural measurement taken by one of the format `--mode`s, shown in a column of its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stat {
+    /// Keys of a config file, nested ones included.
+    Keys,
+    /// How deeply the values of a config file are nested.
+    Depth,
+    /// Lines holding only a comment.
+    CommentLines,
+    /// Subtitle cues.
+    Cues,
+    /// Words shown in subtitle cues, without numbers, timings and formatting tags.
+    SubtitleWords,
+    /// Milliseconds subtitles are on screen.
+    Duration,
+    /// Types declared by a schema, such as protobuf messages, enums and services.
+    Types,
+    /// Fields of the types of a schema, enum values and service methods included.
+    Fields,
+    /// Files a patch touches.
+    FilesTouched,
+    /// Lines a patch adds.
+    LinesAdded,
+    /// Lines a patch removes.
+    LinesRemoved,
+    /// Hunks of a patch.
+    Hunks,
+    /// Messages in a mailbox.
+    Messages,
+    /// Header lines of the messages in a mailbox, folded continuation lines included.
+    HeaderLines,
+    /// Words in the bodies of the messages in a mailbox.
+    BodyWords,
+}
+
+impl Stat {
+    /// Every measurement, in declaration order.
+    pub const ALL: [Stat; 15] = [
+        Stat::Keys,
+        Stat::Depth,
+        Stat::CommentLines,
+        Stat::Cues,
+        Stat::SubtitleWords,
+        Stat::Duration,
+        Stat::Types,
+        Stat::Fields,
+        Stat::FilesTouched,
+        Stat::LinesAdded,
+        Stat::LinesRemoved,
+        Stat::Hunks,
+        Stat::Messages,
+        Stat::HeaderLines,
+        Stat::BodyWords,
+    ];
+}
+
+/// The measurements of one file, by [`Stat`].
+pub type Stats = BTreeMap<Stat, usize>;
+
+impl Stat {
+    /// Returns the title written into the header row.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Stat::Keys => "Keys",
+            Stat::Depth => "Depth",
+            Stat::CommentLines => "Comments",
+            Stat::Cues => "Cues",
+            Stat::SubtitleWords => "Cue words",
+            Stat::Duration => "Duration",
+            Stat::Types => "Types",
+            Stat::Fields => "Fields",
+            Stat::FilesTouched => "Files",
+            Stat::LinesAdded => "Added",
+            Stat::LinesRemoved => "Removed",
+            Stat::Hunks => "Hunks",
+            Stat::Messages => "Messages",
+            Stat::HeaderLines => "Header lines",
+            Stat::BodyWords => "Body words",
+        }
+    }
+
+    /// Returns the field name in machine-readable output.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Stat::Keys => "keys",
+            Stat::Depth => "depth",
+            Stat::CommentLines => "comment_lines",
+            Stat::Cues => "cues",
+            Stat::SubtitleWords => "cue_words",
+            Stat::Duration => "duration_ms",
+            Stat::Types => "types",
+            Stat::Fields => "fields",
+            Stat::FilesTouched => "files_touched",
+            Stat::LinesAdded => "lines_added",
+            Stat::LinesRemoved => "lines_removed",
+            Stat::Hunks => "hunks",
+            Stat::Messages => "messages",
+            Stat::HeaderLines => "header_lines",
+            Stat::BodyWords => "body_words",
+        }
+    }
+
+    /// Formats a measurement for the table; durations read as `1:02:03`.
+    pub fn format(&self, value: usize) -> String {
+        match self {
+            Stat::Duration => {
+                let seconds = value / 1000;
+                format!(
+                    "{}:{:02}:{:02}",
+                    seconds / 3600,
+                    seconds / 60 % 60,
+                    seconds % 60
+                )
+            }
+            _ => value.to_string(),
+        }
+    }
+
+    /// Combines the measurements of two files: depths take the deepest, the rest add up.
+    fn combine(&self, a: usize, b: usize) -> usize {
+        match self {
+            Stat::Depth => a.max(b),
+            _ => a + b,
+        }
+    }
+}
+
+/// Adds the measurements of `other` into `stats`.
+pub fn add(stats: &mut Stats, other: &Stats) {
+    for (stat, value) in other {
+        let combined = match stats.get(stat) {
+            Some(current) => stat.combine(*current, *value),
+            None => *value,
+        };
+        stats.insert(*stat, combined);
+    }
+}
+
+/// Returns the measurements `mode` adds as columns, in column order.
+pub fn columns(mode: Mode) -> &'static [Stat] {
+    match mode {
+        Mode::Config => &[Stat::Keys, Stat::Depth, Stat::CommentLines],
+        Mode::Subtitle => &[Stat::Cues, Stat::SubtitleWords, Stat::Duration],
+        Mode::Idl => &[Stat::Types, Stat::Fields, Stat::CommentLines],
+        Mode::Patch => &[
+            Stat::FilesTouched,
+            Stat::LinesAdded,
+            Stat::LinesRemoved,
+            Stat::Hunks,
+        ],
+        Mode::Mail => &[Stat::Messages, Stat::HeaderLines, Stat::BodyWords],
+        Mode::Text | Mode::Csv | Mode::Json | Mode::Html => &[],
+    }
+}
+
+/// Settings for the measurements of the modes that have any.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MeasureOptions {
+    /// Leaves quoted lines of replies out of the body words of `--mode mail`.
+    pub exclude_quoted: bool,
+}
+
+/// Takes the measurements of `mode` on `content`. `name` tells the format apart where a mode
+/// reads several (such as YAML and TOML).
+pub fn measure(
+    mode: Mode,
+    name: &str,
+    content: &[u8],
+    options: &MeasureOptions,
+) -> anyhow::Result<Stats> {
+    match mode {
+        Mode::Config => config::measure(name, content),
+        Mode::Subtitle => subtitle::measure(name, content),
+        Mode::Idl => idl::measure(name, content),
+        Mode::Patch => patch::measure(name, content),
+        Mode::Mail => Ok(mail::measure(content, options.exclude_quoted)),
+        Mode::Text | Mode::Csv | Mode::Json | Mode::Html => Ok(Stats::new()),
+    }
+}
+
+#[test]
+fn test_add_takes_deepest_depth_and_sums_the_rest() {
+    let mut stats = Stats::from([(Stat::Keys, 4), (Stat::Depth, 3)]);
+    add(
+        &mut stats,
+        &Stats::from([(Stat::Keys, 6), (Stat::Depth, 2)]),
+    );
+
+    assert_eq!(stats, Stats::from([(Stat::Keys, 10), (Stat::Depth, 3)]));
+}
+
+#[test]
+fn test_format_duration() {
+    assert_eq!(Stat::Duration.format(3_723_500), "1:02:03");
+    assert_eq!(Stat::Cues.format(12), "12");
+}
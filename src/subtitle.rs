@@ -0,0 +1,108 @@
+use crate::stats::{Stat, Stats};
+use anyhow::Context;
+
+/// Counts the cues of an SRT or WebVTT file, the words shown in them, and how long they are on
+/// screen in total, in milliseconds. Formatting tags like `<i>` are not counted as words.
+pub fn measure(name: &str, content: &[u8]) -> anyhow::Result<Stats> {
+    let text = std::str::from_utf8(content)
+        .with_context(|| format!("Failed to read {}. Note: subtitles must be UTF-8.", name))?;
+
+    let mut cues = 0;
+    let mut words = 0;
+    let mut duration = 0;
+    let mut in_cue = false;
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() {
+            in_cue = false;
+        } else if let Some((start, end)) = line.split_once("-->") {
+            let start = timestamp(start.trim());
+            // WebVTT cue settings (`line:0 align:start`) follow the end time.
+            let end = timestamp(end.split_whitespace().next().unwrap_or_default());
+            let (Some(start), Some(end)) = (start, end) else {
+                anyhow::bail!("{}:{}: invalid cue timing `{}`", name, number + 1, line);
+            };
+
+            cues += 1;
+            duration = u64::saturating_add(duration, end.saturating_sub(start));
+            in_cue = true;
+        } else if in_cue {
+            words += wcx_core::count_words(&strip_tags(line));
+        }
+    }
+
+    Ok(Stats::from([
+        (Stat::Cues, cues),
+        (Stat::SubtitleWords, words),
+        (Stat::Duration, duration as usize),
+    ]))
+}
+
+/// Parses `01:02:03,500` (SRT) or `01:02:03.500` / `02:03.500` (WebVTT) into milliseconds.
+fn timestamp(timestamp: &str) -> Option<u64> {
+    let (clock, millis) = timestamp.split_once([',', '.'])?;
+    let millis: u64 = millis.parse().ok()?;
+
+    let mut seconds: u64 = 0;
+    for part in clock.split(':') {
+        seconds = seconds.checked_mul(60)?.checked_add(part.parse().ok()?)?;
+    }
+
+    seconds.checked_mul(1000)?.checked_add(millis)
+}
+
+/// Removes `<i>`-style tags and `{\an8}`-style overrides from a line of cue text.
+fn strip_tags(line: &str) -> String {
+    let mut text = String::with_capacity(line.len());
+    let mut closing: Option<char> = None;
+
+    for c in line.chars() {
+        match (closing, c) {
+            (None, '<') => closing = Some('>'),
+            (None, '{') => closing = Some('}'),
+            (None, c) => text.push(c),
+            (Some(end), c) if c == end => closing = None,
+            (Some(_), _) => {}
+        }
+    }
+
+    text
+}
+
+#[test]
+fn test_measure_srt() {
+    let srt = "1\n00:00:01,000 --> 00:00:03,500\nHello <i>there</i>, friend.\n\n2\n00:00:04,000 --> 00:00:05,000\n{\\an8}Bye\n";
+
+    let stats = measure("movie.srt", srt.as_bytes()).expect("Failed to measure");
+
+    assert_eq!(stats[&Stat::Cues], 2);
+    assert_eq!(stats[&Stat::SubtitleWords], 4);
+    assert_eq!(stats[&Stat::Duration], 3500);
+}
+
+#[test]
+fn test_measure_vtt() {
+    let vtt = "WEBVTT\n\nNOTE written by hand\n\n00:01.000 --> 00:02.250 align:start\n- Who?\n- Me.\n\n01:00:00.000 --> 01:00:01.000\nLate line\n";
+
+    let stats = measure("talk.vtt", vtt.as_bytes()).expect("Failed to measure");
+
+    assert_eq!(stats[&Stat::Cues], 2);
+    assert_eq!(stats[&Stat::SubtitleWords], 6);
+    assert_eq!(stats[&Stat::Duration], 2250);
+}
+
+#[test]
+fn test_huge_cue_timings_are_invalid() {
+    let srt = "1
+99999999999999999:00:00,000 --> 99999999999999999:00:01,000
+Too late
+";
+
+    let error = measure("movie.srt", srt.as_bytes()).unwrap_err();
+
+    assert!(error
+        .to_string()
+        .starts_with("movie.srt:2: invalid cue timing"));
+}
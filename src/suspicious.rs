@@ -0,0 +1,29 @@
+/// Whether `char` is invisible yet changes how text reads: a zero-width character, which can
+/// split or join identifiers unseen, or a bidirectional control, which can reorder the source
+/// a reviewer sees (the "Trojan Source" attack).
+pub fn is_suspicious(char: char) -> bool {
+    matches!(
+        char,
+        // Zero-width space, non-joiner and joiner, word joiner, and a BOM past the start.
+        '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}'
+        // Arabic letter mark, left-to-right and right-to-left marks.
+        | '\u{61c}' | '\u{200e}' | '\u{200f}'
+        // Embeddings, overrides and isolates.
+        | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+/// Counts the suspicious characters of `text`, for `--suspicious-chars`.
+pub fn count(text: &str) -> usize {
+    text.chars().filter(|&char| is_suspicious(char)).count()
+}
+
+#[test]
+fn test_count_finds_invisible_and_bidi_characters() {
+    // The comment of the Trojan Source paper's early-return example.
+    let source = "if access_level != \"user\u{202e} \u{2066}// Check if admin\u{2069} \u{2066}\" {";
+
+    assert_eq!(count(source), 4);
+    assert_eq!(count("pass\u{200b}word\u{200d}"), 2);
+    assert_eq!(count("naïve café — ok"), 0);
+}
@@ -0,0 +1,135 @@
+use std::io::{self, Read};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A transfer rate in bytes per second, written like curl's `--limit-rate`: `500K`, `1M` or `2G`
+/// (powers of 1024), or a plain number of bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rate(pub u64);
+
+impl FromStr for Rate {
+    type Err = String;
+
+    fn from_str(rate: &str) -> Result<Rate, String> {
+        let rate = rate.trim();
+        let (number, unit) = match rate.char_indices().find(|(_, c)| c.is_ascii_alphabetic()) {
+            Some((index, _)) => rate.split_at(index),
+            None => (rate, ""),
+        };
+
+        let multiplier: u64 = match unit.to_ascii_uppercase().as_str() {
+            "" | "B" => 1,
+            "K" | "KB" => 1 << 10,
+            "M" | "MB" => 1 << 20,
+            "G" | "GB" => 1 << 30,
+            _ => {
+                return Err(format!(
+                    "unknown unit in rate `{}` (expected K, M or G)",
+                    rate
+                ))
+            }
+        };
+        let number: f64 = number
+            .parse()
+            .map_err(|_| format!("invalid rate `{}`", rate))?;
+
+        match (number * multiplier as f64) as u64 {
+            0 => Err(String::from("the rate must be at least 1 byte per second")),
+            bytes => Ok(Rate(bytes)),
+        }
+    }
+}
+
+/// Keeps the combined speed of every reader sharing it at or below a [`Rate`].
+pub struct Throttle {
+    rate: Rate,
+    started: Instant,
+    transferred: Mutex<u64>,
+}
+
+impl Throttle {
+    pub fn new(rate: Rate) -> Throttle {
+        Throttle {
+            rate,
+            started: Instant::now(),
+            transferred: Mutex::new(0),
+        }
+    }
+
+    /// Wraps `reader` so that reading from it waits whenever the shared transfer is ahead of the
+    /// rate.
+    pub fn reader<R: Read>(&self, reader: R) -> ThrottledReader<'_, R> {
+        ThrottledReader {
+            throttle: self,
+            reader,
+        }
+    }
+
+    /// Counts `bytes` more as transferred, then sleeps for as long as the transfer is ahead.
+    fn consume(&self, bytes: usize) {
+        let transferred = {
+            let mut transferred = self.transferred.lock().expect("Throttle poisoned");
+            *transferred += bytes as u64;
+            *transferred
+        };
+
+        thread::sleep(ahead(transferred, self.rate, self.started.elapsed()));
+    }
+}
+
+/// Returns how much sooner than `rate` allows `transferred` bytes were read, or zero when the
+/// transfer is not ahead.
+fn ahead(transferred: u64, rate: Rate, elapsed: Duration) -> Duration {
+    Duration::from_secs_f64(transferred as f64 / rate.0 as f64).saturating_sub(elapsed)
+}
+
+/// A reader whose reads are slowed down by a shared [`Throttle`].
+pub struct ThrottledReader<'a, R> {
+    throttle: &'a Throttle,
+    reader: R,
+}
+
+impl<R: Read> Read for ThrottledReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        // Small reads keep the transfer smooth instead of bursting and then pausing for long.
+        let chunk = (self.throttle.rate.0 / 10).clamp(1, buf.len() as u64) as usize;
+        let read = self.reader.read(&mut buf[..chunk])?;
+        self.throttle.consume(read);
+        Ok(read)
+    }
+}
+
+#[test]
+fn test_parse_rate() {
+    assert_eq!("1M".parse(), Ok(Rate(1 << 20)));
+    assert_eq!("500k".parse(), Ok(Rate(500 * 1024)));
+    assert_eq!("1.5G".parse(), Ok(Rate(3 << 29)));
+    assert_eq!("2048".parse(), Ok(Rate(2048)));
+    assert!("1T".parse::<Rate>().is_err());
+    assert!("0".parse::<Rate>().is_err());
+}
+
+#[test]
+fn test_ahead_of_rate() {
+    let rate = Rate(1000);
+
+    assert_eq!(
+        ahead(2000, rate, Duration::from_millis(500)),
+        Duration::from_millis(1500)
+    );
+    assert_eq!(ahead(2000, rate, Duration::from_secs(3)), Duration::ZERO);
+}
+
+#[test]
+fn test_empty_reads_do_not_panic() {
+    let throttle = Throttle::new(Rate(1000));
+    let mut reader = throttle.reader(&b"abc"[..]);
+
+    assert_eq!(reader.read(&mut []).expect("Failed to read"), 0);
+}
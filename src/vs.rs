@@ -0,0 +1,23 @@
+use crate::cli::Derived;
+use crate::command::Builder;
+use crate::output;
+use std::path::PathBuf;
+
+/// Compares files side by side: one column per file and one row per metric, with the largest
+/// value of each metric in green and the smallest in red.
+pub fn invoke(files: &[PathBuf]) -> anyhow::Result<()> {
+    let mut table_manager = Builder::new()
+        .enable_flags(false, false, false, false)
+        .derived(&[Derived::AvgWordsPerLine, Derived::AvgWordLen])
+        .pivot(true)
+        .table_format("no_border_line_separator")
+        .build(files.len());
+    table_manager.totals_counter.enabled = false;
+
+    for file in files {
+        table_manager.set_table_row(file)?;
+    }
+
+    output::print_comparison(&table_manager);
+    Ok(())
+}
@@ -0,0 +1,400 @@
+use anyhow::Context;
+use globset::GlobBuilder;
+use ignore::WalkBuilder;
+use std::fs::{self, File};
+use std::io::{read_to_string, stdin, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use walkdir::{DirEntry, WalkDir};
+
+/// Which files a walk below a directory skips.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct WalkOptions {
+    /// Also walks hidden files and directories (names starting with `.`).
+    pub hidden: bool,
+    /// Skips the files matched by `.gitignore` and `.ignore` files, in the directories walked and
+    /// their parents, and by git's global and per-repository excludes.
+    pub respect_gitignore: bool,
+    /// How many directories deep to go below the directory walked: 1 only walks the files
+    /// directly in it. Unlimited when `None`.
+    pub max_depth: Option<usize>,
+    /// Walks the files and directories symbolic links point to, instead of skipping the links.
+    pub follow_symlinks: bool,
+    /// Reports the entries that cannot be read, like a directory without permission, and skips
+    /// them instead of failing the walk.
+    pub skip_unreadable: bool,
+}
+
+/// Collects every regular file below `path` in a stable, name-sorted order. Hidden files and
+/// directories (names starting with `.`) are skipped, except for `path` itself.
+///
+/// If `path` is not a directory it is returned as the only entry.
+pub fn collect_files(path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    walk_files(path, WalkOptions::default())
+}
+
+/// Collects every regular file below `path` like [`collect_files`], skipping the files `options`
+/// says to. `.gitignore` files apply outside git repositories too. A followed symbolic link that
+/// points back to a directory it is in would repeat the walk forever; it is reported and skipped.
+pub fn walk_files(path: &Path, options: WalkOptions) -> anyhow::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files: Vec<PathBuf> = Vec::new();
+
+    let walker = WalkBuilder::new(path)
+        .standard_filters(options.respect_gitignore)
+        .hidden(!options.hidden)
+        .require_git(false)
+        .max_depth(options.max_depth)
+        .follow_links(options.follow_symlinks)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => match symlink_loop(&err) {
+                Some((link, ancestor)) => {
+                    eprintln!(
+                        "wcx: skipping {}: it links back to {}",
+                        link.display(),
+                        ancestor.display()
+                    );
+                    continue;
+                }
+                None if options.skip_unreadable => {
+                    eprintln!("wcx: skipping {}", err);
+                    continue;
+                }
+                None => return Err(err.into()),
+            },
+        };
+        if entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file())
+        {
+            files.push(entry.into_path());
+        }
+    }
+
+    Ok(files)
+}
+
+/// Returns the link and the directory it points back to when `err` reports a symlink loop.
+fn symlink_loop(err: &ignore::Error) -> Option<(&Path, &Path)> {
+    match err {
+        ignore::Error::Loop { child, ancestor } => Some((child, ancestor)),
+        ignore::Error::WithPath { err, .. } | ignore::Error::WithDepth { err, .. } => {
+            symlink_loop(err)
+        }
+        _ => None,
+    }
+}
+
+/// Expands the glob patterns among `files`, like `src/**/*.rs`, into the paths they match in
+/// name order, for shells that pass them on as they are (such as on Windows). Hidden files are
+/// only matched by patterns naming their directory. A path that exists is kept as it is, even
+/// with glob characters in its name. Fails when a pattern matches nothing.
+pub fn expand_globs(files: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut expanded = Vec::new();
+
+    for file in files {
+        match file
+            .to_str()
+            .filter(|pattern| is_glob(pattern) && !file.exists())
+        {
+            Some(pattern) => {
+                let matched = expand_glob(pattern)?;
+                if matched.is_empty() {
+                    anyhow::bail!("No files match `{}`", pattern);
+                }
+                expanded.extend(matched);
+            }
+            None => expanded.push(file.clone()),
+        }
+    }
+
+    Ok(expanded)
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '[', '{'])
+}
+
+fn expand_glob(pattern: &str) -> anyhow::Result<Vec<PathBuf>> {
+    let matcher = GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .with_context(|| format!("Invalid glob `{}`", pattern))?
+        .compile_matcher();
+
+    // Walk from the directory the pattern starts with, before its first glob character, and
+    // no deeper than the pattern reaches.
+    let components: Vec<&str> = pattern.split('/').collect();
+    let literal = components
+        .iter()
+        .take_while(|component| !is_glob(component))
+        .count();
+    let base = components[..literal].join("/");
+    let root = Path::new(if base.is_empty() { "." } else { &base });
+    if !root.is_dir() {
+        return Ok(Vec::new());
+    }
+    let max_depth = match pattern.contains("**") {
+        true => usize::MAX,
+        false => components.len() - literal,
+    };
+
+    let walker = WalkDir::new(root)
+        .max_depth(max_depth)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !is_hidden(entry));
+
+    let mut matched = Vec::new();
+    for entry in walker {
+        let entry = entry?;
+        let path = match base.is_empty() {
+            true => entry.path().strip_prefix(".").unwrap_or(entry.path()),
+            false => entry.path(),
+        };
+        if entry.depth() > 0 && matcher.is_match(path) {
+            matched.push(path.to_path_buf());
+        }
+    }
+
+    Ok(matched)
+}
+
+/// Reads a list of files with one path per line from `list`, or from stdin when `list` is `-`.
+/// Blank lines and lines starting with `#` are skipped.
+pub fn read_file_list(list: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    Ok(parse_file_list(&read_list(list, "file list")?))
+}
+
+/// Reads a list of files separated by NUL bytes from `list`, or from stdin when `list` is `-`,
+/// like `find -print0` writes it. Unlike [`read_file_list`] every byte is part of a path, so
+/// names with newlines or leading `#` come through intact.
+pub fn read_file0_list(list: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let files = match list == Path::new("-") {
+        true => parse_file0_list(stdin().lock()),
+        false => {
+            let file = File::open(list)
+                .with_context(|| format!("Failed to read file list {}", list.display()))?;
+            parse_file0_list(BufReader::new(file))
+        }
+    };
+    files.with_context(|| match list == Path::new("-") {
+        true => "Failed to read the file list from stdin".to_string(),
+        false => format!("Failed to read file list {}", list.display()),
+    })
+}
+
+fn parse_file0_list(reader: impl BufRead) -> anyhow::Result<Vec<PathBuf>> {
+    reader
+        .split(b'\0')
+        .enumerate()
+        .map(|(index, name)| {
+            let name = name?;
+            if name.is_empty() {
+                anyhow::bail!("File name {} is empty", index + 1);
+            }
+            path_from_bytes(name)
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> anyhow::Result<PathBuf> {
+    use std::os::unix::ffi::OsStringExt;
+
+    Ok(PathBuf::from(std::ffi::OsString::from_vec(bytes)))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: Vec<u8>) -> anyhow::Result<PathBuf> {
+    let name = String::from_utf8(bytes).context("File name is not valid UTF-8")?;
+    Ok(PathBuf::from(name))
+}
+
+/// Reads a list of URLs with one URL per line, the same way as [`read_file_list`].
+#[cfg(feature = "remote")]
+pub fn read_url_list(list: &Path) -> anyhow::Result<Vec<String>> {
+    Ok(list_lines(&read_list(list, "URL list")?)
+        .map(|line| line.trim().to_string())
+        .collect())
+}
+
+fn read_list(list: &Path, description: &str) -> anyhow::Result<String> {
+    if list == Path::new("-") {
+        read_to_string(stdin())
+            .with_context(|| format!("Failed to read the {} from stdin", description))
+    } else {
+        fs::read_to_string(list)
+            .with_context(|| format!("Failed to read {} {}", description, list.display()))
+    }
+}
+
+fn parse_file_list(contents: &str) -> Vec<PathBuf> {
+    list_lines(contents).map(PathBuf::from).collect()
+}
+
+fn list_lines(contents: &str) -> impl Iterator<Item = &str> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+}
+
+fn is_hidden(entry: &DirEntry) -> bool {
+    entry.file_name().to_string_lossy().starts_with('.')
+}
+
+#[test]
+fn test_collect_files_in_assets() {
+    let files = collect_files(Path::new("assets")).expect("Failed to walk assets");
+
+    assert_eq!(files.len(), 5);
+    assert_eq!(files[0], PathBuf::from("assets/test_1.txt"));
+}
+
+#[test]
+fn test_collect_files_single_file() {
+    let file = Path::new("assets/test_2.txt");
+    let files = collect_files(file).expect("Failed to walk file");
+
+    assert_eq!(files, vec![file.to_path_buf()]);
+}
+
+#[test]
+fn test_walk_files_skips_ignored_and_hidden_files() {
+    let root = std::env::temp_dir().join(format!("wcx-walk-{}", std::process::id()));
+    fs::create_dir_all(root.join("target")).expect("Failed to create the tree");
+    for (file, contents) in [
+        (".gitignore", "target/\n"),
+        (".env", "SECRET=1\n"),
+        ("main.rs", "fn main() {}\n"),
+        ("target/main.o", "\0"),
+    ] {
+        fs::write(root.join(file), contents).expect("Failed to create the tree");
+    }
+    let walk = |hidden, respect_gitignore| -> Vec<PathBuf> {
+        let options = WalkOptions {
+            hidden,
+            respect_gitignore,
+            ..Default::default()
+        };
+        let files = walk_files(&root, options).expect("Failed to walk");
+        files
+            .iter()
+            .map(|file| file.strip_prefix(&root).unwrap().to_path_buf())
+            .collect()
+    };
+
+    let (all, ignored) = (walk(true, false), walk(false, true));
+    let with_hidden = walk(true, true);
+    fs::remove_dir_all(&root).expect("Failed to remove the tree");
+
+    assert_eq!(all.len(), 4);
+    assert_eq!(ignored, vec![PathBuf::from("main.rs")]);
+    assert_eq!(
+        with_hidden,
+        vec![
+            PathBuf::from(".env"),
+            PathBuf::from(".gitignore"),
+            PathBuf::from("main.rs")
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_walk_files_follows_symlinks_out_of_loops() {
+    let root = std::env::temp_dir().join(format!("wcx-loop-{}", std::process::id()));
+    fs::create_dir_all(root.join("docs/guide")).expect("Failed to create the tree");
+    fs::write(root.join("docs/guide/intro.md"), "# Intro\n").expect("Failed to create the tree");
+    std::os::unix::fs::symlink("..", root.join("docs/guide/up")).expect("Failed to link");
+    let walk = |max_depth, follow_symlinks| {
+        let options = WalkOptions {
+            max_depth,
+            follow_symlinks,
+            ..Default::default()
+        };
+        walk_files(&root, options).expect("Failed to walk").len()
+    };
+
+    let (followed, shallow, unfollowed) =
+        (walk(None, true), walk(Some(2), true), walk(None, false));
+    fs::remove_dir_all(&root).expect("Failed to remove the tree");
+
+    // `up` points back to `docs`, so it is skipped instead of walking `docs` again.
+    assert_eq!(followed, 1);
+    assert_eq!(shallow, 0);
+    assert_eq!(unfollowed, 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_walk_files_can_skip_unreadable_entries() {
+    let root = std::env::temp_dir().join(format!("wcx-unreadable-{}", std::process::id()));
+    fs::create_dir_all(&root).expect("Failed to create the tree");
+    fs::write(root.join("notes.md"), "notes\n").expect("Failed to create the tree");
+    std::os::unix::fs::symlink("missing", root.join("dangling")).expect("Failed to link");
+    let walk = |skip_unreadable| {
+        let options = WalkOptions {
+            follow_symlinks: true,
+            skip_unreadable,
+            ..Default::default()
+        };
+        walk_files(&root, options).map(|files| files.len())
+    };
+
+    let (skipped, failed) = (walk(true), walk(false));
+    fs::remove_dir_all(&root).expect("Failed to remove the tree");
+
+    assert_eq!(skipped.expect("Failed to walk"), 1);
+    assert!(failed.is_err());
+}
+
+#[test]
+fn test_parse_file_list_skips_comments_and_blank_lines() {
+    let contents = "# generated by make\nsrc/main.rs\n\n  \ndocs/my notes.md\r\n";
+
+    assert_eq!(
+        parse_file_list(contents),
+        vec![
+            PathBuf::from("src/main.rs"),
+            PathBuf::from("docs/my notes.md")
+        ]
+    );
+}
+
+#[test]
+fn test_parse_file0_list_keeps_names_whole() {
+    let contents = b"src/main.rs\0# notes\nfrom make.md\0-\0";
+
+    assert_eq!(
+        parse_file0_list(&contents[..]).expect("Failed to parse"),
+        vec![
+            PathBuf::from("src/main.rs"),
+            PathBuf::from("# notes\nfrom make.md"),
+            PathBuf::from("-")
+        ]
+    );
+    assert!(parse_file0_list(&b"a\0\0b"[..]).is_err());
+}
+
+#[test]
+fn test_expand_globs_matches_in_name_order() {
+    let files =
+        |patterns: &[&str]| -> Vec<PathBuf> { patterns.iter().map(PathBuf::from).collect() };
+
+    assert_eq!(
+        expand_globs(&files(&["assets/test_[12].txt", "README.md"])).expect("Failed to expand"),
+        files(&["assets/test_1.txt", "assets/test_2.txt", "README.md"])
+    );
+    assert!(expand_globs(&files(&["src/**/analyze.rs"]))
+        .expect("Failed to expand")
+        .contains(&PathBuf::from("src/analyze.rs")));
+    assert!(expand_globs(&files(&["assets/*.nothing"])).is_err());
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+/// The whitespace characters of a text by kind, for `--whitespace-stats`. Line endings (`\n` and
+/// `\r`) are left out: they are what the line count counts.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Whitespace {
+    pub spaces: usize,
+    pub tabs: usize,
+    /// No-break spaces: U+00A0, and the figure (U+2007) and narrow (U+202F) ones.
+    pub nbsp: usize,
+    /// Any other Unicode whitespace, such as form feeds or ideographic spaces.
+    pub other: usize,
+}
+
+impl Whitespace {
+    /// Counts the whitespace of `text`.
+    pub fn count(text: &str) -> Whitespace {
+        let mut whitespace = Whitespace::default();
+
+        for char in text.chars().filter(|char| char.is_whitespace()) {
+            match char {
+                ' ' => whitespace.spaces += 1,
+                '\t' => whitespace.tabs += 1,
+                '\u{a0}' | '\u{2007}' | '\u{202f}' => whitespace.nbsp += 1,
+                '\n' | '\r' => {}
+                _ => whitespace.other += 1,
+            }
+        }
+
+        whitespace
+    }
+
+    pub fn add(&mut self, other: &Whitespace) {
+        self.spaces += other.spaces;
+        self.tabs += other.tabs;
+        self.nbsp += other.nbsp;
+        self.other += other.other;
+    }
+}
+
+#[test]
+fn test_count_sorts_whitespace_by_kind() {
+    let whitespace = Whitespace::count("key:\u{a0}value\n\tlist: [a, b]\r\n\u{3000}end\u{c}");
+
+    assert_eq!(
+        whitespace,
+        Whitespace {
+            spaces: 2,
+            tabs: 1,
+            nbsp: 1,
+            other: 2,
+        }
+    );
+}
@@ -0,0 +1,220 @@
+use unicode_width::UnicodeWidthChar;
+
+/// The wrap width of `--logical-lines` without `--wrap-width`.
+pub const DEFAULT_WRAP_WIDTH: usize = 80;
+
+/// The page height of `--print-pages` without `--page-lines`: a US Letter page with one-inch
+/// margins holds 54 lines at six lines an inch.
+pub const DEFAULT_PAGE_LINES: usize = 54;
+
+/// Tab stops are this many columns apart.
+const TAB_WIDTH: usize = 8;
+
+/// Counts the display lines text pushed piece by piece takes up when wrapped at `width`
+/// columns, like a terminal does: a line wraps at the character that would run past the edge,
+/// and wide characters (such as CJK ones) take two columns. A blank line takes one display line.
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayLines {
+    width: usize,
+    /// The column the line read so far ends at, on its last display line.
+    column: usize,
+    /// Whether any text follows the last line break.
+    in_line: bool,
+    lines: usize,
+}
+
+impl DisplayLines {
+    pub fn new(width: usize) -> DisplayLines {
+        DisplayLines {
+            width: width.max(1),
+            column: 0,
+            in_line: false,
+            lines: 0,
+        }
+    }
+
+    pub fn push(&mut self, text: &str) {
+        for char in text.chars() {
+            let width = match char {
+                '\n' => {
+                    self.lines += 1;
+                    self.column = 0;
+                    self.in_line = false;
+                    continue;
+                }
+                '\t' => TAB_WIDTH - self.column % TAB_WIDTH,
+                char => char.width().unwrap_or(0),
+            };
+
+            self.in_line = true;
+            if self.column > 0 && self.column + width > self.width {
+                self.lines += 1;
+                self.column = 0;
+            }
+            self.column += width;
+        }
+    }
+
+    /// Returns the display lines of everything pushed, the last line included when it does not
+    /// end in a line break.
+    pub fn finish(self) -> usize {
+        self.lines + usize::from(self.in_line)
+    }
+}
+
+/// Measures the longest line of text pushed piece by piece in display columns, like `wc -L`:
+/// tabs stop every 8 columns, wide characters take two, and a `\r` or form feed ends a line
+/// like a `\n` does.
+#[derive(Clone, Debug, Default)]
+pub struct LongestLine {
+    /// The width of the line read so far.
+    column: usize,
+    longest: usize,
+    /// The start of a character cut off at the end of the bytes pushed last.
+    partial: Vec<u8>,
+}
+
+impl LongestLine {
+    pub fn push(&mut self, text: &str) {
+        for char in text.chars() {
+            match char {
+                '\n' | '\r' | '\x0c' => {
+                    self.longest = self.longest.max(self.column);
+                    self.column = 0;
+                }
+                '\t' => self.column += TAB_WIDTH - self.column % TAB_WIDTH,
+                char => self.column += char.width().unwrap_or(0),
+            }
+        }
+    }
+
+    /// Like [`LongestLine::push`], for bytes that need not be UTF-8. Invalid bytes take no
+    /// columns, as in `wc -L`, so binary and Latin-1 files are measured instead of failing.
+    pub fn push_bytes(&mut self, bytes: &[u8]) {
+        let joined;
+        let mut bytes = match self.partial.is_empty() {
+            true => bytes,
+            false => {
+                joined = [std::mem::take(&mut self.partial).as_slice(), bytes].concat();
+                joined.as_slice()
+            }
+        };
+
+        loop {
+            match std::str::from_utf8(bytes) {
+                Ok(text) => {
+                    self.push(text);
+                    return;
+                }
+                Err(err) => {
+                    let (valid, rest) = bytes.split_at(err.valid_up_to());
+                    // SAFETY: `from_utf8` checked the bytes up to `valid_up_to`.
+                    self.push(unsafe { std::str::from_utf8_unchecked(valid) });
+                    match err.error_len() {
+                        None => {
+                            self.partial = rest.to_vec();
+                            return;
+                        }
+                        Some(len) => bytes = &rest[len..],
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the width of the longest line in everything pushed.
+    pub fn finish(self) -> usize {
+        self.longest.max(self.column)
+    }
+}
+
+/// Measures the longest line of `text`; see [`LongestLine`].
+pub fn longest_line(text: &str) -> usize {
+    let mut longest_line = LongestLine::default();
+    longest_line.push(text);
+    longest_line.finish()
+}
+
+/// Measures the longest line of `content`, which need not be UTF-8; see
+/// [`LongestLine::push_bytes`].
+pub fn longest_line_bytes(content: &[u8]) -> usize {
+    let mut longest_line = LongestLine::default();
+    longest_line.push_bytes(content);
+    longest_line.finish()
+}
+
+/// The printed page of `--print-pages`: how many lines fit on it, and how many columns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PageSize {
+    pub lines: usize,
+    pub width: usize,
+}
+
+impl Default for PageSize {
+    fn default() -> PageSize {
+        PageSize {
+            lines: DEFAULT_PAGE_LINES,
+            width: DEFAULT_WRAP_WIDTH,
+        }
+    }
+}
+
+impl PageSize {
+    /// Returns the pages `display_lines` lines wrapped at the page width fill. Every file starts
+    /// on a new page, so a file's last page counts even when it is nearly empty.
+    pub fn pages(&self, display_lines: usize) -> usize {
+        display_lines.div_ceil(self.lines.max(1))
+    }
+}
+
+/// Counts the display lines of `text` at `width` columns; see [`DisplayLines`].
+pub fn count(text: &str, width: usize) -> usize {
+    let mut display_lines = DisplayLines::new(width);
+    display_lines.push(text);
+    display_lines.finish()
+}
+
+#[test]
+fn test_long_and_wide_lines_wrap() {
+    let long = "a".repeat(25);
+    assert_eq!(count(&format!("{}\n\nshort\n", long), 10), 5);
+    assert_eq!(count("漢字漢字漢字", 5), 3);
+    assert_eq!(count("\tindented\ttext", 16), 2);
+    assert_eq!(count("no newline", 80), 1);
+    assert_eq!(count("", 80), 0);
+
+    // A line split between two pushes wraps as one line.
+    let mut display_lines = DisplayLines::new(4);
+    display_lines.push("abc");
+    display_lines.push("de\n");
+    assert_eq!(display_lines.finish(), 2);
+}
+
+#[test]
+fn test_longest_line_is_measured_in_columns() {
+    assert_eq!(longest_line("short\nthe longest line\r\nend"), 16);
+    assert_eq!(longest_line("\tab\n"), 10);
+    assert_eq!(longest_line("漢字\x0cabc"), 4);
+    assert_eq!(longest_line(""), 0);
+}
+
+#[test]
+fn test_longest_line_skips_invalid_bytes() {
+    assert_eq!(longest_line_bytes(b"caf\xe9\nab\n"), 3);
+
+    let mut longest_line = LongestLine::default();
+    longest_line.push_bytes(b"\xe6\xbc");
+    longest_line.push_bytes(b"\xa2\xe5\xad\x97\n");
+    assert_eq!(longest_line.finish(), 4);
+}
+
+#[test]
+fn test_pages_round_up() {
+    let page_size = PageSize {
+        lines: 54,
+        width: 80,
+    };
+    assert_eq!(page_size.pages(0), 0);
+    assert_eq!(page_size.pages(54), 1);
+    assert_eq!(page_size.pages(55), 2);
+}
@@ -0,0 +1,85 @@
+//! Golden snapshot tests of the table output in every `--format`. Regenerate the snapshots in
+//! `tests/golden/` after an intended output change with `WCX_UPDATE_GOLDEN=1 cargo test`.
+
+use std::fs::{read_to_string, write};
+use std::path::PathBuf;
+use std::process::Command;
+
+const FORMATS: [&str; 10] = [
+    "default",
+    "no_title",
+    "no_linesep_with_title",
+    "no_linesep",
+    "no_colsep",
+    "format_clean",
+    "borders_only",
+    "no_border",
+    "no_border_line_separator",
+    "box_chars",
+];
+
+fn wcx(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_wcx"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env(
+            "WCX_STATE_DIR",
+            std::env::temp_dir().join("wcx-golden-state"),
+        )
+        .output()
+        .expect("Failed to run wcx");
+
+    String::from_utf8(output.stdout).expect("wcx printed invalid UTF-8")
+}
+
+fn assert_golden(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.txt", name));
+
+    if std::env::var_os("WCX_UPDATE_GOLDEN").is_some() {
+        write(&path, actual).expect("Failed to write snapshot");
+        return;
+    }
+
+    let expected =
+        read_to_string(&path).unwrap_or_else(|_| panic!("Missing snapshot {}", path.display()));
+    assert_eq!(actual, expected, "output differs from {}", path.display());
+}
+
+#[test]
+fn test_every_format_matches_its_snapshot() {
+    for format in FORMATS {
+        let output = wcx(&["-f", format, "assets/test_1.txt", "assets/test_4.txt"]);
+        assert_golden(format, &output);
+    }
+}
+
+#[test]
+fn test_machine_outputs_are_deterministic() {
+    for output in ["csv", "json", "markdown"] {
+        let actual = wcx(&[
+            "-o",
+            output,
+            "--deterministic",
+            "assets/test_4.txt",
+            "./assets/test_1.txt",
+        ]);
+        assert_golden(&format!("output_{}", output), &actual);
+    }
+}
+
+#[test]
+fn test_pivoted_outputs_match_their_snapshots() {
+    for output in ["table", "csv", "markdown"] {
+        let actual = wcx(&[
+            "--pivot",
+            "-o",
+            output,
+            "--deterministic",
+            "assets/test_1.txt",
+            "assets/test_4.txt",
+        ]);
+        assert_golden(&format!("pivot_{}", output), &actual);
+    }
+}
@@ -0,0 +1,93 @@
+//! Benchmarks each counter over small, medium and huge synthetic inputs of ASCII prose, CJK
+//! text and binary-ish bytes, and line counting against the byte-by-byte scan it replaced. Run
+//! with `cargo bench -p wcx-core`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::hint::black_box;
+
+const SIZES: [(&str, usize); 3] = [
+    ("small", 4 * 1024),
+    ("medium", 1024 * 1024),
+    ("huge", 32 * 1024 * 1024),
+];
+
+/// Repeats `unit` until the text is at least `size` bytes long.
+fn repeat_to(unit: &str, size: usize) -> String {
+    unit.repeat(size / unit.len() + 1)
+}
+
+fn ascii(size: usize) -> String {
+    repeat_to(
+        "The quick brown fox jumps over the lazy dog, again and again.\n\tIndented line.\n\n",
+        size,
+    )
+}
+
+fn cjk(size: usize) -> String {
+    repeat_to(
+        "漢字かな交じり文、全角スペース\u{3000}も含む。\n日本語の文章です。\n",
+        size,
+    )
+}
+
+/// Counts lines one byte at a time, the way `count_lines` did before it used bytecount.
+fn count_lines_bytewise(content: &[u8]) -> usize {
+    let newlines = content.iter().filter(|&&byte| byte == b'\n').count();
+    newlines + usize::from(content.last().is_some_and(|&last| last != b'\n'))
+}
+
+/// Pseudo-random bytes from a fixed-seed xorshift, with the occasional newline. Mostly not
+/// valid UTF-8, so only the byte-level counters apply.
+fn binary(size: usize) -> Vec<u8> {
+    let mut state: u32 = 0x9e37_79b9;
+    (0..size)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+fn bench_counters(c: &mut Criterion) {
+    for (size_name, size) in SIZES {
+        let mut group = c.benchmark_group(size_name);
+        group.throughput(Throughput::Bytes(size as u64));
+        if size > 1024 * 1024 {
+            group.sample_size(10);
+        }
+
+        for (kind, text) in [("ascii", ascii(size)), ("cjk", cjk(size))] {
+            group.bench_with_input(BenchmarkId::new("lines", kind), &text, |b, text| {
+                b.iter(|| wcx_core::count_lines(black_box(text.as_bytes())))
+            });
+            group.bench_with_input(
+                BenchmarkId::new("lines-bytewise", kind),
+                &text,
+                |b, text| b.iter(|| count_lines_bytewise(black_box(text.as_bytes()))),
+            );
+            group.bench_with_input(BenchmarkId::new("chars", kind), &text, |b, text| {
+                b.iter(|| wcx_core::count_chars(black_box(text)))
+            });
+            group.bench_with_input(BenchmarkId::new("words", kind), &text, |b, text| {
+                b.iter(|| wcx_core::count_words(black_box(text)))
+            });
+        }
+
+        let bytes = binary(size);
+        group.bench_with_input(BenchmarkId::new("lines", "binary"), &bytes, |b, bytes| {
+            b.iter(|| wcx_core::count_lines(black_box(bytes)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("lines-bytewise", "binary"),
+            &bytes,
+            |b, bytes| b.iter(|| count_lines_bytewise(black_box(bytes))),
+        );
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_counters);
+criterion_main!(benches);
@@ -0,0 +1,83 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::string::{String, ToString};
+
+/// Why a file could not be counted. Callers can match on the kind of failure; wcx uses it to
+/// name the file and suggest what to do about it.
+#[derive(Debug)]
+pub enum WcxError {
+    /// The file could not be opened or read.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// Characters or words were asked for, but the content is not valid UTF-8.
+    Decode {
+        name: String,
+        source: core::str::Utf8Error,
+    },
+    /// The input cannot be counted at all, such as a file larger than this platform can address.
+    UnsupportedInput { name: String, reason: String },
+    /// Counting was cancelled after `done` of `total` files.
+    Cancelled { done: usize, total: usize },
+}
+
+impl WcxError {
+    /// Wraps an I/O error about `path`, for use with `map_err`.
+    pub fn io(path: &Path) -> impl FnOnce(std::io::Error) -> WcxError + '_ {
+        |source| WcxError::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for WcxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WcxError::Io { path, .. } => write!(f, "Failed to read {}", display_path(path)),
+            WcxError::Decode { name, .. } => write!(f, "{} is not valid UTF-8", name),
+            WcxError::UnsupportedInput { name, reason } => write!(f, "{} {}", name, reason),
+            WcxError::Cancelled { done, total } => {
+                write!(f, "Cancelled after counting {} of {} files", done, total)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WcxError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WcxError::Io { source, .. } => Some(source),
+            WcxError::Decode { source, .. } => Some(source),
+            WcxError::UnsupportedInput { .. } | WcxError::Cancelled { .. } => None,
+        }
+    }
+}
+
+/// Formats a path for output. Windows verbatim prefixes are dropped, so `\\?\C:\notes.md` shows
+/// as `C:\notes.md` and `\\?\UNC\server\share\notes.md` as `\\server\share\notes.md`.
+///
+/// Opening long paths (over 260 characters) needs no special handling here: the standard library
+/// adds the verbatim prefix itself when a path is too long for the legacy Windows APIs.
+pub fn display_path(path: &Path) -> String {
+    let shown = path.display().to_string();
+
+    if let Some(share) = shown.strip_prefix(r"\\?\UNC\") {
+        std::format!(r"\\{}", share)
+    } else if let Some(local) = shown.strip_prefix(r"\\?\") {
+        local.to_string()
+    } else {
+        shown
+    }
+}
+
+#[test]
+fn test_display_path_drops_verbatim_prefixes() {
+    assert_eq!(display_path(Path::new(r"\\?\C:\notes.md")), r"C:\notes.md");
+    assert_eq!(
+        display_path(Path::new(r"\\?\UNC\server\share\notes.md")),
+        r"\\server\share\notes.md"
+    );
+    assert_eq!(display_path(Path::new("src/main.rs")), "src/main.rs");
+}
@@ -0,0 +1,118 @@
+//! The counting algorithms behind wcx, as pure functions over byte and string slices.
+//!
+//! This crate is `no_std` and does no I/O, so it can be used from embedded and wasm contexts.
+//! Reading files, decoding them, and deciding what to count is left to the caller. With the
+//! `std` feature it also has [`WcxError`], the ways counting a file can fail.
+#![no_std]
+
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "std")]
+mod error;
+
+#[cfg(feature = "std")]
+pub use error::WcxError;
+
+// Shared with the wcx binary so its messages show paths the way `WcxError` does. Not part of the
+// stable API: it may change or go away in any release.
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub use error::display_path;
+
+/// Counts the lines in `content`: every `\n`, plus a final line that has content but no
+/// terminating newline.
+pub fn count_lines(content: &[u8]) -> usize {
+    count_records(content, b'\n')
+}
+
+/// Counts the records in `content` separated by `delimiter`: every delimiter, plus a final
+/// record that has content but no delimiter after it.
+pub fn count_records(content: &[u8], delimiter: u8) -> usize {
+    let delimiters = count_byte(content, delimiter);
+
+    match content.last() {
+        Some(&last) if last != delimiter => delimiters + 1,
+        _ => delimiters,
+    }
+}
+
+/// Counts the `\n` bytes in `content`.
+pub fn count_newlines(content: &[u8]) -> usize {
+    count_byte(content, b'\n')
+}
+
+/// Counts the occurrences of `byte` in `content`, many bytes at a time, so counting lines in a
+/// large file is bound by reading it rather than by the scan.
+pub fn count_byte(content: &[u8], byte: u8) -> usize {
+    bytecount::count(content, byte)
+}
+
+/// Counts the Unicode scalar values in `text`.
+pub fn count_chars(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Counts the words in `text`: runs of characters separated by Unicode whitespace.
+pub fn count_words(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+/// Counts the words in `text` that contain at least one alphanumeric character, so stray
+/// punctuation and separators like `---` or `***` are not counted.
+pub fn count_strict_words(text: &str) -> usize {
+    text.split_whitespace()
+        .filter(|token| is_strict_word(token))
+        .count()
+}
+
+/// Whether a whitespace-separated token counts as a word in strict mode: it has at least one
+/// alphanumeric character.
+pub fn is_strict_word(token: &str) -> bool {
+    token.chars().any(char::is_alphanumeric)
+}
+
+#[test]
+fn test_count_lines_counts_an_unterminated_last_line() {
+    assert_eq!(count_lines(b""), 0);
+    assert_eq!(count_lines(b"one\ntwo\n"), 2);
+    assert_eq!(count_lines(b"one\ntwo"), 2);
+    assert_eq!(count_lines(b"\n\n"), 2);
+}
+
+#[test]
+fn test_count_byte_matches_a_byte_by_byte_scan() {
+    // Lengths around the SIMD block sizes, with the byte at unaligned offsets.
+    let content: [u8; 300] = core::array::from_fn(|index| match index % 7 {
+        0 | 3 => b'\n',
+        _ => b'a' + (index % 26) as u8,
+    });
+    for start in 0..4 {
+        for end in [start, 15, 16, 17, 31, 32, 33, 64, 65, 255, 256, 257, 300] {
+            let slice = &content[start..end.max(start)];
+            let expected = slice.iter().filter(|&&byte| byte == b'\n').count();
+            assert_eq!(count_byte(slice, b'\n'), expected);
+        }
+    }
+}
+
+#[test]
+fn test_count_records_splits_on_the_delimiter() {
+    assert_eq!(count_records(b"a\0b\0", b'\0'), 2);
+    assert_eq!(count_records(b"a\0b", b'\0'), 2);
+    assert_eq!(count_records(b"", b'\0'), 0);
+}
+
+#[test]
+fn test_count_words_splits_on_unicode_whitespace() {
+    assert_eq!(count_words("héllo\u{3000}wörld \t\n!"), 3);
+    assert_eq!(count_chars("héllo"), 5);
+}
+
+#[test]
+fn test_count_strict_words_skips_punctuation_tokens() {
+    assert_eq!(
+        count_strict_words("# Title\n\n---\n\nSome *text* — ok? ***"),
+        4
+    );
+}
@@ -0,0 +1,74 @@
+//! Compares the counters with GNU `wc` on generated inputs. Skipped when no GNU `wc` is on the
+//! PATH (BSD `wc` counts characters and words slightly differently).
+
+use proptest::prelude::*;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Characters both wcx and GNU wc agree on: ASCII, the whitespace they both recognise, and a
+/// few multi-byte letters. No-break spaces are left out on purpose, since GNU wc does not treat
+/// them as word separators.
+const ALPHABET: &[char] = &[
+    'a', 'b', 'Z', '0', '.', '-', ' ', ' ', '\t', '\n', '\n', '\r', '\u{b}', '\u{c}', 'é', 'ß',
+    'Ж', '漢', '字', '😀',
+];
+
+/// Runs `wc -l -w -m -c` over `content` and returns (newlines, words, chars, bytes).
+fn gnu_wc(content: &[u8]) -> Option<(usize, usize, usize, usize)> {
+    let mut child = Command::new("wc")
+        .args(["-l", "-w", "-m", "-c"])
+        .env("LC_ALL", "C.UTF-8")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .ok()?;
+    child.stdin.take()?.write_all(content).ok()?;
+    let output = child.wait_with_output().ok()?;
+
+    let counts: Vec<usize> = String::from_utf8(output.stdout)
+        .ok()?
+        .split_whitespace()
+        .map(|count| count.parse().ok())
+        .collect::<Option<_>>()?;
+
+    match counts[..] {
+        [lines, words, chars, bytes] => Some((lines, words, chars, bytes)),
+        _ => None,
+    }
+}
+
+fn gnu_wc_available() -> bool {
+    Command::new("wc")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains("GNU"))
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn counters_match_gnu_wc(text in prop::collection::vec(prop::sample::select(ALPHABET), 0..200)) {
+        if !gnu_wc_available() {
+            return Ok(());
+        }
+
+        let text: String = text.into_iter().collect();
+        let (newlines, words, chars, bytes) = gnu_wc(text.as_bytes()).expect("Failed to run wc");
+
+        prop_assert_eq!(wcx_core::count_newlines(text.as_bytes()), newlines);
+        prop_assert_eq!(wcx_core::count_words(&text), words);
+        prop_assert_eq!(wcx_core::count_chars(&text), chars);
+        prop_assert_eq!(text.len(), bytes);
+
+        // count_lines also counts a last line without a trailing newline, which wc leaves out.
+        let unterminated = usize::from(!text.is_empty() && !text.ends_with('\n'));
+        prop_assert_eq!(wcx_core::count_lines(text.as_bytes()), newlines + unterminated);
+    }
+
+    #[test]
+    fn counters_do_not_panic_on_arbitrary_bytes(content in prop::collection::vec(any::<u8>(), 0..512)) {
+        let lines = wcx_core::count_lines(&content);
+        prop_assert!(lines <= content.len());
+    }
+}
@@ -0,0 +1,18 @@
+//! Generates the C header from the `extern "C"` functions in `src/lib.rs` into `OUT_DIR`, where
+//! `test_header_is_up_to_date` compares it with the checked-in `include/wcx.h`. The checkout is
+//! never written to by a build; `WCX_UPDATE_HEADER=1 cargo test -p wcx-ffi` refreshes it.
+
+fn main() {
+    let crate_dir =
+        std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR is set by cargo");
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+        .expect("Failed to read cbindgen.toml");
+
+    cbindgen::generate_with_config(&crate_dir, config)
+        .expect("Failed to generate the C header")
+        .write_to_file(format!("{}/wcx.h", out_dir));
+
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+}
@@ -0,0 +1,76 @@
+/* Generated by cbindgen from wcx-ffi/src/lib.rs. Do not edit. */
+
+#ifndef WCX_H
+#define WCX_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Which count [`wcx_result_get`] returns. C passes it as a plain integer, so values outside
+ * these are rejected rather than trusted.
+ */
+typedef enum WcxMetric {
+  WCX_METRIC_LINES = 0,
+  WCX_METRIC_WORDS = 1,
+  WCX_METRIC_CHARS = 2,
+  WCX_METRIC_BYTES = 3,
+} WcxMetric;
+
+/**
+ * The counts of one file. Opaque to C: only ever handled through a pointer.
+ */
+typedef struct WcxResult WcxResult;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Counts the lines, words, characters and bytes of the file at `path`, a NUL-terminated UTF-8
+ * string. Lines are counted like `wcx -l` counts them, as newlines, so a last line without one
+ * is not counted. Returns NULL if the file cannot be read; [`wcx_last_error`] then says why.
+ * The result must be released with [`wcx_result_free`].
+ *
+ * # Safety
+ *
+ * `path` must be NULL or point to a NUL-terminated string.
+ */
+struct WcxResult *wcx_count_path(const char *path);
+
+/**
+ * Writes one count of `result`, a [`WcxMetric`], to `out`. Returns false, leaving `out`
+ * alone, when the count is not available: words and characters of files that are not valid
+ * UTF-8. Also returns false when `metric` is not a [`WcxMetric`]; [`wcx_last_error`] then says
+ * so.
+ *
+ * # Safety
+ *
+ * `result` must come from [`wcx_count_path`] and not be freed yet; `out` must be writable.
+ */
+bool wcx_result_get(const struct WcxResult *result, uint32_t metric, size_t *out);
+
+/**
+ * Releases a result of [`wcx_count_path`]. Does nothing with NULL.
+ *
+ * # Safety
+ *
+ * `result` must come from [`wcx_count_path`] and not be freed already.
+ */
+void wcx_result_free(struct WcxResult *result);
+
+/**
+ * Returns why the last call to [`wcx_count_path`] (or [`wcx_result_get`] with an unknown
+ * metric) on this thread failed, or NULL if none did.
+ * The message stays valid until the next failing call on the same thread.
+ */
+const char *wcx_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* WCX_H */
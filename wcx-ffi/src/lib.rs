@@ -0,0 +1,227 @@
+//! A small C API over the `wcx-core` counters, so editors and other languages (Python through
+//! ctypes, Node through ffi-napi, ...) can count files the way wcx does.
+//!
+//! `include/wcx.h` is generated from this file by cbindgen; see `build.rs`. A count is taken with
+//! [`wcx_count_path`], read with [`wcx_result_get`] and released with [`wcx_result_free`]; when
+//! counting fails, [`wcx_last_error`] says why.
+
+#[cfg(feature = "python")]
+mod python;
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::path::Path;
+use std::ptr;
+
+/// The counts of one file. Opaque to C: only ever handled through a pointer.
+pub struct WcxResult {
+    lines: usize,
+    bytes: usize,
+    /// Words and characters are only counted in valid UTF-8.
+    words: Option<usize>,
+    chars: Option<usize>,
+}
+
+/// Which count [`wcx_result_get`] returns. C passes it as a plain integer, so values outside
+/// these are rejected rather than trusted.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WcxMetric {
+    Lines = 0,
+    Words = 1,
+    Chars = 2,
+    Bytes = 3,
+}
+
+impl TryFrom<u32> for WcxMetric {
+    type Error = u32;
+
+    fn try_from(metric: u32) -> Result<WcxMetric, u32> {
+        match metric {
+            0 => Ok(WcxMetric::Lines),
+            1 => Ok(WcxMetric::Words),
+            2 => Ok(WcxMetric::Chars),
+            3 => Ok(WcxMetric::Bytes),
+            metric => Err(metric),
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    // Interior NUL bytes cannot appear in a C string, so they are dropped from the message.
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+impl WcxResult {
+    fn new(content: &[u8]) -> WcxResult {
+        let text = std::str::from_utf8(content).ok();
+        WcxResult {
+            lines: wcx_core::count_newlines(content),
+            bytes: content.len(),
+            words: text.map(wcx_core::count_words),
+            chars: text.map(wcx_core::count_chars),
+        }
+    }
+
+    fn get(&self, metric: WcxMetric) -> Option<usize> {
+        match metric {
+            WcxMetric::Lines => Some(self.lines),
+            WcxMetric::Words => self.words,
+            WcxMetric::Chars => self.chars,
+            WcxMetric::Bytes => Some(self.bytes),
+        }
+    }
+}
+
+/// Counts the lines, words, characters and bytes of the file at `path`, a NUL-terminated UTF-8
+/// string. Lines are counted like `wcx -l` counts them, as newlines, so a last line without one
+/// is not counted. Returns NULL if the file cannot be read; [`wcx_last_error`] then says why.
+/// The result must be released with [`wcx_result_free`].
+///
+/// # Safety
+///
+/// `path` must be NULL or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn wcx_count_path(path: *const c_char) -> *mut WcxResult {
+    if path.is_null() {
+        set_last_error(String::from("path is NULL"));
+        return ptr::null_mut();
+    }
+
+    // SAFETY: the caller passes a NUL-terminated string.
+    let path = match unsafe { CStr::from_ptr(path) }.to_str() {
+        Ok(path) => Path::new(path),
+        Err(_) => {
+            set_last_error(String::from("path is not valid UTF-8"));
+            return ptr::null_mut();
+        }
+    };
+
+    match std::fs::read(path) {
+        Ok(content) => Box::into_raw(Box::new(WcxResult::new(&content))),
+        Err(err) => {
+            set_last_error(format!("Failed to read {}: {}", path.display(), err));
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Writes one count of `result`, a [`WcxMetric`], to `out`. Returns false, leaving `out`
+/// alone, when the count is not available: words and characters of files that are not valid
+/// UTF-8. Also returns false when `metric` is not a [`WcxMetric`]; [`wcx_last_error`] then says
+/// so.
+///
+/// # Safety
+///
+/// `result` must come from [`wcx_count_path`] and not be freed yet; `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn wcx_result_get(
+    result: *const WcxResult,
+    metric: u32,
+    out: *mut usize,
+) -> bool {
+    if result.is_null() || out.is_null() {
+        return false;
+    }
+    let metric = match WcxMetric::try_from(metric) {
+        Ok(metric) => metric,
+        Err(metric) => {
+            set_last_error(format!("Unknown metric {}", metric));
+            return false;
+        }
+    };
+
+    // SAFETY: the caller passes a live result and a writable `out`.
+    match unsafe { &*result }.get(metric) {
+        Some(count) => {
+            unsafe { *out = count };
+            true
+        }
+        None => false,
+    }
+}
+
+/// Releases a result of [`wcx_count_path`]. Does nothing with NULL.
+///
+/// # Safety
+///
+/// `result` must come from [`wcx_count_path`] and not be freed already.
+#[no_mangle]
+pub unsafe extern "C" fn wcx_result_free(result: *mut WcxResult) {
+    if !result.is_null() {
+        // SAFETY: the result was boxed by `wcx_count_path` and is freed only once.
+        drop(unsafe { Box::from_raw(result) });
+    }
+}
+
+/// Returns why the last call to [`wcx_count_path`] (or [`wcx_result_get`] with an unknown
+/// metric) on this thread failed, or NULL if none did.
+/// The message stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn wcx_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+#[test]
+fn test_count_path_and_get() {
+    let path = CString::new("../assets/test_1.txt").unwrap();
+    let result = unsafe { wcx_count_path(path.as_ptr()) };
+    assert!(!result.is_null());
+
+    let mut bytes = 0;
+    assert!(unsafe { wcx_result_get(result, WcxMetric::Bytes as u32, &mut bytes) });
+    assert_eq!(bytes, 449);
+
+    assert!(!unsafe { wcx_result_get(result, 7, &mut bytes) });
+    let error = unsafe { CStr::from_ptr(wcx_last_error()) };
+    assert_eq!(error.to_str().unwrap(), "Unknown metric 7");
+
+    unsafe { wcx_result_free(result) };
+}
+
+#[test]
+fn test_count_path_reports_errors() {
+    let path = CString::new("../assets/missing.txt").unwrap();
+    assert!(unsafe { wcx_count_path(path.as_ptr()) }.is_null());
+
+    let error = unsafe { CStr::from_ptr(wcx_last_error()) }
+        .to_str()
+        .unwrap();
+    assert!(error.starts_with("Failed to read ../assets/missing.txt: "));
+
+    let binary = WcxResult::new(b"\xff\n");
+    assert_eq!(binary.get(WcxMetric::Lines), Some(1));
+    assert_eq!(binary.get(WcxMetric::Words), None);
+}
+
+#[test]
+fn test_lines_are_counted_like_the_cli() {
+    let unterminated = WcxResult::new(b"one\ntwo");
+    assert_eq!(unterminated.get(WcxMetric::Lines), Some(1));
+}
+
+#[test]
+fn test_header_is_up_to_date() {
+    let generated = include_str!(concat!(env!("OUT_DIR"), "/wcx.h"));
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("include/wcx.h");
+
+    if std::env::var_os("WCX_UPDATE_HEADER").is_some() {
+        std::fs::write(&path, generated).expect("Failed to write the header");
+        return;
+    }
+
+    let checked_in = std::fs::read_to_string(&path).expect("Failed to read the header");
+    assert_eq!(
+        checked_in, generated,
+        "include/wcx.h is stale; run WCX_UPDATE_HEADER=1 cargo test -p wcx-ffi"
+    );
+}
@@ -0,0 +1,154 @@
+//! The `wcx` Python module, built with maturin (see `pyproject.toml`):
+//!
+//! ```python
+//! import wcx
+//! wcx.count("notes.md")                      # {'lines': 12, 'words': 340, 'chars': 1980, ...}
+//! wcx.count("notes.md", counters=["words"])  # {'words': 340}
+//! wcx.count_many(["a.md", "b.md"])           # one dict per path, counted in parallel
+//! ```
+
+use crate::{WcxMetric, WcxResult};
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{IntoPyDict, PyDict};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// Counts by counter name, in the order the counters were asked for.
+type Counts = Vec<(&'static str, usize)>;
+
+const COUNTERS: [(&str, WcxMetric); 4] = [
+    ("lines", WcxMetric::Lines),
+    ("words", WcxMetric::Words),
+    ("chars", WcxMetric::Chars),
+    ("bytes", WcxMetric::Bytes),
+];
+
+/// Why a path could not be counted, raised as the matching Python exception.
+enum CountError {
+    Read(String),
+    Invalid(String),
+}
+
+impl From<CountError> for PyErr {
+    fn from(err: CountError) -> PyErr {
+        match err {
+            CountError::Read(message) => PyOSError::new_err(message),
+            CountError::Invalid(message) => PyValueError::new_err(message),
+        }
+    }
+}
+
+/// Returns the metrics named by `counters`, all four when there are none.
+fn metrics(counters: Option<Vec<String>>) -> Result<Vec<(&'static str, WcxMetric)>, CountError> {
+    let Some(counters) = counters else {
+        return Ok(COUNTERS.to_vec());
+    };
+
+    counters
+        .iter()
+        .map(|name| {
+            COUNTERS
+                .iter()
+                .find(|(counter, _)| counter == name)
+                .copied()
+                .ok_or_else(|| {
+                    CountError::Invalid(format!(
+                        "Unknown counter `{}` (counters: lines, words, chars, bytes)",
+                        name
+                    ))
+                })
+        })
+        .collect()
+}
+
+fn count_path(path: &Path, metrics: &[(&'static str, WcxMetric)]) -> Result<Counts, CountError> {
+    let content = std::fs::read(path)
+        .map_err(|err| CountError::Read(format!("Failed to read {}: {}", path.display(), err)))?;
+    let result = WcxResult::new(&content);
+
+    metrics
+        .iter()
+        .map(|(name, metric)| {
+            let count = result.get(*metric).ok_or_else(|| {
+                CountError::Invalid(format!(
+                    "{} is not valid UTF-8, so it has no {} count",
+                    path.display(),
+                    name
+                ))
+            })?;
+            Ok((*name, count))
+        })
+        .collect()
+}
+
+/// Counts the file at `path`, returning a dict of the `counters` asked for (all by default).
+#[pyfunction]
+#[pyo3(signature = (path, counters = None))]
+fn count(
+    py: Python<'_>,
+    path: PathBuf,
+    counters: Option<Vec<String>>,
+) -> PyResult<Bound<'_, PyDict>> {
+    let metrics = metrics(counters)?;
+    py.detach(|| count_path(&path, &metrics))?.into_py_dict(py)
+}
+
+/// Counts every path like [`count`], spread over one thread per CPU, in the order given.
+#[pyfunction]
+#[pyo3(signature = (paths, counters = None))]
+fn count_many(
+    py: Python<'_>,
+    paths: Vec<PathBuf>,
+    counters: Option<Vec<String>>,
+) -> PyResult<Vec<Bound<'_, PyDict>>> {
+    let metrics = metrics(counters)?;
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let chunk_size = paths.len().div_ceil(threads).max(1);
+
+    let counted = py.detach(|| {
+        thread::scope(|scope| {
+            let workers: Vec<_> = paths
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    let metrics = &metrics;
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|path| count_path(path, metrics))
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("Counting thread panicked"))
+                .collect::<Result<Vec<_>, _>>()
+        })
+    })?;
+
+    counted
+        .into_iter()
+        .flatten()
+        .map(|counts| counts.into_py_dict(py))
+        .collect()
+}
+
+#[pymodule]
+fn wcx(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(count, module)?)?;
+    module.add_function(wrap_pyfunction!(count_many, module)?)?;
+    Ok(())
+}
+
+#[test]
+fn test_count_path_selects_counters() {
+    let bytes = metrics(Some(vec![String::from("bytes")])).ok().unwrap();
+    let counts = count_path(Path::new("../assets/test_1.txt"), &bytes)
+        .ok()
+        .unwrap();
+
+    assert_eq!(counts, vec![("bytes", 449)]);
+    assert!(metrics(Some(vec![String::from("pages")])).is_err());
+}